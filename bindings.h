@@ -6,8 +6,124 @@
 
 #define DOCUMENT_BUDGET_BYTES 50000000
 
+/**
+ * Any failure without a more specific code.
+ */
+#define ERROR_INTERNAL 1
+
+#define ERROR_NULL_POINTER 2
+
+#define ERROR_INVALID_UTF8 3
+
+/**
+ * An option, value or request of the host is invalid.
+ */
+#define ERROR_INVALID_ARGUMENT 4
+
+#define ERROR_QUERY_PARSE 5
+
+#define ERROR_UNKNOWN_FIELD 6
+
+/**
+ * The query text of a field produced no terms, e.g. only stop words.
+ */
+#define ERROR_NO_TERMS 7
+
+/**
+ * A document does not match the schema or the batch it is part of.
+ */
+#define ERROR_INVALID_DOCUMENT 8
+
+#define ERROR_INDEX_CORRUPTED 9
+
+/**
+ * The index is locked by another writer.
+ */
+#define ERROR_LOCK_BUSY 10
+
+#define ERROR_IO 11
+
+/**
+ * The call panicked, which poisoned its context.
+ */
+#define ERROR_PANIC 12
+
+/**
+ * The context is unusable since an earlier call on it panicked.
+ */
+#define ERROR_CONTEXT_POISONED 13
+
+/**
+ * Precision of the indexed and fast values of a date field, the tantivy default.
+ */
+#define DATE_PRECISION_SECONDS 0
+
+#define DATE_PRECISION_MILLISECONDS 1
+
+#define DATE_PRECISION_MICROSECONDS 2
+
+#define DATE_PRECISION_NANOSECONDS 3
+
+/**
+ * Type codes of fast field values, `FAST_FIELD_TYPE_NONE` for documents without a value.
+ */
+#define FAST_FIELD_TYPE_NONE 0
+
+#define FAST_FIELD_TYPE_STR 1
+
+#define FAST_FIELD_TYPE_U64 2
+
+#define FAST_FIELD_TYPE_I64 3
+
+#define FAST_FIELD_TYPE_F64 4
+
+#define FAST_FIELD_TYPE_BOOL 5
+
+#define FAST_FIELD_TYPE_DATE 6
+
+/**
+ * Sort field id that stands for the relevance score instead of a fast field.
+ */
+#define SORT_BY_SCORE UINT32_MAX
+
+/**
+ * Version field id that stands for no version check.
+ */
+#define NO_VERSION UINT32_MAX
+
+/**
+ * Indexing policy that fails the whole batch on the first failing document.
+ * Invalid documents are found before anything of the batch is staged. When the index writer
+ * fails midway the caller rolls back the staged operations, see `CheckedDocuments::stage`.
+ */
+#define INDEXING_FAIL_FAST 0
+
+/**
+ * Indexing policy that skips failing documents and indexes the others.
+ */
+#define INDEXING_BEST_EFFORT 1
+
 typedef struct Document Document;
 
+/**
+ * Error of a failing call, `code` is one of the `ERROR_*` constants. The message is to be
+ * freed with `string_free`, both are left untouched by a successful call.
+ */
+typedef struct ErrorBuffer {
+  uint32_t code;
+  char *message;
+} ErrorBuffer;
+
+/**
+ * Fast field values of the top documents of a search, one column per requested field.
+ */
+typedef struct FastFieldResult FastFieldResult;
+
+/**
+ * Searcher handed to the host, so that several searches see the same commit.
+ */
+typedef struct PinnedSearcher PinnedSearcher;
+
 typedef struct SearchResult SearchResult;
 
 typedef struct TantivyContext TantivyContext;
@@ -21,153 +137,543 @@ uint32_t schema_builder_add_text_field(SchemaBuilder *builder_ptr,
                                        bool is_fast,
                                        uintptr_t index_record_option_const,
                                        const char *tokenizer_name_ptr,
-                                       char **error_buffer);
+                                       struct ErrorBuffer *error_buffer);
+
+uint32_t schema_builder_add_i64_field(SchemaBuilder *builder_ptr,
+                                      const char *field_name_ptr,
+                                      bool stored,
+                                      bool indexed,
+                                      bool is_fast,
+                                      struct ErrorBuffer *error_buffer);
+
+uint32_t schema_builder_add_u64_field(SchemaBuilder *builder_ptr,
+                                      const char *field_name_ptr,
+                                      bool stored,
+                                      bool indexed,
+                                      bool is_fast,
+                                      struct ErrorBuffer *error_buffer);
+
+uint32_t schema_builder_add_f64_field(SchemaBuilder *builder_ptr,
+                                      const char *field_name_ptr,
+                                      bool stored,
+                                      bool indexed,
+                                      bool is_fast,
+                                      struct ErrorBuffer *error_buffer);
+
+uint32_t schema_builder_add_bool_field(SchemaBuilder *builder_ptr,
+                                       const char *field_name_ptr,
+                                       bool stored,
+                                       bool indexed,
+                                       bool is_fast,
+                                       struct ErrorBuffer *error_buffer);
 
-Schema *schema_builder_build(SchemaBuilder *builder_ptr, char **error_buffer);
+uint32_t schema_builder_add_date_field(SchemaBuilder *builder_ptr,
+                                       const char *field_name_ptr,
+                                       bool stored,
+                                       bool indexed,
+                                       bool is_fast,
+                                       uint8_t precision,
+                                       struct ErrorBuffer *error_buffer);
 
+uint32_t schema_builder_add_json_field(SchemaBuilder *builder_ptr,
+                                       const char *field_name_ptr,
+                                       bool stored,
+                                       bool is_fast,
+                                       bool expand_dots,
+                                       uintptr_t index_record_option_const,
+                                       const char *tokenizer_name_ptr,
+                                       struct ErrorBuffer *error_buffer);
+
+/**
+ * Adds a hierarchical facet field. Facets are always indexed.
+ */
+uint32_t schema_builder_add_facet_field(SchemaBuilder *builder_ptr,
+                                        const char *field_name_ptr,
+                                        bool stored,
+                                        struct ErrorBuffer *error_buffer);
+
+Schema *schema_builder_build(SchemaBuilder *builder_ptr, struct ErrorBuffer *error_buffer);
+
+/**
+ * Opens or creates the index at the path.
+ * A non-null `options_ptr` configures the context with a json object like
+ * `{"memory_budget_bytes": .., "num_threads": .., "merge_policy": {..}, "reader_reload": ..}`,
+ * every key optional. The merge policy keys are the ones of `context_set_merge_policy`.
+ * `reader_reload` is `"on_search"` (default) to reload the reader before every search,
+ * `"on_commit_with_delay"` to reload it shortly after every commit, or `"manual"` to reload it
+ * only on `context_reload_reader`. `utf8_lenient` replaces invalid utf-8 in document values,
+ * queries and terms, `clear_on_panic` removes the index directory when a call on the context
+ * panics, both default to the values given to `init_lib`.
+ */
 struct TantivyContext *context_create_with_schema(const char *path_ptr,
                                                   Schema *schema_ptr,
-                                                  char **error_buffer);
+                                                  const char *options_ptr,
+                                                  struct ErrorBuffer *error_buffer);
+
+/**
+ * Replaces the merge policy of the index writer by a `LogMergePolicy` given as a json object
+ * like `{"min_num_segments": .., "max_docs_before_merge": .., "min_layer_size": ..,
+ * "level_log_size": .., "del_docs_ratio_before_merge": ..}`, every key optional.
+ * It applies to the merges scheduled after the call.
+ */
+void context_set_merge_policy(struct TantivyContext *context_ptr,
+                              const char *merge_policy_ptr,
+                              struct ErrorBuffer *error_buffer);
+
+/**
+ * Stops scheduling background merges until `context_resume_merging`.
+ * Running merges are not interrupted, `context_set_merge_policy` applies once resumed.
+ */
+void context_pause_merging(struct TantivyContext *context_ptr, struct ErrorBuffer *error_buffer);
+
+/**
+ * Schedules background merges again with the current merge policy.
+ */
+void context_resume_merging(struct TantivyContext *context_ptr, struct ErrorBuffer *error_buffer);
+
+/**
+ * Merges the smallest searchable segments so that at most `target_segments` (at least 1)
+ * remain and waits for the merge, returning the number of searchable segments after it.
+ * Fails when one of the segments is being merged in the background,
+ * see `context_pause_merging`.
+ */
+uintptr_t context_force_merge(struct TantivyContext *context_ptr,
+                              uintptr_t target_segments,
+                              struct ErrorBuffer *error_buffer);
+
+/**
+ * Rewrites the searchable segments having at least `deleted_ratio` (in (0, 1]) of deleted
+ * documents without them and waits for the merges, returning the number of rewritten segments.
+ */
+uintptr_t context_purge_deletes(struct TantivyContext *context_ptr,
+                                float deleted_ratio,
+                                struct ErrorBuffer *error_buffer);
 
 void context_register_text_analyzer_ngram(struct TantivyContext *context_ptr,
                                           const char *tokenizer_name_ptr,
                                           uintptr_t min_gram,
                                           uintptr_t max_gram,
                                           bool prefix_only,
-                                          char **error_buffer);
+                                          struct ErrorBuffer *error_buffer);
 
 void context_register_text_analyzer_edge_ngram(struct TantivyContext *context_ptr,
                                                const char *tokenizer_name_ptr,
                                                uintptr_t min_gram,
                                                uintptr_t max_gram,
                                                uintptr_t limit,
-                                               char **error_buffer);
+                                               struct ErrorBuffer *error_buffer);
 
 void context_register_text_analyzer_simple(struct TantivyContext *context_ptr,
                                            const char *tokenizer_name_ptr,
                                            uintptr_t text_limit,
                                            const char *lang_str_ptr,
-                                           char **error_buffer);
+                                           struct ErrorBuffer *error_buffer);
 
 void context_register_jieba_tokenizer(struct TantivyContext *context_ptr,
                                       const char *tokenizer_name_ptr,
                                       uintptr_t text_limit,
-                                      char **error_buffer);
+                                      struct ErrorBuffer *error_buffer);
 
 void context_register_text_analyzer_raw(struct TantivyContext *context_ptr,
                                         const char *tokenizer_name_ptr,
-                                        char **error_buffer);
+                                        struct ErrorBuffer *error_buffer);
 
+/**
+ * Adds the documents and commits, returning the opstamp of the commit.
+ * `indexing_policy` is one of the `INDEXING_*` constants. With `INDEXING_BEST_EFFORT` failing
+ * documents are skipped and written to `failures_buffer` as a json array of
+ * `{"index": .., "error": ..}` objects, to be freed with `string_free`.
+ */
 uint64_t context_add_and_consume_documents(struct TantivyContext *context_ptr,
                                            struct Document **docs_ptr,
                                            uintptr_t docs_len,
-                                           char **error_buffer);
+                                           uint8_t indexing_policy,
+                                           const char *payload_ptr,
+                                           char **failures_buffer,
+                                           struct ErrorBuffer *error_buffer);
 
 uint64_t context_delete_documents(struct TantivyContext *context_ptr,
                                   unsigned int field_id,
                                   const char **delete_ids_ptr,
                                   uintptr_t delete_ids_len,
-                                  char **error_buffer);
+                                  const char *payload_ptr,
+                                  struct ErrorBuffer *error_buffer);
 
+/**
+ * Deletes the documents having one of the ids in the field, adds the documents and commits
+ * once, returning the opstamp of the commit.
+ * Failing documents are handled as in `context_add_and_consume_documents`.
+ */
 uint64_t context_batch_add_and_delete_documents(struct TantivyContext *context_ptr,
                                                 struct Document **add_docs_ptr,
                                                 uintptr_t add_docs_len,
                                                 unsigned int delete_field_id,
                                                 const char **delete_ids_ptr,
                                                 uintptr_t delete_ids_len,
-                                                char **error_buffer);
+                                                uint8_t indexing_policy,
+                                                const char *payload_ptr,
+                                                char **failures_buffer,
+                                                struct ErrorBuffer *error_buffer);
+
+/**
+ * Deletes the documents matching a query and commits, returning the opstamp of the commit.
+ * With `query_is_json` the query is parsed as the JSON query DSL and the fields are ignored.
+ * A non-null `payload_ptr` is stored with the commit in the index meta.
+ */
+uint64_t context_delete_by_query(struct TantivyContext *context_ptr,
+                                 unsigned int *field_ids_ptr,
+                                 float *field_weights_ptr,
+                                 uintptr_t field_ids_len,
+                                 const char *query_ptr,
+                                 bool query_is_json,
+                                 const char *payload_ptr,
+                                 struct ErrorBuffer *error_buffer);
+
+/**
+ * Deletes every document and commits, returning the opstamp of the commit.
+ * The index directory and the schema are kept.
+ */
+uint64_t context_delete_all(struct TantivyContext *context_ptr,
+                            const char *payload_ptr,
+                            struct ErrorBuffer *error_buffer);
 
-uint64_t context_num_docs(struct TantivyContext *context_ptr, char **error_buffer);
+/**
+ * Replaces the indexed documents having the id of a new document, read from `id_field_id`,
+ * and commits, returning the opstamp of the commit. The documents are consumed.
+ * With a `version_field_id` other than `NO_VERSION` a document only replaces the indexed ones
+ * when its version is newer, and is skipped otherwise.
+ * A non-null `payload_ptr` is stored with the commit in the index meta.
+ */
+uint64_t context_upsert_documents(struct TantivyContext *context_ptr,
+                                  unsigned int id_field_id,
+                                  struct Document **docs_ptr,
+                                  uintptr_t docs_len,
+                                  unsigned int version_field_id,
+                                  const char *payload_ptr,
+                                  struct ErrorBuffer *error_buffer);
+
+/**
+ * Stages documents without committing them, returning the opstamp of the last one.
+ * The documents are consumed. They become searchable after `context_commit`.
+ * Failing documents are handled as in `context_add_and_consume_documents`.
+ */
+uint64_t context_add_documents(struct TantivyContext *context_ptr,
+                               struct Document **docs_ptr,
+                               uintptr_t docs_len,
+                               uint8_t indexing_policy,
+                               char **failures_buffer,
+                               struct ErrorBuffer *error_buffer);
+
+/**
+ * Stages the deletion of the documents having one of the terms in the field without
+ * committing it, returning the opstamp of the last deletion.
+ */
+uint64_t context_delete_terms(struct TantivyContext *context_ptr,
+                              unsigned int field_id,
+                              const char **delete_ids_ptr,
+                              uintptr_t delete_ids_len,
+                              struct ErrorBuffer *error_buffer);
+
+/**
+ * Commits all staged operations, returning the opstamp of the commit.
+ * A non-null `payload_ptr` is stored with the commit in the index meta.
+ */
+uint64_t context_commit(struct TantivyContext *context_ptr,
+                        const char *payload_ptr,
+                        struct ErrorBuffer *error_buffer);
 
+/**
+ * Drops all operations staged since the last commit, returning the opstamp of that commit.
+ */
+uint64_t context_rollback(struct TantivyContext *context_ptr, struct ErrorBuffer *error_buffer);
+
+uint64_t context_num_docs(struct TantivyContext *context_ptr, struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns statistics of the searchable segments as json: document and segment counts,
+ * per-segment document counts, the opstamp of the last commit on disk and space usage in bytes,
+ * in total and per field name. The string must be freed with `string_free`.
+ */
+char *context_stats(struct TantivyContext *context_ptr, struct ErrorBuffer *error_buffer);
+
+/**
+ * Pins the searcher of the current reader generation so that several searches see the same
+ * documents, whatever is committed or reloaded meanwhile. The handle can be passed to the search
+ * functions and must be freed with `pinned_searcher_free`.
+ */
+struct PinnedSearcher *context_acquire_searcher(struct TantivyContext *context_ptr,
+                                                struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns the reader generation id of a pinned searcher.
+ */
+uint64_t pinned_searcher_get_generation(struct PinnedSearcher *searcher_ptr,
+                                        struct ErrorBuffer *error_buffer);
+
+void pinned_searcher_free(struct PinnedSearcher *searcher_ptr);
+
+/**
+ * A non-null `searcher_ptr` runs the search on that pinned searcher instead of the current one.
+ * `options_ptr` holds the `SearchOptions` as json, the defaults when null.
+ */
 struct SearchResult *context_search(struct TantivyContext *context_ptr,
+                                    struct PinnedSearcher *searcher_ptr,
                                     unsigned int *field_ids_ptr,
                                     float *field_weights_ptr,
                                     uintptr_t field_ids_len,
                                     const char *query_ptr,
-                                    char **error_buffer,
-                                    uintptr_t docs_limit,
-                                    bool with_highlights);
+                                    const char *options_ptr,
+                                    struct ErrorBuffer *error_buffer);
 
+/**
+ * Like `context_search`, with the query given as the JSON query DSL.
+ */
 struct SearchResult *context_search_json(struct TantivyContext *context_ptr,
+                                         struct PinnedSearcher *searcher_ptr,
                                          const char *query_ptr,
-                                         char **error_buffer,
-                                         uintptr_t docs_limit,
-                                         bool with_highlights);
+                                         const char *options_ptr,
+                                         struct ErrorBuffer *error_buffer);
 
 /**
- * Performs a search and returns only fast field values (no full document loading).
- * Returns the number of results found. Results are written to pre-allocated output arrays.
+ * Counts the documents matching a query without collecting them.
+ * With `query_is_json` the query is parsed as the JSON query DSL and the fields are ignored.
  */
-uintptr_t context_search_fast_field(struct TantivyContext *context_ptr,
-                                    unsigned int *field_ids_ptr,
-                                    float *field_weights_ptr,
-                                    uintptr_t field_ids_len,
-                                    const char *query_ptr,
-                                    unsigned int fast_field_id,
-                                    uintptr_t docs_limit,
-                                    float *out_scores_ptr,
-                                    char **out_values_ptr,
-                                    char **error_buffer);
+uintptr_t context_count(struct TantivyContext *context_ptr,
+                        struct PinnedSearcher *searcher_ptr,
+                        unsigned int *field_ids_ptr,
+                        float *field_weights_ptr,
+                        uintptr_t field_ids_len,
+                        const char *query_ptr,
+                        bool query_is_json,
+                        struct ErrorBuffer *error_buffer);
 
 /**
- * Free an array of strings returned by context_search_fast_field
+ * Runs an aggregation request in the tantivy aggregation JSON format (terms, range, histogram,
+ * date_histogram, stats, ...) over the documents matching a query.
+ * With `query_is_json` the query is parsed as the JSON query DSL and the fields are ignored.
+ * Returns the aggregation results as JSON, to be freed with `string_free`.
  */
-void fast_field_values_free(char **values_ptr, uintptr_t count);
+char *context_aggregate(struct TantivyContext *context_ptr,
+                        struct PinnedSearcher *searcher_ptr,
+                        unsigned int *field_ids_ptr,
+                        float *field_weights_ptr,
+                        uintptr_t field_ids_len,
+                        const char *query_ptr,
+                        bool query_is_json,
+                        const char *aggregation_ptr,
+                        struct ErrorBuffer *error_buffer);
 
 /**
- * Performs a search using JSON query and returns only fast field values (no full document loading).
- * Returns the number of results found. Results are written to pre-allocated output arrays.
+ * Performs a search and returns only the values of the given fast fields of the top documents
+ * (no full document loading). Fast fields may be str, numeric, bool or date fields.
+ * With `query_is_json` the query is parsed as the JSON query DSL and the fields are ignored.
+ * `options_ptr` holds the `SearchOptions` as json, without facets, highlights or siblings.
+ * The result must be freed with `fast_field_result_free`.
  */
-uintptr_t context_search_fast_field_json(struct TantivyContext *context_ptr,
-                                         const char *query_ptr,
-                                         unsigned int fast_field_id,
-                                         uintptr_t docs_limit,
-                                         float *out_scores_ptr,
-                                         char **out_values_ptr,
-                                         char **error_buffer);
+struct FastFieldResult *context_search_fast_fields(struct TantivyContext *context_ptr,
+                                                   struct PinnedSearcher *searcher_ptr,
+                                                   unsigned int *field_ids_ptr,
+                                                   float *field_weights_ptr,
+                                                   uintptr_t field_ids_len,
+                                                   const char *query_ptr,
+                                                   bool query_is_json,
+                                                   unsigned int *fast_field_ids_ptr,
+                                                   uintptr_t fast_field_ids_len,
+                                                   const char *options_ptr,
+                                                   struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns the number of documents (rows) of a fast field result.
+ */
+uintptr_t fast_field_result_get_size(struct FastFieldResult *result_ptr,
+                                     struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns the number of documents matching the query, which may exceed the returned size.
+ */
+uintptr_t fast_field_result_get_total_hits(struct FastFieldResult *result_ptr,
+                                           struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns the cursor of the last document, or null when the result is empty.
+ * The string must be freed with `string_free`.
+ */
+char *fast_field_result_get_cursor(struct FastFieldResult *result_ptr,
+                                   struct ErrorBuffer *error_buffer);
+
+float fast_field_result_get_score(struct FastFieldResult *result_ptr,
+                                  uintptr_t row,
+                                  struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns the `FAST_FIELD_TYPE_*` code of a value, `FAST_FIELD_TYPE_NONE` when the
+ * document has no value. `column` is the position of the field in the requested fast fields.
+ */
+uint8_t fast_field_result_get_type(struct FastFieldResult *result_ptr,
+                                   uintptr_t row,
+                                   uintptr_t column,
+                                   struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns a str value, to be freed with `string_free`.
+ */
+char *fast_field_result_get_str(struct FastFieldResult *result_ptr,
+                                uintptr_t row,
+                                uintptr_t column,
+                                struct ErrorBuffer *error_buffer);
+
+uint64_t fast_field_result_get_u64(struct FastFieldResult *result_ptr,
+                                   uintptr_t row,
+                                   uintptr_t column,
+                                   struct ErrorBuffer *error_buffer);
+
+int64_t fast_field_result_get_i64(struct FastFieldResult *result_ptr,
+                                  uintptr_t row,
+                                  uintptr_t column,
+                                  struct ErrorBuffer *error_buffer);
+
+double fast_field_result_get_f64(struct FastFieldResult *result_ptr,
+                                 uintptr_t row,
+                                 uintptr_t column,
+                                 struct ErrorBuffer *error_buffer);
+
+bool fast_field_result_get_bool(struct FastFieldResult *result_ptr,
+                                uintptr_t row,
+                                uintptr_t column,
+                                struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns a date value as nanoseconds since the Unix epoch.
+ */
+int64_t fast_field_result_get_date(struct FastFieldResult *result_ptr,
+                                   uintptr_t row,
+                                   uintptr_t column,
+                                   struct ErrorBuffer *error_buffer);
+
+void fast_field_result_free(struct FastFieldResult *result_ptr);
 
 void context_free(struct TantivyContext *context_ptr);
 
-uintptr_t search_result_get_size(struct SearchResult *result_ptr, char **error_buffer);
+uintptr_t search_result_get_size(struct SearchResult *result_ptr, struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns the number of documents matching the query, which may exceed the returned size.
+ */
+uintptr_t search_result_get_total_hits(struct SearchResult *result_ptr,
+                                       struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns the cursor of the last document, to be passed as `search_after_ptr` of the next
+ * search, or null when the result is empty. The string must be freed with `string_free`.
+ */
+char *search_result_get_cursor(struct SearchResult *result_ptr, struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns the facet counts of the search as a json object mapping every requested prefix to
+ * the counts of its children, or null when no facet was requested.
+ * The string must be freed with `string_free`.
+ */
+char *search_result_get_facet_counts(struct SearchResult *result_ptr,
+                                     struct ErrorBuffer *error_buffer);
 
 struct Document *search_result_get_doc(struct SearchResult *result_ptr,
                                        uintptr_t index,
-                                       char **error_buffer);
+                                       struct ErrorBuffer *error_buffer);
 
 void search_result_free(struct SearchResult *result_ptr);
 
 struct Document *document_create(void);
 
 void document_add_field(struct Document *doc_ptr,
+                        struct TantivyContext *context_ptr,
                         unsigned int field_id,
                         const char *field_value_ptr,
-                        char **error_buffer);
+                        struct ErrorBuffer *error_buffer);
 
 void document_add_fields(struct Document *doc_ptr,
+                         struct TantivyContext *context_ptr,
                          unsigned int *field_ids_ptr,
                          uintptr_t field_ids_len,
                          const char *field_value_ptr,
-                         char **error_buffer);
+                         struct ErrorBuffer *error_buffer);
+
+void document_add_i64(struct Document *doc_ptr,
+                      unsigned int field_id,
+                      int64_t field_value,
+                      struct ErrorBuffer *error_buffer);
+
+void document_add_u64(struct Document *doc_ptr,
+                      unsigned int field_id,
+                      uint64_t field_value,
+                      struct ErrorBuffer *error_buffer);
+
+void document_add_f64(struct Document *doc_ptr,
+                      unsigned int field_id,
+                      double field_value,
+                      struct ErrorBuffer *error_buffer);
+
+void document_add_bool(struct Document *doc_ptr,
+                       unsigned int field_id,
+                       bool field_value,
+                       struct ErrorBuffer *error_buffer);
+
+/**
+ * Adds a date value given as nanoseconds since the Unix epoch.
+ */
+void document_add_date(struct Document *doc_ptr,
+                       unsigned int field_id,
+                       int64_t timestamp_nanos,
+                       struct ErrorBuffer *error_buffer);
+
+/**
+ * Adds a facet given as a path like `/space/abc/type/note`.
+ */
+void document_add_facet(struct Document *doc_ptr,
+                        struct TantivyContext *context_ptr,
+                        unsigned int field_id,
+                        const char *facet_path_ptr,
+                        struct ErrorBuffer *error_buffer);
+
+/**
+ * Adds a json object field value given as a serialized json object.
+ */
+void document_add_json(struct Document *doc_ptr,
+                       struct TantivyContext *context_ptr,
+                       unsigned int field_id,
+                       const char *json_ptr,
+                       struct ErrorBuffer *error_buffer);
 
 char *document_as_json(struct Document *doc_ptr,
                        unsigned int *include_field_ids_ptr,
                        uintptr_t include_field_ids_len,
                        Schema *schema_ptr,
-                       char **error_buffer);
+                       struct ErrorBuffer *error_buffer);
 
 void document_free(struct Document *doc_ptr);
 
 void string_free(char *s);
 
+/**
+ * Initializes logging. `clear_on_panic` and `utf8_lenient` are the defaults of the contexts
+ * created afterwards, which may override them in their options.
+ */
 void init_lib(const char *log_level_ptr,
-              char **error_buffer,
+              struct ErrorBuffer *error_buffer,
               bool clear_on_panic,
               bool utf8_lenient);
 
-void context_wait_and_free(struct TantivyContext *context_ptr, char **error_buffer);
+void context_wait_and_free(struct TantivyContext *context_ptr, struct ErrorBuffer *error_buffer);
+
+/**
+ * Returns the payload of the last commit, or null when it has none.
+ * The string must be freed with `string_free`.
+ */
+char *context_last_commit_payload(struct TantivyContext *context_ptr,
+                                  struct ErrorBuffer *error_buffer);
 
 uint64_t context_commit_opstamp(struct TantivyContext *context_ptr);
 
-void context_reload_reader(struct TantivyContext *context_ptr, char **error_buffer);
+void context_reload_reader(struct TantivyContext *context_ptr, struct ErrorBuffer *error_buffer);
 
-uint64_t context_garbage_collect_files(struct TantivyContext *context_ptr, char **error_buffer);
+uint64_t context_garbage_collect_files(struct TantivyContext *context_ptr,
+                                       struct ErrorBuffer *error_buffer);
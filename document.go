@@ -4,6 +4,7 @@ package tantivy_go
 import "C"
 import (
 	"errors"
+	"time"
 	"unsafe"
 )
 
@@ -38,8 +39,8 @@ func (d *Document) AddField(fieldValue string, tc *TantivyContext, fieldName str
 	}
 	cFieldValue := C.CString(fieldValue)
 	d.toFree = append(d.toFree, func() { C.string_free(cFieldValue) })
-	var errBuffer *C.char
-	C.document_add_field(d.ptr, C.uint(fieldId), cFieldValue, &errBuffer)
+	var errBuffer C.ErrorBuffer
+	C.document_add_field(d.ptr, tc.ptr, C.uint(fieldId), cFieldValue, &errBuffer)
 
 	return tryExtractError(errBuffer)
 }
@@ -62,8 +63,155 @@ func (d *Document) AddFields(fieldValue string, tc *TantivyContext, fieldNames .
 
 	cFieldValue := C.CString(fieldValue)
 	d.toFree = append(d.toFree, func() { C.string_free(cFieldValue) })
-	var errBuffer *C.char
-	C.document_add_fields(d.ptr, (*C.uint)(unsafe.Pointer(&includeFieldsPtr[0])), C.uintptr_t(len(includeFieldsPtr)), cFieldValue, &errBuffer)
+	var errBuffer C.ErrorBuffer
+	C.document_add_fields(d.ptr, tc.ptr, (*C.uint)(unsafe.Pointer(&includeFieldsPtr[0])), C.uintptr_t(len(includeFieldsPtr)), cFieldValue, &errBuffer)
+
+	return tryExtractError(errBuffer)
+}
+
+// AddI64Field adds a int64 value for the field with the specified name to the document.
+//
+// Parameters:
+//   - fieldValue: the value of the field to add
+//   - tc: the context whose schema contains the field
+//   - fieldName: the name of the field to add
+//
+// Returns:
+//   - error: an error if adding the field fails, or nil if the operation is successful
+func (d *Document) AddI64Field(fieldValue int64, tc *TantivyContext, fieldName string) error {
+	fieldId, contains := tc.schema.fieldNames[fieldName]
+	if !contains {
+		return errors.New("field not found in schema")
+	}
+	var errBuffer C.ErrorBuffer
+	C.document_add_i64(d.ptr, C.uint(fieldId), C.int64_t(fieldValue), &errBuffer)
+
+	return tryExtractError(errBuffer)
+}
+
+// AddU64Field adds a uint64 value for the field with the specified name to the document.
+//
+// Parameters:
+//   - fieldValue: the value of the field to add
+//   - tc: the context whose schema contains the field
+//   - fieldName: the name of the field to add
+//
+// Returns:
+//   - error: an error if adding the field fails, or nil if the operation is successful
+func (d *Document) AddU64Field(fieldValue uint64, tc *TantivyContext, fieldName string) error {
+	fieldId, contains := tc.schema.fieldNames[fieldName]
+	if !contains {
+		return errors.New("field not found in schema")
+	}
+	var errBuffer C.ErrorBuffer
+	C.document_add_u64(d.ptr, C.uint(fieldId), C.uint64_t(fieldValue), &errBuffer)
+
+	return tryExtractError(errBuffer)
+}
+
+// AddF64Field adds a float64 value for the field with the specified name to the document.
+//
+// Parameters:
+//   - fieldValue: the value of the field to add
+//   - tc: the context whose schema contains the field
+//   - fieldName: the name of the field to add
+//
+// Returns:
+//   - error: an error if adding the field fails, or nil if the operation is successful
+func (d *Document) AddF64Field(fieldValue float64, tc *TantivyContext, fieldName string) error {
+	fieldId, contains := tc.schema.fieldNames[fieldName]
+	if !contains {
+		return errors.New("field not found in schema")
+	}
+	var errBuffer C.ErrorBuffer
+	C.document_add_f64(d.ptr, C.uint(fieldId), C.double(fieldValue), &errBuffer)
+
+	return tryExtractError(errBuffer)
+}
+
+// AddBoolField adds a bool value for the field with the specified name to the document.
+//
+// Parameters:
+//   - fieldValue: the value of the field to add
+//   - tc: the context whose schema contains the field
+//   - fieldName: the name of the field to add
+//
+// Returns:
+//   - error: an error if adding the field fails, or nil if the operation is successful
+func (d *Document) AddBoolField(fieldValue bool, tc *TantivyContext, fieldName string) error {
+	fieldId, contains := tc.schema.fieldNames[fieldName]
+	if !contains {
+		return errors.New("field not found in schema")
+	}
+	var errBuffer C.ErrorBuffer
+	C.document_add_bool(d.ptr, C.uint(fieldId), C.bool(fieldValue), &errBuffer)
+
+	return tryExtractError(errBuffer)
+}
+
+// AddDateField adds a date value for the field with the specified name to the document.
+// The stored date keeps its full precision and is returned by ToJson as nanoseconds since the Unix
+// epoch, while indexed and fast values are truncated to the precision of the field, seconds by
+// default (see SchemaBuilder.AddDateFieldWithPrecision).
+//
+// Parameters:
+//   - fieldValue: the value of the field to add
+//   - tc: the context whose schema contains the field
+//   - fieldName: the name of the field to add
+//
+// Returns:
+//   - error: an error if adding the field fails, or nil if the operation is successful
+func (d *Document) AddDateField(fieldValue time.Time, tc *TantivyContext, fieldName string) error {
+	fieldId, contains := tc.schema.fieldNames[fieldName]
+	if !contains {
+		return errors.New("field not found in schema")
+	}
+	var errBuffer C.ErrorBuffer
+	C.document_add_date(d.ptr, C.uint(fieldId), C.int64_t(fieldValue.UnixNano()), &errBuffer)
+
+	return tryExtractError(errBuffer)
+}
+
+// AddFacetField adds a facet for the field with the specified name to the document.
+//
+// Parameters:
+//   - facetPath: the facet path to add, e.g. `/space/abc/type/note`
+//   - tc: the context whose schema contains the field
+//   - fieldName: the name of the field to add
+//
+// Returns:
+//   - error: an error if the path is not a valid facet path or adding the field fails
+func (d *Document) AddFacetField(facetPath string, tc *TantivyContext, fieldName string) error {
+	fieldId, contains := tc.schema.fieldNames[fieldName]
+	if !contains {
+		return errors.New("field not found in schema")
+	}
+	cFacetPath := C.CString(facetPath)
+	defer C.string_free(cFacetPath)
+	var errBuffer C.ErrorBuffer
+	C.document_add_facet(d.ptr, tc.ptr, C.uint(fieldId), cFacetPath, &errBuffer)
+
+	return tryExtractError(errBuffer)
+}
+
+// AddJsonField adds a json object for the field with the specified name to the document.
+//
+// Parameters:
+//   - fieldValue: the serialized json object to add
+//   - tc: the context whose schema contains the field
+//   - fieldName: the name of the field to add
+//
+// Returns:
+//   - error: an error if the value is not a json object or adding the field fails
+func (d *Document) AddJsonField(fieldValue string, tc *TantivyContext, fieldName string) error {
+	fieldId, contains := tc.schema.fieldNames[fieldName]
+	if !contains {
+		return errors.New("field not found in schema")
+	}
+	cFieldValue := C.CString(fieldValue)
+	defer C.string_free(cFieldValue)
+	var errBuffer C.ErrorBuffer
+	C.document_add_json(d.ptr, tc.ptr, C.uint(fieldId), cFieldValue, &errBuffer)
 
 	return tryExtractError(errBuffer)
 }
@@ -79,7 +227,7 @@ func (d *Document) AddFields(fieldValue string, tc *TantivyContext, fieldNames .
 //   - string: the JSON representation of the document
 //   - error: an error if the conversion fails, or nil if the operation is successful
 func (d *Document) ToJson(tc *TantivyContext, includeFields ...string) (string, error) {
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 
 	includeFieldsPtr, err := tc.extractFields(includeFields)
 	if err != nil {
@@ -95,9 +243,7 @@ func (d *Document) ToJson(tc *TantivyContext, includeFields ...string) (string,
 	)
 
 	if cStr == nil {
-		errorMessage := C.GoString(errBuffer)
-		defer C.string_free(errBuffer)
-		return "", errors.New(errorMessage)
+		return "", tryExtractError(errBuffer)
 	}
 	defer C.string_free(cStr)
 
@@ -0,0 +1,47 @@
+package tantivy_go
+
+// #include "bindings.h"
+import "C"
+
+// ErrorCode identifies the kind of failure of a call, stable across releases.
+type ErrorCode uint32
+
+const (
+	// ErrInternal is any failure without a more specific code.
+	ErrInternal ErrorCode = C.ERROR_INTERNAL
+	// ErrNullPointer is a null handle or string given to the library.
+	ErrNullPointer ErrorCode = C.ERROR_NULL_POINTER
+	// ErrInvalidUtf8 is a string that is not valid UTF-8, see ContextOptions.Utf8Lenient.
+	ErrInvalidUtf8 ErrorCode = C.ERROR_INVALID_UTF8
+	// ErrInvalidArgument is an invalid option, value or request, e.g. a malformed search cursor.
+	ErrInvalidArgument ErrorCode = C.ERROR_INVALID_ARGUMENT
+	// ErrQueryParse is a query that can not be parsed.
+	ErrQueryParse ErrorCode = C.ERROR_QUERY_PARSE
+	// ErrUnknownField is a field missing from the schema.
+	ErrUnknownField ErrorCode = C.ERROR_UNKNOWN_FIELD
+	// ErrNoTerms is a query text producing no terms, e.g. only stop words.
+	ErrNoTerms ErrorCode = C.ERROR_NO_TERMS
+	// ErrInvalidDocument is a document that does not match the schema or its batch.
+	ErrInvalidDocument ErrorCode = C.ERROR_INVALID_DOCUMENT
+	// ErrIndexCorrupted is an index whose files are corrupted or of an incompatible version.
+	ErrIndexCorrupted ErrorCode = C.ERROR_INDEX_CORRUPTED
+	// ErrLockBusy is an index locked by another writer.
+	ErrLockBusy ErrorCode = C.ERROR_LOCK_BUSY
+	// ErrIo is a failure reading or writing the index directory.
+	ErrIo ErrorCode = C.ERROR_IO
+	// ErrPanic is a call that panicked, the context is poisoned afterwards.
+	ErrPanic ErrorCode = C.ERROR_PANIC
+	// ErrContextPoisoned is a call on a context poisoned by an earlier panic.
+	ErrContextPoisoned ErrorCode = C.ERROR_CONTEXT_POISONED
+)
+
+// Error is the error returned by the calls of the library.
+// Use errors.As to get its Code.
+type Error struct {
+	Code    ErrorCode
+	Message string
+}
+
+func (e *Error) Error() string {
+	return e.Message
+}
@@ -5,36 +5,41 @@ import "C"
 import (
 	"errors"
 	"fmt"
+	"time"
 	"unsafe"
 )
 
+// FastFieldValues holds the values of several fast fields of the top documents of a search.
+type FastFieldValues struct {
+	Scores []float32
+	// Rows holds one row per document with a value per requested fast field, in request order.
+	// Values are string, uint64, int64, float64, bool or time.Time, and nil when the document has none.
+	Rows [][]any
+	// Cursor of the last result, to pass to SearchContextBuilder.SetSearchAfter for the next page.
+	Cursor string
+	// TotalHits is the number of documents matching the query.
+	TotalHits uint64
+}
+
 // FastFieldResult holds the results of a fast field search.
 type FastFieldResult struct {
 	Values []string
 	Scores []float32
+	// Cursor of the last result, to pass to SearchContextBuilder.SetSearchAfter for the next page.
+	Cursor string
+	// TotalHits is the number of documents matching the query.
+	TotalHits uint64
 }
 
-// SearchFastField performs a search returning only fast field values without loading full documents.
-// The field must be configured with isFast=true in the schema.
-func (tc *TantivyContext) SearchFastField(sCtx SearchContext, fastFieldName string) (*FastFieldResult, error) {
+// SearchFastFields performs a search returning only the values of the given fast fields
+// without loading full documents. The fields must be configured with isFast=true in the schema
+// and may be text, numeric, bool or date fields.
+func (tc *TantivyContext) SearchFastFields(sCtx SearchContext, fastFieldNames ...string) (*FastFieldValues, error) {
 	fieldNames, weights := sCtx.GetFieldAndWeights()
 	if len(fieldNames) == 0 {
 		return nil, fmt.Errorf("fieldNames must not be empty")
 	}
 
-	docsLimit := sCtx.GetDocsLimit()
-	if docsLimit == 0 {
-		return nil, errors.New("docsLimit must be greater than 0")
-	}
-
-	fastFieldId, contains := tc.schema.fieldNames[fastFieldName]
-	if !contains {
-		return nil, errors.New("fast field not found in schema")
-	}
-
-	cQuery := C.CString(sCtx.GetQuery())
-	defer C.string_free(cQuery)
-
 	fieldNamesPtr, err := tc.extractFields(fieldNames)
 	if err != nil {
 		return nil, err
@@ -45,118 +50,208 @@ func (tc *TantivyContext) SearchFastField(sCtx SearchContext, fastFieldName stri
 		fieldWeightsPtr[j] = C.float(weight)
 	}
 
-	outScores := make([]C.float, docsLimit)
-	outValues := make([]*C.char, docsLimit)
-
-	var errBuffer *C.char
-	count := C.context_search_fast_field(
-		tc.ptr,
+	return tc.searchFastFields(
+		sCtx,
+		fastFieldNames,
 		(*C.uint)(unsafe.Pointer(&fieldNamesPtr[0])),
 		(*C.float)(unsafe.Pointer(&fieldWeightsPtr[0])),
-		C.uintptr_t(len(fieldNames)),
-		cQuery,
-		C.uint(fastFieldId),
-		pointerCType(docsLimit),
-		(*C.float)(unsafe.Pointer(&outScores[0])),
-		(**C.char)(unsafe.Pointer(&outValues[0])),
-		&errBuffer,
+		len(fieldNames),
+		false,
 	)
+}
 
-	if count == 0 {
-		if errBuffer != nil {
-			errMsg := C.GoString(errBuffer)
-			C.string_free(errBuffer)
-			if errMsg != "" {
-				return nil, errors.New(errMsg)
-			}
-		}
-		return &FastFieldResult{
-			Values: []string{},
-			Scores: []float32{},
-		}, nil
-	}
-
-	result := &FastFieldResult{
-		Values: make([]string, count),
-		Scores: make([]float32, count),
-	}
+// SearchFastFieldsJson performs a search using JSON query returning only the values of the given fast fields.
+// Use this with AllQuery or other JSON-based queries.
+func (tc *TantivyContext) SearchFastFieldsJson(sCtx SearchContext, fastFieldNames ...string) (*FastFieldValues, error) {
+	return tc.searchFastFields(sCtx, fastFieldNames, nil, nil, 0, true)
+}
 
-	for i := 0; i < int(count); i++ {
-		result.Scores[i] = float32(outScores[i])
-		if outValues[i] != nil {
-			result.Values[i] = C.GoString(outValues[i])
-		}
+// SearchFastField performs a search returning only fast field values without loading full documents.
+// The field must be configured with isFast=true in the schema.
+func (tc *TantivyContext) SearchFastField(sCtx SearchContext, fastFieldName string) (*FastFieldResult, error) {
+	values, err := tc.SearchFastFields(sCtx, fastFieldName)
+	if err != nil {
+		return nil, err
 	}
-
-	C.fast_field_values_free(
-		(**C.char)(unsafe.Pointer(&outValues[0])),
-		C.uintptr_t(count),
-	)
-
-	return result, nil
+	return values.singleField(), nil
 }
 
 // SearchFastFieldJson performs a search using JSON query returning only fast field values.
 // The field must be configured with isFast=true in the schema.
 // Use this with AllQuery or other JSON-based queries.
 func (tc *TantivyContext) SearchFastFieldJson(sCtx SearchContext, fastFieldName string) (*FastFieldResult, error) {
+	values, err := tc.SearchFastFieldsJson(sCtx, fastFieldName)
+	if err != nil {
+		return nil, err
+	}
+	return values.singleField(), nil
+}
+
+func (v *FastFieldValues) singleField() *FastFieldResult {
+	result := &FastFieldResult{
+		Values:    make([]string, len(v.Rows)),
+		Scores:    v.Scores,
+		Cursor:    v.Cursor,
+		TotalHits: v.TotalHits,
+	}
+	for i, row := range v.Rows {
+		if row[0] != nil {
+			result.Values[i] = fmt.Sprint(row[0])
+		}
+	}
+	return result
+}
+
+func (tc *TantivyContext) searchFastFields(
+	sCtx SearchContext,
+	fastFieldNames []string,
+	fieldIdsPtr *C.uint,
+	fieldWeightsPtr *C.float,
+	fieldIdsLen int,
+	queryIsJson bool,
+) (*FastFieldValues, error) {
 	docsLimit := sCtx.GetDocsLimit()
 	if docsLimit == 0 {
 		return nil, errors.New("docsLimit must be greater than 0")
 	}
+	if len(fastFieldNames) == 0 {
+		return nil, errors.New("fast field names must not be empty")
+	}
 
-	fastFieldId, contains := tc.schema.fieldNames[fastFieldName]
-	if !contains {
-		return nil, errors.New("fast field not found in schema")
+	fastFieldIds := make([]C.uint, len(fastFieldNames))
+	for i, name := range fastFieldNames {
+		fastFieldId, contains := tc.schema.fieldNames[name]
+		if !contains {
+			return nil, errors.New("fast field not found in schema")
+		}
+		fastFieldIds[i] = C.uint(fastFieldId)
 	}
 
+	options, err := tc.extractSearchOptions(sCtx)
+	if err != nil {
+		return nil, err
+	}
+	// Fast field searches return neither facet counts, highlights nor collapsed siblings
+	options.Facets = nil
+	options.WithHighlights = false
+	if options.Collapse != nil {
+		options.Collapse.WithSiblings = false
+		options.Collapse.SiblingLimit = 0
+	}
+	cOptions, err := options.cString()
+	if err != nil {
+		return nil, err
+	}
+	defer C.string_free(cOptions)
+
 	cQuery := C.CString(sCtx.GetQuery())
 	defer C.string_free(cQuery)
 
-	outScores := make([]C.float, docsLimit)
-	outValues := make([]*C.char, docsLimit)
-
-	var errBuffer *C.char
-	count := C.context_search_fast_field_json(
+	var errBuffer C.ErrorBuffer
+	ptr := C.context_search_fast_fields(
 		tc.ptr,
+		searcherPtr(sCtx),
+		fieldIdsPtr,
+		fieldWeightsPtr,
+		C.uintptr_t(fieldIdsLen),
 		cQuery,
-		C.uint(fastFieldId),
-		pointerCType(docsLimit),
-		(*C.float)(unsafe.Pointer(&outScores[0])),
-		(**C.char)(unsafe.Pointer(&outValues[0])),
+		C.bool(queryIsJson),
+		&fastFieldIds[0],
+		C.uintptr_t(len(fastFieldIds)),
+		cOptions,
 		&errBuffer,
 	)
-
-	if count == 0 {
-		if errBuffer != nil {
-			errMsg := C.GoString(errBuffer)
-			C.string_free(errBuffer)
-			if errMsg != "" {
-				return nil, errors.New(errMsg)
-			}
+	if ptr == nil {
+		if err := tryExtractError(errBuffer); err != nil {
+			return nil, err
 		}
-		return &FastFieldResult{
-			Values: []string{},
-			Scores: []float32{},
-		}, nil
+		return nil, errors.New("empty fast field result")
 	}
+	defer C.fast_field_result_free(ptr)
 
-	result := &FastFieldResult{
-		Values: make([]string, count),
-		Scores: make([]float32, count),
+	return readFastFieldValues(ptr, len(fastFieldIds))
+}
+
+func readFastFieldValues(ptr *C.FastFieldResult, columns int) (*FastFieldValues, error) {
+	var errBuffer C.ErrorBuffer
+	size := int(C.fast_field_result_get_size(ptr, &errBuffer))
+	if err := tryExtractError(errBuffer); err != nil {
+		return nil, err
+	}
+	totalHits := C.fast_field_result_get_total_hits(ptr, &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		return nil, err
 	}
 
-	for i := 0; i < int(count); i++ {
-		result.Scores[i] = float32(outScores[i])
-		if outValues[i] != nil {
-			result.Values[i] = C.GoString(outValues[i])
+	result := &FastFieldValues{
+		Scores:    make([]float32, size),
+		Rows:      make([][]any, size),
+		TotalHits: uint64(totalHits),
+	}
+
+	cursor := C.fast_field_result_get_cursor(ptr, &errBuffer)
+	if cursor == nil {
+		if err := tryExtractError(errBuffer); err != nil {
+			return nil, err
 		}
+	} else {
+		result.Cursor = C.GoString(cursor)
+		C.string_free(cursor)
 	}
 
-	C.fast_field_values_free(
-		(**C.char)(unsafe.Pointer(&outValues[0])),
-		C.uintptr_t(count),
-	)
+	for row := 0; row < size; row++ {
+		score := C.fast_field_result_get_score(ptr, C.uintptr_t(row), &errBuffer)
+		if err := tryExtractError(errBuffer); err != nil {
+			return nil, err
+		}
+		result.Scores[row] = float32(score)
 
+		result.Rows[row] = make([]any, columns)
+		for column := 0; column < columns; column++ {
+			value, err := fastFieldValue(ptr, C.uintptr_t(row), C.uintptr_t(column))
+			if err != nil {
+				return nil, err
+			}
+			result.Rows[row][column] = value
+		}
+	}
 	return result, nil
 }
+
+func fastFieldValue(ptr *C.FastFieldResult, row C.uintptr_t, column C.uintptr_t) (any, error) {
+	var errBuffer C.ErrorBuffer
+	valueType := C.fast_field_result_get_type(ptr, row, column, &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		return nil, err
+	}
+
+	var value any
+	switch valueType {
+	case C.FAST_FIELD_TYPE_NONE:
+		return nil, nil
+	case C.FAST_FIELD_TYPE_STR:
+		cStr := C.fast_field_result_get_str(ptr, row, column, &errBuffer)
+		if cStr == nil {
+			return nil, tryExtractError(errBuffer)
+		}
+		value = C.GoString(cStr)
+		C.string_free(cStr)
+	case C.FAST_FIELD_TYPE_U64:
+		value = uint64(C.fast_field_result_get_u64(ptr, row, column, &errBuffer))
+	case C.FAST_FIELD_TYPE_I64:
+		value = int64(C.fast_field_result_get_i64(ptr, row, column, &errBuffer))
+	case C.FAST_FIELD_TYPE_F64:
+		value = float64(C.fast_field_result_get_f64(ptr, row, column, &errBuffer))
+	case C.FAST_FIELD_TYPE_BOOL:
+		value = bool(C.fast_field_result_get_bool(ptr, row, column, &errBuffer))
+	case C.FAST_FIELD_TYPE_DATE:
+		nanos := C.fast_field_result_get_date(ptr, row, column, &errBuffer)
+		value = time.Unix(0, int64(nanos)).UTC()
+	default:
+		return nil, fmt.Errorf("unknown fast field type %d", valueType)
+	}
+	if err := tryExtractError(errBuffer); err != nil {
+		return nil, err
+	}
+	return value, nil
+}
@@ -0,0 +1,43 @@
+package tantivy_go
+
+// #include "bindings.h"
+import "C"
+import (
+	"encoding/json"
+	"fmt"
+)
+
+// IndexingPolicy decides what happens to a batch when some of its documents fail to index,
+// for example because a value does not match the type of its field.
+type IndexingPolicy uint8
+
+const (
+	// FailFast fails the whole batch on the first failing document. An invalid document fails it
+	// before anything is staged. When the index writer fails midway the staged operations are
+	// rolled back, as for any failed call (see TantivyContext).
+	FailFast IndexingPolicy = C.INDEXING_FAIL_FAST
+	// BestEffort skips failing documents, indexes the others and reports the skipped ones.
+	BestEffort IndexingPolicy = C.INDEXING_BEST_EFFORT
+)
+
+// DocumentFailure is a document of a batch that could not be indexed.
+type DocumentFailure struct {
+	// Index is the position of the document in the batch.
+	Index int `json:"index"`
+	// Error is the reason the document failed.
+	Error string `json:"error"`
+}
+
+// extractFailures returns nil when no document failed.
+func extractFailures(failuresBuffer *C.char) ([]DocumentFailure, error) {
+	if failuresBuffer == nil {
+		return nil, nil
+	}
+	defer C.string_free(failuresBuffer)
+
+	var failures []DocumentFailure
+	if err := json.Unmarshal([]byte(C.GoString(failuresBuffer)), &failures); err != nil {
+		return nil, fmt.Errorf("failed to parse document failures: %w", err)
+	}
+	return failures, nil
+}
@@ -17,12 +17,15 @@ func LibInit(cleanOnPanic, utf8Lenient bool, directive ...string) error {
 	defer C.string_free(cInitVal)
 	cCleanOnPanic := C.bool(cleanOnPanic)
 	cUtf8Lenient := C.bool(utf8Lenient)
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	fmt.Println("### lenient", utf8Lenient)
 	C.init_lib(cInitVal, &errBuffer, cCleanOnPanic, cUtf8Lenient)
 
-	errorMessage := C.GoString(errBuffer)
-	defer C.string_free(errBuffer)
+	if errBuffer.message == nil {
+		return nil
+	}
+	errorMessage := C.GoString(errBuffer.message)
+	defer C.string_free(errBuffer.message)
 
 	if errorMessage != "" {
 		return fmt.Errorf(errorMessage)
@@ -0,0 +1,69 @@
+package tantivy_go
+
+// #include "bindings.h"
+import "C"
+import (
+	"encoding/json"
+	"fmt"
+)
+
+// ContextOptions configures the index writer and reader of a TantivyContext.
+// Zero values keep the defaults.
+type ContextOptions struct {
+	// MemoryBudgetBytes is the memory budget shared by all indexing threads, DOCUMENT_BUDGET_BYTES by default.
+	// Every thread needs at least 15MB.
+	MemoryBudgetBytes uint64 `json:"memory_budget_bytes,omitempty"`
+	// NumThreads is the number of indexing threads, picked from the number of CPUs by default.
+	NumThreads uint64 `json:"num_threads,omitempty"`
+	// MergePolicy replaces the default log merge policy when set.
+	MergePolicy *MergePolicy `json:"merge_policy,omitempty"`
+	// ReaderReload decides when searches see new commits, ReloadOnSearch by default.
+	ReaderReload ReaderReload `json:"reader_reload,omitempty"`
+	// Utf8Lenient replaces invalid utf-8 in document values, queries and terms instead of failing.
+	// The value given to LibInit when nil.
+	Utf8Lenient *bool `json:"utf8_lenient,omitempty"`
+	// ClearOnPanic removes the index directory when a call on the context panics, which poisons the
+	// context, or when the process panics outside of a call.
+	// The value given to LibInit when nil.
+	ClearOnPanic *bool `json:"clear_on_panic,omitempty"`
+}
+
+// ReaderReload is the policy reloading the index reader of a TantivyContext.
+type ReaderReload string
+
+const (
+	// ReloadOnSearch reloads the reader before every search, which then sees every commit.
+	// A search fails when the reload fails.
+	ReloadOnSearch ReaderReload = "on_search"
+	// ReloadOnCommitWithDelay reloads the reader shortly after every commit.
+	ReloadOnCommitWithDelay ReaderReload = "on_commit_with_delay"
+	// ReloadManual reloads the reader only on TantivyContext.ReloadReader.
+	ReloadManual ReaderReload = "manual"
+)
+
+// MergePolicy holds the parameters of the log merge policy, which merges segments of similar sizes.
+// Zero values keep the tantivy defaults.
+type MergePolicy struct {
+	// MinNumSegments is the minimum number of segments of a level to merge them.
+	MinNumSegments uint64 `json:"min_num_segments,omitempty"`
+	// MaxDocsBeforeMerge is the number of documents above which a segment is not merged anymore.
+	MaxDocsBeforeMerge uint64 `json:"max_docs_before_merge,omitempty"`
+	// MinLayerSize is the number of documents below which segments all belong to the first level.
+	MinLayerSize uint32 `json:"min_layer_size,omitempty"`
+	// LevelLogSize is the log size ratio between two levels.
+	LevelLogSize float64 `json:"level_log_size,omitempty"`
+	// DelDocsRatioBeforeMerge is the ratio of deleted documents, in (0, 1], above which a segment is merged.
+	DelDocsRatioBeforeMerge float32 `json:"del_docs_ratio_before_merge,omitempty"`
+}
+
+// contextOptionsCString returns nil when no option is set.
+func contextOptionsCString(options *ContextOptions) (*C.char, error) {
+	if options == nil {
+		return nil, nil
+	}
+	optionsJson, err := json.Marshal(options)
+	if err != nil {
+		return nil, fmt.Errorf("failed to serialize context options: %w", err)
+	}
+	return C.CString(string(optionsJson)), nil
+}
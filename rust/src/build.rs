@@ -27,8 +27,10 @@ fn add_typedefs() -> io::Result<()> {
 
 fn main() -> Result<(), Box<dyn Error>> {
     let crate_dir = env::var("CARGO_MANIFEST_DIR")?;
-    let mut config: cbindgen::Config = Default::default();
-    config.language = cbindgen::Language::C;
+    let config = cbindgen::Config {
+        language: cbindgen::Language::C,
+        ..Default::default()
+    };
 
     match cbindgen::Builder::new()
         .with_crate(crate_dir)
@@ -1,20 +1,52 @@
 mod util;
 
 pub use self::util::set_error;
+pub use self::util::ErrorBuffer;
+pub use self::util::catch_panic;
+pub use self::util::catch_context_panic;
+pub use self::util::log_panic;
 pub use self::util::assert_string;
 pub use self::util::assert_str;
+pub use self::util::context_str;
 pub use self::util::assert_pointer;
+pub use self::util::assert_ref;
 pub use self::util::convert_document_as_json;
 pub use self::util::start_lib_init;
 pub use self::util::box_from;
 pub use self::util::add_and_consume_documents;
 pub use self::util::delete_docs;
+pub use self::util::add_documents;
+pub use self::util::consume_documents;
+pub use self::util::set_failures;
+pub use self::util::delete_terms;
+pub use self::util::delete_by_query;
+pub use self::util::delete_all;
+pub use self::util::upsert_documents;
+pub use self::util::commit;
+pub use self::util::rollback;
+pub use self::util::payload_from_ptr;
+pub use self::util::last_commit_payload;
 pub use self::util::create_context_with_schema;
+pub use self::util::context_options_from_ptr;
+pub use self::util::aggregate;
+pub use self::util::count;
 pub use self::util::search;
-pub use self::util::search_fast_field;
-pub use self::util::search_fast_field_json;
+pub use self::util::search_fast_fields;
+pub use self::util::fast_field_value;
+pub use self::util::typed_fast_field_value;
 pub use self::util::search_json;
+pub use self::util::searcher_from_ptr;
+pub use self::util::search_options_from_ptr;
 pub use self::util::drop_any;
 pub use self::util::get_doc;
 pub use self::util::add_field;
-pub use self::util::add_fields;
\ No newline at end of file
+pub use self::util::add_fields;
+pub use self::util::add_i64_value;
+pub use self::util::add_u64_value;
+pub use self::util::add_f64_value;
+pub use self::util::add_bool_value;
+pub use self::util::add_date_value;
+pub use self::util::add_json_value;
+pub use self::util::add_facet_value;
+pub use self::util::index_record_option_from_const;
+pub use self::util::date_precision_from_const;
\ No newline at end of file
@@ -1,29 +1,99 @@
-use crate::config;
+use crate::config::{catching_panics, set_lib_defaults, ContextSettings, LibDefaults};
 use crate::queries::parse_query_from_json;
 use crate::tantivy_util::{
-    convert_document_to_json, find_highlights, read_fast_field_values, Document, SearchResult,
-    TantivyContext, TantivyGoError, DOCUMENT_BUDGET_BYTES,
+    collect_top_docs, convert_document_to_json, find_highlights, parse_facet,
+    read_fast_field_values, schema_field, CheckedDocuments, CollapsedHits, ContextOptions,
+    ContextWriter, Document, DocumentFailure, FastFieldResult, FastFieldValue, IndexingPolicy,
+    PinnedSearcher, SearchOptions, SearchPage, SearchResult, TantivyContext, TantivyGoError,
+    Upsert, DATE_PRECISION_MICROSECONDS, DATE_PRECISION_MILLISECONDS, DATE_PRECISION_NANOSECONDS,
+    DATE_PRECISION_SECONDS, NO_VERSION,
 };
-use log::debug;
+use log::{debug, error};
 use serde_json::json;
+use std::any::Any;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::{c_uint, CStr, CString};
 use std::os::raw::{c_char, c_float};
-use std::panic::PanicHookInfo;
+use std::panic::AssertUnwindSafe;
 use std::path::Path;
-use std::{fs, panic, slice};
+use std::{fs, slice};
+use tantivy::aggregation::agg_req::Aggregations;
+use tantivy::aggregation::{AggregationCollector, AggregationLimitsGuard};
 use tantivy::directory::MmapDirectory;
-use tantivy::query::{Query, QueryParser};
-use tantivy::schema::{Field, Schema};
-use tantivy::{Index, IndexWriter, Opstamp, ReloadPolicy, Score, TantivyDocument, TantivyError, Term};
-
-pub fn set_error(err: &str, error_buffer: *mut *mut c_char) {
-    let err_str = match CString::new(err) {
-        Ok(s) => s,
-        Err(_) => return,
+use tantivy::query::{Query, QueryParser, QueryParserError};
+use tantivy::schema::{DateTimePrecision, Field, IndexRecordOption, OwnedValue, Schema};
+use tantivy::{DateTime, DocAddress, Index, Opstamp, Score, Searcher, TantivyDocument, Term};
+
+/// Runs the body of an extern "C" function, returning a panic as an error instead of unwinding
+/// into the host.
+pub fn catch_panic<T, F>(body: F) -> Result<T, TantivyGoError>
+where
+    F: FnOnce() -> Result<T, TantivyGoError>,
+{
+    catching_panics(AssertUnwindSafe(body)).unwrap_or_else(|payload| Err(panic_error(payload)))
+}
+
+/// Like `catch_panic` for a call on a context: a panic poisons the context,
+/// and calls on a poisoned context fail without running.
+pub fn catch_context_panic<T, F>(
+    context_ptr: *const TantivyContext,
+    body: F,
+) -> Result<T, TantivyGoError>
+where
+    F: FnOnce() -> Result<T, TantivyGoError>,
+{
+    let context = unsafe { context_ptr.as_ref() };
+    if let Some(context) = context {
+        context.check_poisoned()?;
+    }
+    catching_panics(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        if let Some(context) = context {
+            context.poison();
+        }
+        Err(panic_error(payload))
+    })
+}
+
+/// Like `catch_panic` for functions without an error buffer, logging the panic.
+pub fn log_panic<T, F>(fallback: T, body: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    catching_panics(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        error!("{}", panic_error(payload));
+        fallback
+    })
+}
+
+fn panic_error(payload: Box<dyn Any + Send>) -> TantivyGoError {
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown cause".to_string(),
+        },
+    };
+    TantivyGoError::Panic(format!("Panic: {}", message))
+}
+
+/// Error of a failing call, `code` is one of the `ERROR_*` constants. The message is to be
+/// freed with `string_free`, both are left untouched by a successful call.
+#[repr(C)]
+pub struct ErrorBuffer {
+    pub code: u32,
+    pub message: *mut c_char,
+}
+
+pub fn set_error(err: &TantivyGoError, error_buffer: *mut ErrorBuffer) {
+    let error_buffer = match unsafe { error_buffer.as_mut() } {
+        Some(error_buffer) => error_buffer,
+        None => return,
     };
-    write_buffer(error_buffer, err_str);
+    error_buffer.code = err.code();
+    // Interior NULs, e.g. from a user value, would make the message fail to convert
+    let err_str = CString::new(err.message().replace('\0', "\\0")).unwrap_or_default();
+    write_buffer(&mut error_buffer.message, err_str);
 }
 
 fn write_buffer(error_buffer: *mut *mut c_char, err_str: CString) {
@@ -37,23 +107,29 @@ fn write_buffer(error_buffer: *mut *mut c_char, err_str: CString) {
 
 // Try not to copy one-time-living strings if possible
 pub fn assert_str<'a>(str_ptr: *const c_char) -> Result<Cow<'a, str>, TantivyGoError> {
+    decode_str(str_ptr, false)
+}
+
+/// Like `assert_str`, replacing invalid utf-8 when the context is utf-8 lenient.
+pub fn context_str<'a>(
+    context: &TantivyContext,
+    str_ptr: *const c_char,
+) -> Result<Cow<'a, str>, TantivyGoError> {
+    decode_str(str_ptr, context.settings().utf8_lenient)
+}
+
+fn decode_str<'a>(str_ptr: *const c_char, utf8_lenient: bool) -> Result<Cow<'a, str>, TantivyGoError> {
     unsafe {
         if str_ptr.is_null() {
-            return Err(TantivyGoError(POINTER_IS_NULL.to_owned()));
+            return Err(TantivyGoError::NullPointer(POINTER_IS_NULL.to_owned()));
         }
-        let is_lenient = match config::CONFIG.read() {
-            Ok(config) => config.utf8_lenient,
-            Err(err) => {
-                return Err(TantivyGoError(err.to_string()));
-            }
-        };
         let cstr = CStr::from_ptr(str_ptr);
-        if is_lenient {
+        if utf8_lenient {
             Ok(cstr.to_string_lossy())
         } else {
             match cstr.to_str() {
                 Ok(valid_str) => Ok(Cow::Borrowed(valid_str)),
-                Err(err) => Err(TantivyGoError(err.to_string())),
+                Err(err) => Err(TantivyGoError::InvalidUtf8(err.to_string())),
             }
         }
     }
@@ -66,12 +142,20 @@ pub fn assert_string(str_ptr: *const c_char) -> Result<String, TantivyGoError> {
 
 pub fn assert_pointer<'a, T>(ptr: *mut T) -> Result<&'a mut T, TantivyGoError> {
     if ptr.is_null() {
-        return Err(TantivyGoError(POINTER_IS_NULL.to_owned()));
+        return Err(TantivyGoError::NullPointer(POINTER_IS_NULL.to_owned()));
     }
     unsafe { Ok(&mut *ptr) }
 }
 
-pub fn process_type_slice<'a, T, F>(
+/// Shared reference for handles used from several threads at once, like `TantivyContext`.
+pub fn assert_ref<'a, T>(ptr: *const T) -> Result<&'a T, TantivyGoError> {
+    if ptr.is_null() {
+        return Err(TantivyGoError::NullPointer(POINTER_IS_NULL.to_owned()));
+    }
+    unsafe { Ok(&*ptr) }
+}
+
+pub fn process_type_slice<T, F>(
     ptr: *mut *mut T,
     len: usize,
     mut func: F,
@@ -89,17 +173,20 @@ where
 pub fn process_string_slice<'a, F>(
     ptr: *mut *const c_char,
     len: usize,
+    utf8_lenient: bool,
     mut func: F,
 ) -> Result<(), TantivyGoError>
 where
     F: FnMut(Cow<'a, str>) -> Result<(), TantivyGoError>,
 {
     let slice = unsafe { slice::from_raw_parts(assert_pointer(ptr)?, len) };
-    slice.iter().try_for_each(|&item| func(assert_str(item)?))?;
+    slice
+        .iter()
+        .try_for_each(|&item| func(decode_str(item, utf8_lenient)?))?;
     Ok(())
 }
 
-pub fn process_slice<'a, F, T>(ptr: *mut T, len: usize, mut func: F) -> Result<(), TantivyGoError>
+pub fn process_slice<F, T>(ptr: *mut T, len: usize, mut func: F) -> Result<(), TantivyGoError>
 where
     F: FnMut(usize, T) -> Result<(), TantivyGoError>,
     T: Copy,
@@ -121,7 +208,7 @@ pub fn convert_document_as_json(
     let mut field_to_name = HashMap::new();
 
     process_slice(field_ids, field_ids_len, |_, field_id| {
-        let field = Field::from_field_id(field_id);
+        let field = schema_field(&schema, field_id)?;
         field_to_name.insert(field, Cow::from(schema.get_field_name(field)));
         Ok(())
     })?;
@@ -132,58 +219,28 @@ pub fn convert_document_as_json(
 }
 
 pub fn start_lib_init(log_level: &str, clear_on_panic: bool, utf8_lenient: bool) {
-    let old_hook = panic::take_hook();
-    if clear_on_panic {
-        handle_panic(old_hook);
-    }
-
-    set_utf8_lenient(utf8_lenient);
+    set_lib_defaults(LibDefaults {
+        utf8_lenient,
+        clear_on_panic,
+    });
 
     let _ = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level))
         .try_init();
 }
 
-fn set_utf8_lenient(utf8_lenient: bool) {
-    if let Err(e) = config::CONFIG.write().map(|mut config| {
-        config.update_utf8_lenient(utf8_lenient);
-    }) {
-        debug!("Failed to set utf8_lenient: {}", e);
-    }
-}
-
-fn handle_panic(old_hook: Box<dyn Fn(&PanicHookInfo) + Sync + Send>) {
-    panic::set_hook(Box::new(move |panic_info| {
-        if let Ok(config) = config::CONFIG.read() {
-            let fts_path = config.fts_path.as_str();
-            if fts_path.is_empty() {
-                debug!("fts path is empty");
-            } else if let Err(e) = fs::remove_dir_all(Path::new(fts_path)) {
-                debug!("Failed to remove directory: {}", e);
-            }
-        } else {
-            debug!("Failed to read config.");
-        }
-        old_hook(panic_info)
-    }));
-}
-
 pub fn create_context_with_schema(
     schema: Schema,
     path: String,
+    options: ContextOptions,
 ) -> Result<*mut TantivyContext, TantivyGoError> {
-    config::CONFIG
-        .write()
-        .map_err(|e| TantivyGoError::from_err("Failed to set path", &e.to_string()))?
-        .update_fts_path(path.clone());
-
     fs::create_dir_all(Path::new(&path))
-        .map_err(|e| TantivyGoError::from_err("Failed to create directories", &e.to_string()))?;
+        .map_err(|e| TantivyGoError::Io(format!("Failed to create directories: {}", e)))?;
 
     let dir =
-        MmapDirectory::open(&path).map_err(|err| TantivyGoError::from_str(&err.to_string()))?;
+        MmapDirectory::open(&path).map_err(|err| TantivyGoError::Io(err.to_string()))?;
 
-    let ctx = create_tantivy_context(dir, schema)
-        .map_err(|err| TantivyGoError::from_str(&err.to_string()))?;
+    let settings = options.settings(path);
+    let ctx = create_tantivy_context(dir, schema, &options, settings)?;
 
     Ok(Box::into_raw(Box::new(ctx)))
 }
@@ -191,85 +248,278 @@ pub fn create_context_with_schema(
 fn create_tantivy_context(
     dir: MmapDirectory,
     schema: Schema,
-) -> Result<TantivyContext, TantivyError> {
-    let index = Index::open_or_create(dir, schema)?;
-    let writer = index.writer(DOCUMENT_BUDGET_BYTES)?;
-    let reader = index.reader_builder().reload_policy(ReloadPolicy::Manual).try_into()?;
-    Ok(TantivyContext::new(index, writer, reader))
+    options: &ContextOptions,
+    settings: ContextSettings,
+) -> Result<TantivyContext, TantivyGoError> {
+    let index = Index::open_or_create(dir, schema)
+        .map_err(|err| TantivyGoError::from_tantivy("Failed to open the index", &err))?;
+    let merge_policy = options.log_merge_policy()?;
+    let writer = options.create_writer(&index)?;
+    let reader = index
+        .reader_builder()
+        .reload_policy(options.reader_reload.reload_policy())
+        .try_into()
+        .map_err(|err| TantivyGoError::from_tantivy("Failed to create the reader", &err))?;
+    Ok(TantivyContext::new(
+        index,
+        writer,
+        reader,
+        options.clone(),
+        merge_policy,
+        settings,
+    ))
+}
+
+/// Reads the context options, the defaults when the pointer is null.
+pub fn context_options_from_ptr(options_ptr: *const c_char) -> Result<ContextOptions, TantivyGoError> {
+    if options_ptr.is_null() {
+        return Ok(ContextOptions::default());
+    }
+    ContextOptions::parse(&assert_str(options_ptr)?)
 }
 
 pub fn add_and_consume_documents(
     docs_ptr: *mut *mut Document,
     docs_len: usize,
-    writer: &mut IndexWriter,
-) -> Result<Opstamp, TantivyGoError> {
+    context: &TantivyContext,
+    payload: Option<String>,
+    policy: IndexingPolicy,
+) -> Result<(Opstamp, Vec<DocumentFailure>), TantivyGoError> {
+    let docs = consume_documents(docs_ptr, docs_len)?;
+    let mut writer = context.writer()?;
+    let (_, failures) = stage_documents(&context.index, &mut writer, docs, policy)?;
+
+    let opstamp = commit(&mut writer, payload, "Failed to commit the document")?;
+    Ok((opstamp, failures))
+}
+
+/// Stages documents without committing them following the indexing policy.
+/// Returns the opstamp of the last one and the documents that failed.
+pub fn add_documents(
+    docs_ptr: *mut *mut Document,
+    docs_len: usize,
+    context: &TantivyContext,
+    policy: IndexingPolicy,
+) -> Result<(Opstamp, Vec<DocumentFailure>), TantivyGoError> {
+    let docs = consume_documents(docs_ptr, docs_len)?;
+    let mut writer = context.writer()?;
+    stage_documents(&context.index, &mut writer, docs, policy)
+}
+
+fn stage_documents(
+    index: &Index,
+    writer: &mut ContextWriter,
+    docs: Vec<TantivyDocument>,
+    policy: IndexingPolicy,
+) -> Result<(Opstamp, Vec<DocumentFailure>), TantivyGoError> {
+    CheckedDocuments::check(index, docs, policy)?
+        .stage(writer)
+        .inspect_err(|_| rollback(writer))
+}
+
+/// Takes ownership of the documents, none when `docs_len` is 0.
+pub fn consume_documents(
+    docs_ptr: *mut *mut Document,
+    docs_len: usize,
+) -> Result<Vec<TantivyDocument>, TantivyGoError> {
+    let mut docs = Vec::with_capacity(docs_len);
+    if docs_len == 0 {
+        return Ok(docs);
+    }
     process_type_slice(docs_ptr, docs_len, |doc| {
-        let doc = *box_from(doc);
-        let _ = writer.add_document(doc.tantivy_doc);
+        docs.push(box_from(doc).tantivy_doc);
         Ok(())
-    })
-    .map_err(|err| {
-        rollback(writer);
-        TantivyGoError(format!("Failed to add the document: {}", err))
     })?;
+    Ok(docs)
+}
 
-    let opstamp = commit(writer, "Failed to commit the document")?;
-    Ok(opstamp)
+/// Writes the failed documents of a batch as a json array, nothing when none failed.
+pub fn set_failures(
+    failures: &[DocumentFailure],
+    failures_buffer: *mut *mut c_char,
+) -> Result<(), TantivyGoError> {
+    if failures.is_empty() {
+        return Ok(());
+    }
+    let json = serde_json::to_string(failures)
+        .map_err(|err| TantivyGoError::from_err("Failed to serialize failures", &err.to_string()))?;
+    let json = CString::new(json)
+        .map_err(|err| TantivyGoError::from_err("Failed to serialize failures", &err.to_string()))?;
+    write_buffer(failures_buffer, json);
+    Ok(())
 }
 
-fn commit(writer: &mut IndexWriter, message: &str) -> Result<Opstamp, TantivyGoError> {
-    writer.commit().map_err(|err| {
+/// Commits the staged operations, storing `payload` in the index meta when given.
+pub fn commit(
+    writer: &mut ContextWriter,
+    payload: Option<String>,
+    message: &str,
+) -> Result<Opstamp, TantivyGoError> {
+    let result = writer.prepare_commit().and_then(|mut prepared_commit| {
+        if let Some(payload) = payload {
+            prepared_commit.set_payload(&payload);
+        }
+        prepared_commit.commit()
+    });
+    result.map_err(|err| {
         rollback(writer);
-        TantivyGoError::from_err(message, &err.to_string())
+        TantivyGoError::from_tantivy(message, &err)
     })
 }
 
-pub fn delete_docs<'a>(
+/// Reads the payload of a commit, none when the pointer is null.
+pub fn payload_from_ptr(payload_ptr: *const c_char) -> Result<Option<String>, TantivyGoError> {
+    if payload_ptr.is_null() {
+        return Ok(None);
+    }
+    assert_string(payload_ptr).map(Some)
+}
+
+/// Payload of the last commit, read from the index meta.
+pub fn last_commit_payload(index: &Index) -> Result<Option<String>, TantivyGoError> {
+    index
+        .load_metas()
+        .map(|metas| metas.payload)
+        .map_err(|err| TantivyGoError::from_tantivy("Failed to load index meta", &err))
+}
+
+pub fn delete_docs(
     delete_ids_ptr: *mut *const c_char,
     delete_ids_len: usize,
-    context: &mut TantivyContext,
+    context: &TantivyContext,
     field_id: u32,
+    payload: Option<String>,
 ) -> Result<Opstamp, TantivyGoError> {
-    let field = Field::from_field_id(field_id);
+    let mut writer = context.writer()?;
+    delete_terms(
+        delete_ids_ptr,
+        delete_ids_len,
+        context.settings().utf8_lenient,
+        &mut writer,
+        field_id,
+    )?;
+
+    let opstamp = commit(&mut writer, payload, "Failed to commit removing")?;
+    Ok(opstamp)
+}
+
+/// Stages the deletion of the documents having one of the terms in the field without
+/// committing it, returning the opstamp of the last deletion.
+pub fn delete_terms(
+    delete_ids_ptr: *mut *const c_char,
+    delete_ids_len: usize,
+    utf8_lenient: bool,
+    writer: &mut ContextWriter,
+    field_id: u32,
+) -> Result<Opstamp, TantivyGoError> {
+    let field = schema_field(&writer.index().schema(), field_id)?;
 
-    process_string_slice(delete_ids_ptr, delete_ids_len, |id_value| {
-        context
-            .writer
-            .delete_term(Term::from_field_text(field, &id_value));
+    let mut opstamp = 0;
+    process_string_slice(delete_ids_ptr, delete_ids_len, utf8_lenient, |id_value| {
+        opstamp = writer.delete_term(Term::from_field_text(field, &id_value));
         Ok(())
     })
-    .map_err(|err| {
-        rollback(&mut context.writer);
-        err
+    .inspect_err(|_| rollback(writer))?;
+    Ok(opstamp)
+}
+
+/// Deletes the documents matching a query and commits.
+pub fn delete_by_query(
+    field_ids: *mut c_uint,
+    field_weights_ptr: *mut c_float,
+    field_ids_len: usize,
+    query_ptr: *const c_char,
+    query_is_json: bool,
+    context: &TantivyContext,
+    payload: Option<String>,
+) -> Result<Opstamp, TantivyGoError> {
+    let query = parse_any_query(
+        context,
+        field_ids,
+        field_weights_ptr,
+        field_ids_len,
+        query_ptr,
+        query_is_json,
+    )?;
+
+    let mut writer = context.writer()?;
+    writer.delete_query(query).map_err(|err| {
+        rollback(&mut writer);
+        TantivyGoError::from_tantivy("Failed to delete by query", &err)
     })?;
 
-    let opstamp = commit(&mut context.writer, "Failed to commit removing")?;
-    Ok(opstamp)
+    commit(&mut writer, payload, "Failed to commit removing")
+}
+
+/// Deletes every document of the index and commits, keeping the index directory and schema.
+pub fn delete_all(
+    context: &TantivyContext,
+    payload: Option<String>,
+) -> Result<Opstamp, TantivyGoError> {
+    let mut writer = context.writer()?;
+    writer.delete_all_documents().map_err(|err| {
+        rollback(&mut writer);
+        TantivyGoError::from_tantivy("Failed to delete all documents", &err)
+    })?;
+
+    commit(&mut writer, payload, "Failed to commit removing")
+}
+
+/// Replaces the indexed documents having the id of a new document and commits.
+/// With a version field other than `NO_VERSION` only newer documents replace the indexed ones.
+pub fn upsert_documents(
+    docs_ptr: *mut *mut Document,
+    docs_len: usize,
+    id_field_id: u32,
+    version_field_id: u32,
+    context: &TantivyContext,
+    payload: Option<String>,
+) -> Result<Opstamp, TantivyGoError> {
+    let docs = consume_documents(docs_ptr, docs_len)?;
+
+    let schema = context.index.schema();
+    let upsert = Upsert {
+        id_field: schema_field(&schema, id_field_id)?,
+        version_field: match version_field_id {
+            NO_VERSION => None,
+            field_id => Some(schema_field(&schema, field_id)?),
+        },
+    };
+    let mut writer = context.writer()?;
+    // The reader of the context may not have loaded the last commit yet
+    let searcher = context.last_commit_searcher()?;
+    let skipped = upsert
+        .stage(&mut writer, &searcher, docs)
+        .inspect_err(|_| rollback(&mut writer))?;
+    debug!("Upsert skipped {} documents that are not newer", skipped);
+
+    commit(&mut writer, payload, "Failed to commit upsert")
 }
 
-fn rollback(writer: &mut IndexWriter) {
-    // TEMPORARILY DISABLED: Tantivy has a critical bug in the rollback mechanism
-    // https://github.com/quickwit-oss/tantivy/issues/2666
-    // TODO: Re-enable when the bug is fixed
-    // let _ = writer.rollback();
-    let _ = writer; // Suppress unused variable warning
+/// Drops the operations staged since the last commit after a failed call, logging a failure.
+pub fn rollback(writer: &mut ContextWriter) {
+    if let Err(err) = writer.rollback() {
+        error!("Failed to rollback: {}", err);
+    }
 }
 
-pub fn get_doc<'a>(
+pub fn get_doc(
     index: usize,
     result: &mut SearchResult,
 ) -> Result<*mut Document, TantivyGoError> {
     if index >= result.documents.len() {
-        return Err(TantivyGoError(
-            format!("{} is more than {}", index, result.documents.len() - 1).to_string(),
-        ));
+        return Err(TantivyGoError::InvalidArgument(format!(
+            "{} is more than {}",
+            index,
+            result.documents.len() - 1
+        )));
     }
 
     let doc = result.documents[index].clone();
     Ok(Box::into_raw(Box::new(doc)))
 }
 
-pub fn add_fields<'a>(
+pub fn add_fields(
     doc: &mut Document,
     field_ids: *mut c_uint,
     field_ids_len: usize,
@@ -284,7 +534,7 @@ pub fn add_fields<'a>(
     Ok(())
 }
 
-pub fn add_field<'a>(
+pub fn add_field(
     doc: &mut Document,
     field_id: u32,
     field_value: &str,
@@ -294,56 +544,182 @@ pub fn add_field<'a>(
     Ok(())
 }
 
+pub fn add_i64_value(doc: &mut Document, field_id: u32, value: i64) -> Result<(), TantivyGoError> {
+    doc.tantivy_doc.add_i64(Field::from_field_id(field_id), value);
+    Ok(())
+}
+
+pub fn add_u64_value(doc: &mut Document, field_id: u32, value: u64) -> Result<(), TantivyGoError> {
+    doc.tantivy_doc.add_u64(Field::from_field_id(field_id), value);
+    Ok(())
+}
+
+pub fn add_f64_value(doc: &mut Document, field_id: u32, value: f64) -> Result<(), TantivyGoError> {
+    doc.tantivy_doc.add_f64(Field::from_field_id(field_id), value);
+    Ok(())
+}
+
+pub fn add_bool_value(doc: &mut Document, field_id: u32, value: bool) -> Result<(), TantivyGoError> {
+    doc.tantivy_doc.add_bool(Field::from_field_id(field_id), value);
+    Ok(())
+}
+
+/// Adds a date value given as nanoseconds since the Unix epoch.
+pub fn add_date_value(
+    doc: &mut Document,
+    field_id: u32,
+    timestamp_nanos: i64,
+) -> Result<(), TantivyGoError> {
+    doc.tantivy_doc.add_date(
+        Field::from_field_id(field_id),
+        DateTime::from_timestamp_nanos(timestamp_nanos),
+    );
+    Ok(())
+}
+
+/// Adds a facet given as a path like `/space/abc/type/note`.
+pub fn add_facet_value(doc: &mut Document, field_id: u32, path: &str) -> Result<(), TantivyGoError> {
+    let facet = parse_facet(path)?;
+    doc.tantivy_doc.add_facet(Field::from_field_id(field_id), facet);
+    Ok(())
+}
+
+/// Adds a json object given as a serialized string.
+pub fn add_json_value(doc: &mut Document, field_id: u32, json: &str) -> Result<(), TantivyGoError> {
+    let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json)
+        .map_err(|err| TantivyGoError::InvalidDocument(format!("Failed to parse json object: {}", err)))?;
+    let object: BTreeMap<String, OwnedValue> = object
+        .into_iter()
+        .map(|(key, value)| (key, OwnedValue::from(value)))
+        .collect();
+    doc.tantivy_doc.add_object(Field::from_field_id(field_id), object);
+    Ok(())
+}
+
+pub fn index_record_option_from_const(
+    index_record_option_const: usize,
+) -> Result<IndexRecordOption, TantivyGoError> {
+    match index_record_option_const {
+        0 => Ok(IndexRecordOption::Basic),
+        1 => Ok(IndexRecordOption::WithFreqs),
+        2 => Ok(IndexRecordOption::WithFreqsAndPositions),
+        _ => Err(TantivyGoError::InvalidArgument(
+            "Invalid index_record_option_const".to_string(),
+        )),
+    }
+}
+
+pub fn date_precision_from_const(precision_const: u8) -> Result<DateTimePrecision, TantivyGoError> {
+    match precision_const {
+        DATE_PRECISION_SECONDS => Ok(DateTimePrecision::Seconds),
+        DATE_PRECISION_MILLISECONDS => Ok(DateTimePrecision::Milliseconds),
+        DATE_PRECISION_MICROSECONDS => Ok(DateTimePrecision::Microseconds),
+        DATE_PRECISION_NANOSECONDS => Ok(DateTimePrecision::Nanoseconds),
+        _ => Err(TantivyGoError::InvalidArgument(format!(
+            "Unknown date precision {}",
+            precision_const
+        ))),
+    }
+}
+
+/// Reads the search options, the defaults when the pointer is null.
+pub fn search_options_from_ptr(options_ptr: *const c_char) -> Result<SearchOptions, TantivyGoError> {
+    if options_ptr.is_null() {
+        return Ok(SearchOptions::default());
+    }
+    SearchOptions::parse(&assert_str(options_ptr)?)
+}
+
+/// Searcher of a pinned searcher handle, a fresh one from the context when the pointer is null.
+pub fn searcher_from_ptr(
+    context: &TantivyContext,
+    searcher_ptr: *const PinnedSearcher,
+) -> Result<Searcher, TantivyGoError> {
+    if searcher_ptr.is_null() {
+        return context.searcher();
+    }
+    Ok(assert_ref(searcher_ptr)?.searcher.clone())
+}
+
 fn perform_search<F>(
     query_parser_fn: F,
-    docs_limit: usize,
-    context: &mut TantivyContext,
+    page: &SearchPage,
+    context: &TantivyContext,
+    searcher: &Searcher,
     with_highlights: bool,
 ) -> Result<*mut SearchResult, TantivyGoError>
 where
-    F: FnOnce(&Index) -> Result<Box<dyn Query>, String>,
+    F: FnOnce(&Index) -> Result<Box<dyn Query>, TantivyGoError>,
 {
-    let searcher = &context.reader().searcher();
     let schema = context.index.schema();
 
-    let query = query_parser_fn(&context.index).map_err(|err| TantivyGoError(err))?;
+    let query = query_parser_fn(&context.index)?;
 
-    let top_docs = searcher
-        .search(&query, &tantivy::collector::TopDocs::with_limit(docs_limit))
-        .map_err(|err| TantivyGoError::from_err("Search err", &err.to_string()))?;
+    let top_docs = collect_top_docs(searcher, &schema, &query, page)?;
 
+    let mut collapsed = top_docs.collapsed.map(|siblings| siblings.into_iter());
     let mut documents = Vec::new();
-    for (score, doc_address) in top_docs {
+    for (score, doc_address) in top_docs.docs {
         //let explanation = query.explain(&searcher, doc_address).unwrap();
         //debug!("### exp {:#?}", explanation);
         let doc = searcher
             .doc::<TantivyDocument>(doc_address)
-            .map_err(|err| TantivyGoError(err.to_string()))?;
-        let highlights = find_highlights(with_highlights, &searcher, &query, &doc, schema.clone())
-            .map_err(|err| TantivyGoError(err.to_string()))?;
+            .map_err(|err| TantivyGoError::from_tantivy("Failed to load the document", &err))?;
+        let highlights = find_highlights(with_highlights, searcher, &query, &doc, schema.clone())
+            .map_err(|err| TantivyGoError::from_tantivy("Failed to find highlights", &err))?;
+        let collapsed = match collapsed.as_mut().and_then(|siblings| siblings.next()) {
+            Some(siblings) => {
+                let mut highlights = Vec::new();
+                for (_, sibling_address) in siblings.top_docs {
+                    let sibling = searcher
+                        .doc::<TantivyDocument>(sibling_address)
+                        .map_err(|err| {
+                            TantivyGoError::from_tantivy("Failed to load the document", &err)
+                        })?;
+                    highlights.extend(
+                        find_highlights(with_highlights, searcher, &query, &sibling, schema.clone())
+                            .map_err(|err| {
+                                TantivyGoError::from_tantivy("Failed to find highlights", &err)
+                            })?,
+                    );
+                }
+                Some(CollapsedHits { count: siblings.count, highlights })
+            }
+            None => None,
+        };
         documents.push(Document {
             tantivy_doc: doc,
             highlights,
             score,
+            collapsed,
         });
     }
 
     let size = documents.len();
-    Ok(Box::into_raw(Box::new(SearchResult { documents, size })))
+    let cursor = top_docs.cursor.map(|cursor| cursor.to_string());
+    Ok(Box::into_raw(Box::new(SearchResult {
+        documents,
+        size,
+        total_hits: top_docs.total_hits,
+        cursor,
+        facet_counts: top_docs.facet_counts,
+    })))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn search(
     field_ids: *mut c_uint,
     field_weights_ptr: *mut c_float,
     field_ids_len: usize,
     query_ptr: *const c_char,
-    docs_limit: usize,
-    context: &mut TantivyContext,
+    page: &SearchPage,
+    context: &TantivyContext,
+    searcher: &Searcher,
     with_highlights: bool,
 ) -> Result<*mut SearchResult, TantivyGoError> {
     let mut fields = Vec::with_capacity(field_ids_len);
     process_slice(field_ids, field_ids_len, |_, field_id| {
-        fields.push(Field::from_field_id(field_id));
+        fields.push(schema_field(searcher.schema(), field_id)?);
         Ok(())
     })?;
 
@@ -353,7 +729,7 @@ pub fn search(
         Ok(())
     })?;
 
-    let query_str = assert_string(query_ptr)?;
+    let query_str = context_str(context, query_ptr)?;
 
     perform_search(
         |index: &Index| {
@@ -362,48 +738,46 @@ pub fn search(
                 query_parser.set_field_boost(field, weight as Score);
             }
             query_parser
-                .parse_query(query_str.as_str())
-                .map_err(|e| e.to_string())
+                .parse_query(&query_str)
+                .map_err(query_parser_error)
         },
-        docs_limit,
+        page,
         context,
+        searcher,
         with_highlights,
     )
 }
 
 pub fn search_json(
     query_ptr: *const c_char,
-    docs_limit: usize,
-    context: &mut TantivyContext,
+    page: &SearchPage,
+    context: &TantivyContext,
+    searcher: &Searcher,
     with_highlights: bool,
 ) -> Result<*mut SearchResult, TantivyGoError> {
     let schema = context.index.schema();
 
-    let query_str = assert_string(query_ptr)?;
+    let query_str = context_str(context, query_ptr)?;
 
     perform_search(
-        |index: &Index| {
-            parse_query_from_json(index, &schema, &query_str).map_err(|e| e.to_string())
-        },
-        docs_limit,
+        |index: &Index| parse_query_from_json(index, &schema, &query_str),
+        page,
         context,
+        searcher,
         with_highlights,
     )
 }
 
-/// Performs a search and returns only fast field values (no full document loading).
-pub fn search_fast_field(
+fn parse_string_query(
+    index: &Index,
     field_ids: *mut c_uint,
     field_weights_ptr: *mut c_float,
     field_ids_len: usize,
-    query_ptr: *const c_char,
-    fast_field_id: c_uint,
-    docs_limit: usize,
-    context: &mut TantivyContext,
-) -> Result<(Vec<f32>, Vec<Option<String>>), TantivyGoError> {
+    query_str: &str,
+) -> Result<Box<dyn Query>, TantivyGoError> {
     let mut fields = Vec::with_capacity(field_ids_len);
     process_slice(field_ids, field_ids_len, |_, field_id| {
-        fields.push(Field::from_field_id(field_id));
+        fields.push(schema_field(&index.schema(), field_id)?);
         Ok(())
     })?;
 
@@ -413,83 +787,187 @@ pub fn search_fast_field(
         Ok(())
     })?;
 
-    let query_str = assert_string(query_ptr)?;
-
-    let searcher = context.reader().searcher();
-    let schema = context.index.schema();
-    let fast_field = Field::from_field_id(fast_field_id);
-
-    let mut query_parser = QueryParser::for_index(&context.index, fields);
+    let mut query_parser = QueryParser::for_index(index, fields);
     for (field, weight) in weights {
         query_parser.set_field_boost(field, weight as Score);
     }
-    let query = query_parser
-        .parse_query(&query_str)
-        .map_err(|e| TantivyGoError(e.to_string()))?;
-
-    let top_docs = searcher
-        .search(&query, &tantivy::collector::TopDocs::with_limit(docs_limit))
-        .map_err(|err| TantivyGoError::from_err("Search err", &err.to_string()))?;
+    query_parser
+        .parse_query(query_str)
+        .map_err(query_parser_error)
+}
 
-    if top_docs.is_empty() {
-        return Ok((vec![], vec![]));
+fn query_parser_error(err: QueryParserError) -> TantivyGoError {
+    match err {
+        QueryParserError::FieldDoesNotExist(_) => TantivyGoError::UnknownField(err.to_string()),
+        _ => TantivyGoError::QueryParse(err.to_string()),
     }
+}
 
-    let mut scores = Vec::with_capacity(top_docs.len());
-    let mut doc_addresses = Vec::with_capacity(top_docs.len());
-
-    for (score, doc_address) in top_docs {
-        scores.push(score);
-        doc_addresses.push(doc_address);
+// Parses a query string, or a JSON query when `query_is_json` is set (the fields are ignored then)
+fn parse_any_query(
+    context: &TantivyContext,
+    field_ids: *mut c_uint,
+    field_weights_ptr: *mut c_float,
+    field_ids_len: usize,
+    query_ptr: *const c_char,
+    query_is_json: bool,
+) -> Result<Box<dyn Query>, TantivyGoError> {
+    let index = &context.index;
+    let query_str = context_str(context, query_ptr)?;
+
+    if query_is_json {
+        parse_query_from_json(index, &index.schema(), &query_str)
+    } else {
+        parse_string_query(index, field_ids, field_weights_ptr, field_ids_len, &query_str)
     }
-
-    let values = read_fast_field_values(&searcher, &schema, fast_field, &doc_addresses)?;
-
-    Ok((scores, values))
 }
 
-/// Performs a search using JSON query and returns only fast field values (no full document loading).
-pub fn search_fast_field_json(
+/// Counts the documents matching a query without collecting any document.
+pub fn count(
+    field_ids: *mut c_uint,
+    field_weights_ptr: *mut c_float,
+    field_ids_len: usize,
     query_ptr: *const c_char,
-    fast_field_id: c_uint,
-    docs_limit: usize,
-    context: &mut TantivyContext,
-) -> Result<(Vec<f32>, Vec<Option<String>>), TantivyGoError> {
-    let query_str = assert_string(query_ptr)?;
+    query_is_json: bool,
+    context: &TantivyContext,
+    searcher: &Searcher,
+) -> Result<usize, TantivyGoError> {
+    let query = parse_any_query(
+        context,
+        field_ids,
+        field_weights_ptr,
+        field_ids_len,
+        query_ptr,
+        query_is_json,
+    )?;
+
+    query
+        .count(searcher)
+        .map_err(|err| TantivyGoError::from_tantivy("Search err", &err))
+}
 
-    let searcher = context.reader().searcher();
-    let schema = context.index.schema();
-    let fast_field = Field::from_field_id(fast_field_id);
+/// Runs a request in the tantivy aggregation JSON format over the documents matching a query
+/// and returns the aggregation results as JSON.
+#[allow(clippy::too_many_arguments)]
+pub fn aggregate(
+    field_ids: *mut c_uint,
+    field_weights_ptr: *mut c_float,
+    field_ids_len: usize,
+    query_ptr: *const c_char,
+    query_is_json: bool,
+    aggregation_ptr: *const c_char,
+    context: &TantivyContext,
+    searcher: &Searcher,
+) -> Result<String, TantivyGoError> {
+    let query = parse_any_query(
+        context,
+        field_ids,
+        field_weights_ptr,
+        field_ids_len,
+        query_ptr,
+        query_is_json,
+    )?;
+
+    let aggregation_str = assert_str(aggregation_ptr)?;
+    let aggregations: Aggregations = serde_json::from_str(&aggregation_str)
+        .map_err(|err| {
+            TantivyGoError::InvalidArgument(format!("Invalid aggregation request: {}", err))
+        })?;
+    let collector =
+        AggregationCollector::from_aggs(aggregations, AggregationLimitsGuard::default());
+
+    let results = searcher
+        .search(&query, &collector)
+        .map_err(|err| TantivyGoError::from_tantivy("Aggregation err", &err))?;
+
+    serde_json::to_string(&results).map_err(|err| TantivyGoError::Internal(err.to_string()))
+}
 
-    let query = parse_query_from_json(&context.index, &schema, &query_str)
-        .map_err(|e| TantivyGoError(e.to_string()))?;
+/// Fast field values of a search page, with the cursor of the last document
+/// and the number of documents matching the query.
+/// Performs a search and returns only the values of the given fast fields
+/// (no full document loading). With `query_is_json` the query is parsed as the JSON query DSL
+/// and the fields are ignored.
+#[allow(clippy::too_many_arguments)]
+pub fn search_fast_fields(
+    field_ids: *mut c_uint,
+    field_weights_ptr: *mut c_float,
+    field_ids_len: usize,
+    query_ptr: *const c_char,
+    query_is_json: bool,
+    fast_field_ids: *mut c_uint,
+    fast_field_ids_len: usize,
+    page: &SearchPage,
+    context: &TantivyContext,
+    searcher: &Searcher,
+) -> Result<*mut FastFieldResult, TantivyGoError> {
+    let mut fast_fields = Vec::with_capacity(fast_field_ids_len);
+    process_slice(fast_field_ids, fast_field_ids_len, |_, field_id| {
+        fast_fields.push(schema_field(searcher.schema(), field_id)?);
+        Ok(())
+    })?;
 
-    let top_docs = searcher
-        .search(&query, &tantivy::collector::TopDocs::with_limit(docs_limit))
-        .map_err(|err| TantivyGoError::from_err("Search err", &err.to_string()))?;
+    let query = parse_any_query(
+        context,
+        field_ids,
+        field_weights_ptr,
+        field_ids_len,
+        query_ptr,
+        query_is_json,
+    )?;
 
-    if top_docs.is_empty() {
-        return Ok((vec![], vec![]));
-    }
+    let schema = context.index.schema();
 
-    let mut scores = Vec::with_capacity(top_docs.len());
-    let mut doc_addresses = Vec::with_capacity(top_docs.len());
+    let top_docs = collect_top_docs(searcher, &schema, &query, page)?;
+    let (scores, doc_addresses): (Vec<f32>, Vec<DocAddress>) = top_docs.docs.into_iter().unzip();
+    let columns = read_fast_field_values(searcher, &schema, &fast_fields, &doc_addresses)?;
 
-    for (score, doc_address) in top_docs {
-        scores.push(score);
-        doc_addresses.push(doc_address);
-    }
+    Ok(Box::into_raw(Box::new(FastFieldResult {
+        scores,
+        columns,
+        cursor: top_docs.cursor.map(|cursor| cursor.to_string()),
+        total_hits: top_docs.total_hits,
+    })))
+}
 
-    let values = read_fast_field_values(&searcher, &schema, fast_field, &doc_addresses)?;
+/// Returns the value of a fast field result cell, none when the document has no value.
+pub fn fast_field_value(
+    result: &FastFieldResult,
+    row: usize,
+    column: usize,
+) -> Result<Option<&FastFieldValue>, TantivyGoError> {
+    result
+        .columns
+        .get(column)
+        .and_then(|values| values.get(row))
+        .map(Option::as_ref)
+        .ok_or_else(|| {
+            TantivyGoError::InvalidArgument(format!(
+                "Fast field value {}:{} is out of range",
+                row, column
+            ))
+        })
+}
 
-    Ok((scores, values))
+/// Returns a typed fast field value, failing when the value is missing or of another type.
+pub fn typed_fast_field_value<T>(
+    result_ptr: *mut FastFieldResult,
+    row: usize,
+    column: usize,
+    type_name: &str,
+    extract: fn(&FastFieldValue) -> Option<T>,
+) -> Result<T, TantivyGoError> {
+    let result = assert_pointer(result_ptr)?;
+    fast_field_value(result, row, column)?
+        .and_then(extract)
+        .ok_or_else(|| TantivyGoError::InvalidArgument(format!("Fast field value is not a {}", type_name)))
 }
 
 pub fn drop_any<T>(ptr: *mut T) {
     if !ptr.is_null() {
-        unsafe {
+        log_panic((), || unsafe {
             drop(Box::from_raw(ptr));
-        }
+        })
     }
 }
 
@@ -497,4 +975,115 @@ pub fn box_from<T>(ptr: *mut T) -> Box<T> {
     unsafe { Box::from_raw(ptr) }
 }
 
-const POINTER_IS_NULL: &'static str = "Pointer is null";
+const POINTER_IS_NULL: &str = "Pointer is null";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+    use tantivy::doc;
+    use tantivy::schema::STRING;
+
+    fn ram_context() -> TantivyContext {
+        let mut builder = Schema::builder();
+        builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(builder.build());
+        let writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        let reader = index.reader().unwrap();
+        let options = ContextOptions::default();
+        TantivyContext::new(
+            index,
+            writer,
+            reader,
+            options.clone(),
+            options.log_merge_policy().unwrap(),
+            options.settings(String::new()),
+        )
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        let err = catch_panic(|| -> Result<(), TantivyGoError> { panic!("boom") }).unwrap_err();
+        assert_eq!(err.to_string(), "Panic: boom");
+        assert!(matches!(err, TantivyGoError::Panic(_)));
+        assert_eq!(catch_panic(|| Ok(1)).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_catch_context_panic_poisons_context() {
+        let context = ram_context();
+        let context_ptr: *const TantivyContext = &context;
+
+        let err = catch_context_panic(context_ptr, || -> Result<(), TantivyGoError> {
+            panic!("index {}", "broken")
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Panic: index broken");
+
+        let mut ran = false;
+        let err = catch_context_panic(context_ptr, || {
+            ran = true;
+            Ok(())
+        })
+        .unwrap_err();
+        assert!(!ran);
+        assert!(err.to_string().contains("poisoned"));
+        assert!(matches!(err, TantivyGoError::ContextPoisoned(_)));
+    }
+
+    #[test]
+    fn test_set_error() {
+        let mut error_buffer = ErrorBuffer {
+            code: 0,
+            message: ptr::null_mut(),
+        };
+        let err = assert_str(ptr::null()).unwrap_err();
+        set_error(&err, &mut error_buffer);
+        assert!(matches!(err, TantivyGoError::NullPointer(_)));
+        assert_eq!(error_buffer.code, err.code());
+        let message = unsafe { CString::from_raw(error_buffer.message) };
+        assert_eq!(message.to_str().unwrap(), POINTER_IS_NULL);
+
+        set_error(&err, ptr::null_mut());
+
+        let err = TantivyGoError::InvalidArgument("Bad value 'a\0b'".to_string());
+        set_error(&err, &mut error_buffer);
+        assert_eq!(error_buffer.code, err.code());
+        let message = unsafe { CString::from_raw(error_buffer.message) };
+        assert_eq!(message.to_str().unwrap(), "Bad value 'a\\0b'");
+    }
+
+    #[test]
+    fn test_rollback_drops_staged_operations() {
+        let context = ram_context();
+        let id = context.index.schema().get_field("id").unwrap();
+        let mut writer = context.writer().unwrap();
+        writer.add_document(doc!(id => "1")).unwrap();
+        commit(&mut writer, None, "Failed to commit").unwrap();
+
+        writer.add_document(doc!(id => "2")).unwrap();
+        writer.delete_term(Term::from_field_text(id, "1"));
+        writer.pause_merging();
+        assert_eq!(writer.rollback().unwrap(), writer.commit_opstamp());
+        commit(&mut writer, None, "Failed to commit").unwrap();
+        drop(writer);
+
+        context.reload_reader().unwrap();
+        let searcher = context.searcher().unwrap();
+        assert_eq!(searcher.num_docs(), 1);
+        assert_eq!(searcher.doc_freq(&Term::from_field_text(id, "1")).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_last_commit_searcher_sees_unloaded_commit() {
+        let context = ram_context();
+        let id = context.index.schema().get_field("id").unwrap();
+        let loaded = context.searcher().unwrap();
+        let mut writer = context.writer().unwrap();
+        writer.add_document(doc!(id => "1")).unwrap();
+        commit(&mut writer, None, "Failed to commit").unwrap();
+
+        assert_eq!(loaded.num_docs(), 0);
+        assert_eq!(context.last_commit_searcher().unwrap().num_docs(), 1);
+    }
+}
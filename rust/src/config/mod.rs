@@ -1,3 +1,11 @@
-mod config;
+mod registry;
+mod settings;
 
-pub use self::config::CONFIG;
\ No newline at end of file
+pub use self::registry::catching_panics;
+pub use self::registry::clear_index_dir;
+pub use self::registry::register_context;
+pub use self::registry::ContextRegistration;
+pub use self::settings::lib_defaults;
+pub use self::settings::set_lib_defaults;
+pub use self::settings::ContextSettings;
+pub use self::settings::LibDefaults;
@@ -0,0 +1,96 @@
+use lazy_static::lazy_static;
+use log::debug;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs;
+use std::panic::{self, UnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Once, PoisonError};
+use std::thread;
+
+use crate::config::ContextSettings;
+
+lazy_static! {
+    static ref OPEN_CONTEXTS: Mutex<HashMap<u64, Arc<ContextSettings>>> =
+        Mutex::new(HashMap::new());
+}
+
+thread_local! {
+    /// Set while the thread runs an FFI call catching its panics, see `catching_panics`.
+    static CATCHING_PANICS: Cell<bool> = const { Cell::new(false) };
+}
+
+static NEXT_CONTEXT_ID: AtomicU64 = AtomicU64::new(0);
+static PANIC_HOOK: Once = Once::new();
+
+/// Entry of an open context in the registry, removed when dropped with the context.
+pub struct ContextRegistration {
+    id: u64,
+    settings: Arc<ContextSettings>,
+}
+
+impl ContextRegistration {
+    pub fn settings(&self) -> &ContextSettings {
+        &self.settings
+    }
+}
+
+impl Drop for ContextRegistration {
+    fn drop(&mut self) {
+        OPEN_CONTEXTS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.id);
+    }
+}
+
+/// Registers a context being opened, so that process-wide hooks reach it until it is dropped.
+pub fn register_context(settings: ContextSettings) -> ContextRegistration {
+    if settings.clear_on_panic {
+        install_panic_hook();
+    }
+    let id = NEXT_CONTEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let settings = Arc::new(settings);
+    OPEN_CONTEXTS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(id, settings.clone());
+    ContextRegistration { id, settings }
+}
+
+/// Chains a hook removing the open indexes to the panic hook, once per process.
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let old_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |panic_info| {
+            if !CATCHING_PANICS.with(Cell::get) {
+                clear_open_contexts();
+            }
+            old_hook(panic_info)
+        }));
+    });
+}
+
+/// Runs `body` catching its panics. The panic hook then leaves the open contexts alone, as the
+/// caller poisons the context that panicked instead.
+pub fn catching_panics<T>(body: impl FnOnce() -> T + UnwindSafe) -> thread::Result<T> {
+    let catching = CATCHING_PANICS.with(|flag| flag.replace(true));
+    let result = panic::catch_unwind(body);
+    CATCHING_PANICS.with(|flag| flag.set(catching));
+    result
+}
+
+/// Removes the directory of every open context created with `clear_on_panic`.
+fn clear_open_contexts() {
+    let open_contexts = OPEN_CONTEXTS.lock().unwrap_or_else(PoisonError::into_inner);
+    for settings in open_contexts.values().filter(|settings| settings.clear_on_panic) {
+        clear_index_dir(settings);
+    }
+}
+
+pub fn clear_index_dir(settings: &ContextSettings) {
+    if let Err(e) = fs::remove_dir_all(Path::new(&settings.fts_path)) {
+        debug!("Failed to remove directory {}: {}", settings.fts_path, e);
+    }
+}
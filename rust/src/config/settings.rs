@@ -0,0 +1,33 @@
+use lazy_static::lazy_static;
+use std::sync::{PoisonError, RwLock};
+
+lazy_static! {
+    static ref LIB_DEFAULTS: RwLock<LibDefaults> = RwLock::new(LibDefaults::default());
+}
+
+/// Settings of a context, fixed when it is opened.
+#[derive(Clone, Debug)]
+pub struct ContextSettings {
+    pub fts_path: String,
+    /// Replaces invalid utf-8 in document values, queries and terms instead of failing.
+    pub utf8_lenient: bool,
+    /// Removes the index directory when a call on the context panics, or when the process panics
+    /// outside of a call.
+    pub clear_on_panic: bool,
+}
+
+/// Settings of the contexts opened without setting them, given to `init_lib`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LibDefaults {
+    pub utf8_lenient: bool,
+    pub clear_on_panic: bool,
+}
+
+/// Changes the defaults of the contexts opened afterwards, open contexts keep their settings.
+pub fn set_lib_defaults(defaults: LibDefaults) {
+    *LIB_DEFAULTS.write().unwrap_or_else(PoisonError::into_inner) = defaults;
+}
+
+pub fn lib_defaults() -> LibDefaults {
+    *LIB_DEFAULTS.read().unwrap_or_else(PoisonError::into_inner)
+}
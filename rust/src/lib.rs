@@ -3,17 +3,27 @@ use std::ffi::{c_uint, CString};
 use std::os::raw::{c_char, c_float};
 use std::ptr;
 use tantivy::schema::*;
-use tantivy::{Opstamp, Term};
+use tantivy::Opstamp;
 
 use crate::c_util::{
-    add_and_consume_documents, add_field, add_fields, assert_pointer, assert_str, assert_string,
-    box_from, convert_document_as_json, create_context_with_schema, delete_docs, drop_any, get_doc,
-    search, search_fast_field, search_fast_field_json, search_json, set_error, start_lib_init,
+    add_and_consume_documents, add_bool_value, add_date_value, add_documents, add_f64_value,
+    add_facet_value, add_field, add_fields, add_i64_value, add_json_value, add_u64_value, aggregate,
+    assert_pointer, assert_ref, assert_str, assert_string, box_from, catch_context_panic,
+    catch_panic, commit, consume_documents, context_options_from_ptr, context_str,
+    convert_document_as_json, count, create_context_with_schema, date_precision_from_const,
+    delete_all, delete_by_query, delete_docs, delete_terms, drop_any, fast_field_value, get_doc,
+    index_record_option_from_const,
+    last_commit_payload, log_panic, payload_from_ptr, rollback, search, search_fast_fields,
+    search_json, search_options_from_ptr, searcher_from_ptr, set_error, set_failures,
+    start_lib_init, typed_fast_field_value, upsert_documents, ErrorBuffer,
 };
 use crate::tantivy_util::{
-    add_text_field, register_edge_ngram_tokenizer, register_jieba_tokenizer,
-    register_ngram_tokenizer, register_raw_tokenizer, register_simple_tokenizer, Document,
-    SearchResult, TantivyContext, TantivyGoError,
+    add_bool_field, add_date_field, add_f64_field, add_facet_field, add_i64_field, add_json_field,
+    add_text_field, add_u64_field, force_merge, purge_deletes,
+    register_edge_ngram_tokenizer, register_jieba_tokenizer, register_ngram_tokenizer,
+    register_raw_tokenizer, register_simple_tokenizer, CheckedDocuments, Document, FastFieldResult,
+    FastFieldValue, IndexStats, IndexingPolicy, MergePolicyOptions, PinnedSearcher,
+    SearchResult, TantivyContext, TantivyGoError, FAST_FIELD_TYPE_NONE,
 };
 
 mod c_util;
@@ -24,7 +34,7 @@ mod tantivy_util;
 #[logcall]
 #[no_mangle]
 pub extern "C" fn schema_builder_new() -> *mut SchemaBuilder {
-    Box::into_raw(Box::new(Schema::builder()))
+    log_panic(ptr::null_mut(), || Box::into_raw(Box::new(Schema::builder())))
 }
 
 #[logcall]
@@ -37,23 +47,14 @@ pub extern "C" fn schema_builder_add_text_field(
     is_fast: bool,
     index_record_option_const: usize,
     tokenizer_name_ptr: *const c_char,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) -> u32 {
     let result = || -> Result<u32, TantivyGoError> {
         let builder = assert_pointer(builder_ptr)?;
         let tokenizer_name = assert_string(tokenizer_name_ptr)?;
         let field_name = assert_string(field_name_ptr)?;
 
-        let index_record_option = match index_record_option_const {
-            0 => IndexRecordOption::Basic,
-            1 => IndexRecordOption::WithFreqs,
-            2 => IndexRecordOption::WithFreqsAndPositions,
-            _ => {
-                return Err(TantivyGoError(
-                    "Invalid index_record_option_const".to_string(),
-                ))
-            }
-        };
+        let index_record_option = index_record_option_from_const(index_record_option_const)?;
 
         Ok(add_text_field(
             stored,
@@ -66,10 +67,10 @@ pub extern "C" fn schema_builder_add_text_field(
         ))
     };
 
-    match result() {
+    match catch_panic(result) {
         Ok(val) => val,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
             0
         }
     }
@@ -77,43 +78,362 @@ pub extern "C" fn schema_builder_add_text_field(
 
 #[logcall]
 #[no_mangle]
-pub extern "C" fn schema_builder_build(
+pub extern "C" fn schema_builder_add_i64_field(
     builder_ptr: *mut SchemaBuilder,
-    error_buffer: *mut *mut c_char,
-) -> *mut Schema {
-    let builder = match assert_pointer(builder_ptr) {
-        Ok(value) => box_from(value),
+    field_name_ptr: *const c_char,
+    stored: bool,
+    indexed: bool,
+    is_fast: bool,
+    error_buffer: *mut ErrorBuffer,
+) -> u32 {
+    let result = || -> Result<u32, TantivyGoError> {
+        let builder = assert_pointer(builder_ptr)?;
+        let field_name = assert_string(field_name_ptr)?;
+
+        Ok(add_i64_field(stored, indexed, is_fast, builder, field_name.as_str()))
+    };
+
+    match catch_panic(result) {
+        Ok(val) => val,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn schema_builder_add_u64_field(
+    builder_ptr: *mut SchemaBuilder,
+    field_name_ptr: *const c_char,
+    stored: bool,
+    indexed: bool,
+    is_fast: bool,
+    error_buffer: *mut ErrorBuffer,
+) -> u32 {
+    let result = || -> Result<u32, TantivyGoError> {
+        let builder = assert_pointer(builder_ptr)?;
+        let field_name = assert_string(field_name_ptr)?;
+
+        Ok(add_u64_field(stored, indexed, is_fast, builder, field_name.as_str()))
+    };
+
+    match catch_panic(result) {
+        Ok(val) => val,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn schema_builder_add_f64_field(
+    builder_ptr: *mut SchemaBuilder,
+    field_name_ptr: *const c_char,
+    stored: bool,
+    indexed: bool,
+    is_fast: bool,
+    error_buffer: *mut ErrorBuffer,
+) -> u32 {
+    let result = || -> Result<u32, TantivyGoError> {
+        let builder = assert_pointer(builder_ptr)?;
+        let field_name = assert_string(field_name_ptr)?;
+
+        Ok(add_f64_field(stored, indexed, is_fast, builder, field_name.as_str()))
+    };
+
+    match catch_panic(result) {
+        Ok(val) => val,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn schema_builder_add_bool_field(
+    builder_ptr: *mut SchemaBuilder,
+    field_name_ptr: *const c_char,
+    stored: bool,
+    indexed: bool,
+    is_fast: bool,
+    error_buffer: *mut ErrorBuffer,
+) -> u32 {
+    let result = || -> Result<u32, TantivyGoError> {
+        let builder = assert_pointer(builder_ptr)?;
+        let field_name = assert_string(field_name_ptr)?;
+
+        Ok(add_bool_field(stored, indexed, is_fast, builder, field_name.as_str()))
+    };
+
+    match catch_panic(result) {
+        Ok(val) => val,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn schema_builder_add_date_field(
+    builder_ptr: *mut SchemaBuilder,
+    field_name_ptr: *const c_char,
+    stored: bool,
+    indexed: bool,
+    is_fast: bool,
+    precision: u8,
+    error_buffer: *mut ErrorBuffer,
+) -> u32 {
+    let result = || -> Result<u32, TantivyGoError> {
+        let builder = assert_pointer(builder_ptr)?;
+        let field_name = assert_string(field_name_ptr)?;
+        let precision = date_precision_from_const(precision)?;
+
+        Ok(add_date_field(stored, indexed, is_fast, precision, builder, field_name.as_str()))
+    };
+
+    match catch_panic(result) {
+        Ok(val) => val,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn schema_builder_add_json_field(
+    builder_ptr: *mut SchemaBuilder,
+    field_name_ptr: *const c_char,
+    stored: bool,
+    is_fast: bool,
+    expand_dots: bool,
+    index_record_option_const: usize,
+    tokenizer_name_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) -> u32 {
+    let result = || -> Result<u32, TantivyGoError> {
+        let builder = assert_pointer(builder_ptr)?;
+        let tokenizer_name = assert_string(tokenizer_name_ptr)?;
+        let field_name = assert_string(field_name_ptr)?;
+        let index_record_option = index_record_option_from_const(index_record_option_const)?;
+
+        Ok(add_json_field(
+            stored,
+            is_fast,
+            expand_dots,
+            builder,
+            tokenizer_name.as_str(),
+            field_name.as_str(),
+            index_record_option,
+        ))
+    };
+
+    match catch_panic(result) {
+        Ok(val) => val,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Adds a hierarchical facet field. Facets are always indexed.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn schema_builder_add_facet_field(
+    builder_ptr: *mut SchemaBuilder,
+    field_name_ptr: *const c_char,
+    stored: bool,
+    error_buffer: *mut ErrorBuffer,
+) -> u32 {
+    let result = || -> Result<u32, TantivyGoError> {
+        let builder = assert_pointer(builder_ptr)?;
+        let field_name = assert_string(field_name_ptr)?;
+
+        Ok(add_facet_field(stored, builder, field_name.as_str()))
+    };
+
+    match catch_panic(result) {
+        Ok(val) => val,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
-            return ptr::null_mut();
+            set_error(&err, error_buffer);
+            0
         }
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn schema_builder_build(
+    builder_ptr: *mut SchemaBuilder,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut Schema {
+    let result = || -> Result<*mut Schema, TantivyGoError> {
+        let builder = box_from(assert_pointer(builder_ptr)?);
+        Ok(Box::into_raw(Box::new(builder.build())))
     };
 
-    Box::into_raw(Box::new(builder.build()))
+    match catch_panic(result) {
+        Ok(schema) => schema,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            ptr::null_mut()
+        }
+    }
 }
 
+/// Opens or creates the index at the path.
+/// A non-null `options_ptr` configures the context with a json object like
+/// `{"memory_budget_bytes": .., "num_threads": .., "merge_policy": {..}, "reader_reload": ..}`,
+/// every key optional. The merge policy keys are the ones of `context_set_merge_policy`.
+/// `reader_reload` is `"on_search"` (default) to reload the reader before every search,
+/// `"on_commit_with_delay"` to reload it shortly after every commit, or `"manual"` to reload it
+/// only on `context_reload_reader`. `utf8_lenient` replaces invalid utf-8 in document values,
+/// queries and terms, `clear_on_panic` removes the index directory when a call on the context
+/// panics, both default to the values given to `init_lib`.
 #[logcall]
 #[no_mangle]
 pub extern "C" fn context_create_with_schema(
     path_ptr: *const c_char,
     schema_ptr: *mut Schema,
-    error_buffer: *mut *mut c_char,
+    options_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
 ) -> *mut TantivyContext {
     let result = || -> Result<*mut TantivyContext, TantivyGoError> {
         let schema = assert_pointer(schema_ptr)?.clone();
         let path = assert_string(path_ptr)?;
-        create_context_with_schema(schema, path)
+        let options = context_options_from_ptr(options_ptr)?;
+        create_context_with_schema(schema, path, options)
     };
 
-    match result() {
+    match catch_panic(result) {
         Ok(context) => context,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
             ptr::null_mut()
         }
     }
 }
 
+/// Replaces the merge policy of the index writer by a `LogMergePolicy` given as a json object
+/// like `{"min_num_segments": .., "max_docs_before_merge": .., "min_layer_size": ..,
+/// "level_log_size": .., "del_docs_ratio_before_merge": ..}`, every key optional.
+/// It applies to the merges scheduled after the call.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_set_merge_policy(
+    context_ptr: *mut TantivyContext,
+    merge_policy_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) {
+    let result = || -> Result<(), TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let merge_policy = MergePolicyOptions::parse(&assert_str(merge_policy_ptr)?)?;
+        context.writer()?.set_merge_policy(merge_policy.to_log_merge_policy()?);
+        Ok(())
+    };
+
+    if let Err(err) = catch_context_panic(context_ptr, result) {
+        set_error(&err, error_buffer);
+    }
+}
+
+/// Stops scheduling background merges until `context_resume_merging`.
+/// Running merges are not interrupted, `context_set_merge_policy` applies once resumed.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_pause_merging(
+    context_ptr: *mut TantivyContext,
+    error_buffer: *mut ErrorBuffer,
+) {
+    let result = || -> Result<(), TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        context.writer()?.pause_merging();
+        Ok(())
+    };
+
+    if let Err(err) = catch_context_panic(context_ptr, result) {
+        set_error(&err, error_buffer);
+    }
+}
+
+/// Schedules background merges again with the current merge policy.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_resume_merging(
+    context_ptr: *mut TantivyContext,
+    error_buffer: *mut ErrorBuffer,
+) {
+    let result = || -> Result<(), TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        context.writer()?.resume_merging();
+        Ok(())
+    };
+
+    if let Err(err) = catch_context_panic(context_ptr, result) {
+        set_error(&err, error_buffer);
+    }
+}
+
+/// Merges the smallest searchable segments so that at most `target_segments` (at least 1)
+/// remain and waits for the merge, returning the number of searchable segments after it.
+/// Fails when one of the segments is being merged in the background,
+/// see `context_pause_merging`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_force_merge(
+    context_ptr: *mut TantivyContext,
+    target_segments: usize,
+    error_buffer: *mut ErrorBuffer,
+) -> usize {
+    let result = || -> Result<usize, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let mut writer = context.writer()?;
+        force_merge(&context.index, &mut writer, target_segments)
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(num_segments) => num_segments,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Rewrites the searchable segments having at least `deleted_ratio` (in (0, 1]) of deleted
+/// documents without them and waits for the merges, returning the number of rewritten segments.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_purge_deletes(
+    context_ptr: *mut TantivyContext,
+    deleted_ratio: c_float,
+    error_buffer: *mut ErrorBuffer,
+) -> usize {
+    let result = || -> Result<usize, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let mut writer = context.writer()?;
+        purge_deletes(&context.index, &mut writer, deleted_ratio)
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(purged) => purged,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
 #[logcall]
 #[no_mangle]
 pub extern "C" fn context_register_text_analyzer_ngram(
@@ -122,10 +442,10 @@ pub extern "C" fn context_register_text_analyzer_ngram(
     min_gram: usize,
     max_gram: usize,
     prefix_only: bool,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) {
     let result = || -> Result<(), TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
+        let context = assert_ref(context_ptr)?;
         let tokenizer_name = assert_string(tokenizer_name_ptr)?;
         register_ngram_tokenizer(
             min_gram,
@@ -137,8 +457,8 @@ pub extern "C" fn context_register_text_analyzer_ngram(
         Ok(())
     };
 
-    if let Err(err) = result() {
-        set_error(&err.to_string(), error_buffer);
+    if let Err(err) = catch_context_panic(context_ptr, result) {
+        set_error(&err, error_buffer);
     }
 }
 
@@ -150,10 +470,10 @@ pub extern "C" fn context_register_text_analyzer_edge_ngram(
     min_gram: usize,
     max_gram: usize,
     limit: usize,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) {
     let result = || -> Result<(), TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
+        let context = assert_ref(context_ptr)?;
         let tokenizer_name = assert_string(tokenizer_name_ptr)?;
         register_edge_ngram_tokenizer(
             min_gram,
@@ -165,8 +485,8 @@ pub extern "C" fn context_register_text_analyzer_edge_ngram(
         Ok(())
     };
 
-    if let Err(err) = result() {
-        set_error(&err.to_string(), error_buffer);
+    if let Err(err) = catch_context_panic(context_ptr, result) {
+        set_error(&err, error_buffer);
     }
 }
 
@@ -177,18 +497,18 @@ pub extern "C" fn context_register_text_analyzer_simple(
     tokenizer_name_ptr: *const c_char,
     text_limit: usize,
     lang_str_ptr: *const c_char,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) {
     let result = || -> Result<(), TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
+        let context = assert_ref(context_ptr)?;
         let tokenizer_name = assert_string(tokenizer_name_ptr)?;
         let lang = assert_string(lang_str_ptr)?;
         register_simple_tokenizer(text_limit, &context.index, tokenizer_name.as_str(), &lang)?;
         Ok(())
     };
 
-    if let Err(err) = result() {
-        set_error(&err.to_string(), error_buffer);
+    if let Err(err) = catch_context_panic(context_ptr, result) {
+        set_error(&err, error_buffer);
     }
 }
 
@@ -198,17 +518,17 @@ pub extern "C" fn context_register_jieba_tokenizer(
     context_ptr: *mut TantivyContext,
     tokenizer_name_ptr: *const c_char,
     text_limit: usize,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) {
     let result = || -> Result<(), TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
+        let context = assert_ref(context_ptr)?;
         let tokenizer_name = assert_string(tokenizer_name_ptr)?;
         register_jieba_tokenizer(text_limit, &context.index, tokenizer_name.as_str());
         Ok(())
     };
 
-    if let Err(err) = result() {
-        set_error(&err.to_string(), error_buffer);
+    if let Err(err) = catch_context_panic(context_ptr, result) {
+        set_error(&err, error_buffer);
     }
 }
 
@@ -217,38 +537,49 @@ pub extern "C" fn context_register_jieba_tokenizer(
 pub extern "C" fn context_register_text_analyzer_raw(
     context_ptr: *mut TantivyContext,
     tokenizer_name_ptr: *const c_char,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) {
     let result = || -> Result<(), TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
+        let context = assert_ref(context_ptr)?;
         let tokenizer_name = assert_string(tokenizer_name_ptr)?;
         register_raw_tokenizer(&context.index, tokenizer_name.as_str());
         Ok(())
     };
 
-    if let Err(err) = result() {
-        set_error(&err.to_string(), error_buffer);
+    if let Err(err) = catch_context_panic(context_ptr, result) {
+        set_error(&err, error_buffer);
     }
 }
 
+/// Adds the documents and commits, returning the opstamp of the commit.
+/// `indexing_policy` is one of the `INDEXING_*` constants. With `INDEXING_BEST_EFFORT` failing
+/// documents are skipped and written to `failures_buffer` as a json array of
+/// `{"index": .., "error": ..}` objects, to be freed with `string_free`.
 #[logcall]
 #[no_mangle]
 pub extern "C" fn context_add_and_consume_documents(
     context_ptr: *mut TantivyContext,
     docs_ptr: *mut *mut Document,
     docs_len: usize,
-    error_buffer: *mut *mut c_char,
+    indexing_policy: u8,
+    payload_ptr: *const c_char,
+    failures_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) -> u64 {
     let result = || -> Result<u64, TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
-        let opstamp = add_and_consume_documents(docs_ptr, docs_len, &mut context.writer)?;
+        let context = assert_ref(context_ptr)?;
+        let payload = payload_from_ptr(payload_ptr)?;
+        let policy = IndexingPolicy::from_const(indexing_policy)?;
+        let (opstamp, failures) =
+            add_and_consume_documents(docs_ptr, docs_len, context, payload, policy)?;
+        set_failures(&failures, failures_buffer)?;
         Ok(opstamp)
     };
 
-    match result() {
+    match catch_context_panic(context_ptr, result) {
         Ok(opstamp) => opstamp,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
             0
         }
     }
@@ -261,22 +592,27 @@ pub extern "C" fn context_delete_documents(
     field_id: c_uint,
     delete_ids_ptr: *mut *const c_char,
     delete_ids_len: usize,
-    error_buffer: *mut *mut c_char,
+    payload_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
 ) -> u64 {
     let result = || -> Result<Opstamp, TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
-        delete_docs(delete_ids_ptr, delete_ids_len, context, field_id)
+        let context = assert_ref(context_ptr)?;
+        let payload = payload_from_ptr(payload_ptr)?;
+        delete_docs(delete_ids_ptr, delete_ids_len, context, field_id, payload)
     };
 
-    match result() {
+    match catch_context_panic(context_ptr, result) {
         Ok(opstamp) => opstamp,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
             0
         }
     }
 }
 
+/// Deletes the documents having one of the ids in the field, adds the documents and commits
+/// once, returning the opstamp of the commit.
+/// Failing documents are handled as in `context_add_and_consume_documents`.
 #[logcall]
 #[no_mangle]
 pub extern "C" fn context_batch_add_and_delete_documents(
@@ -286,107 +622,633 @@ pub extern "C" fn context_batch_add_and_delete_documents(
     delete_field_id: c_uint,
     delete_ids_ptr: *mut *const c_char,
     delete_ids_len: usize,
-    error_buffer: *mut *mut c_char,
+    indexing_policy: u8,
+    payload_ptr: *const c_char,
+    failures_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) -> u64 {
     let result = || -> Result<Opstamp, TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
-        
-        // First, delete documents (without committing)
+        let context = assert_ref(context_ptr)?;
+        let payload = payload_from_ptr(payload_ptr)?;
+        let policy = IndexingPolicy::from_const(indexing_policy)?;
+
+        // First, check the documents, so that a failing batch stages nothing
+        let docs = consume_documents(add_docs_ptr, add_docs_len)?;
+        let docs = CheckedDocuments::check(&context.index, docs, policy)?;
+
+        // Then, delete documents (without committing)
+        let mut writer = context.writer()?;
         if delete_ids_len > 0 {
-            let field = Field::from_field_id(delete_field_id);
-            let slice = unsafe { std::slice::from_raw_parts(delete_ids_ptr, delete_ids_len) };
-            for &id_ptr in slice {
-                let id_value = assert_str(id_ptr)?;
-                context
-                    .writer
-                    .delete_term(Term::from_field_text(field, &id_value));
-            }
+            delete_terms(
+                delete_ids_ptr,
+                delete_ids_len,
+                context.settings().utf8_lenient,
+                &mut writer,
+                delete_field_id,
+            )?;
         }
-        
+
         // Then, add all documents (without committing)
-        if add_docs_len > 0 {
-            let slice = unsafe { std::slice::from_raw_parts(add_docs_ptr, add_docs_len) };
-            for &doc_ptr in slice {
-                if doc_ptr.is_null() {
-                    return Err(TantivyGoError("Document pointer is null".to_string()));
-                }
-                let doc = unsafe { Box::from_raw(doc_ptr) };
-                let _ = context.writer.add_document(doc.tantivy_doc);
-                // Doc is consumed, Box automatically drops the rest
-            }
-        }
-        
+        let (_, failures) = docs
+            .stage(&mut writer)
+            .inspect_err(|_| rollback(&mut writer))?;
+
         // Finally, commit everything at once
-        let opstamp = context.writer.commit().map_err(|err| {
-            // TEMPORARILY DISABLED: Tantivy has a critical bug in the rollback mechanism
-            // https://github.com/quickwit-oss/tantivy/issues/2666
-            // TODO: Re-enable when the bug is fixed
-            // let _ = context.writer.rollback();
-            TantivyGoError::from_err("Failed to commit batch operation", &err.to_string())
-        })?;
+        let opstamp = commit(&mut writer, payload, "Failed to commit batch operation")?;
+        set_failures(&failures, failures_buffer)?;
         Ok(opstamp)
     };
 
-    match result() {
+    match catch_context_panic(context_ptr, result) {
         Ok(opstamp) => opstamp,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
             0
         }
     }
 }
 
+/// Deletes the documents matching a query and commits, returning the opstamp of the commit.
+/// With `query_is_json` the query is parsed as the JSON query DSL and the fields are ignored.
+/// A non-null `payload_ptr` is stored with the commit in the index meta.
 #[logcall]
 #[no_mangle]
-pub extern "C" fn context_num_docs(
+pub extern "C" fn context_delete_by_query(
     context_ptr: *mut TantivyContext,
-    error_buffer: *mut *mut c_char,
+    field_ids_ptr: *mut c_uint,
+    field_weights_ptr: *mut c_float,
+    field_ids_len: usize,
+    query_ptr: *const c_char,
+    query_is_json: bool,
+    payload_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
 ) -> u64 {
-    let result = || -> Result<u64, TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
-        let reader = context.reader();
-        Ok(reader.searcher().num_docs())
+    let result = || -> Result<Opstamp, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let payload = payload_from_ptr(payload_ptr)?;
+        delete_by_query(
+            field_ids_ptr,
+            field_weights_ptr,
+            field_ids_len,
+            query_ptr,
+            query_is_json,
+            context,
+            payload,
+        )
     };
 
-    match result() {
-        Ok(num_docs) => num_docs,
+    match catch_context_panic(context_ptr, result) {
+        Ok(opstamp) => opstamp,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
             0
         }
     }
 }
 
+/// Deletes every document and commits, returning the opstamp of the commit.
+/// The index directory and the schema are kept.
 #[logcall]
 #[no_mangle]
-pub extern "C" fn context_search(
+pub extern "C" fn context_delete_all(
     context_ptr: *mut TantivyContext,
-    field_ids_ptr: *mut c_uint,
-    field_weights_ptr: *mut c_float,
-    field_ids_len: usize,
-    query_ptr: *const c_char,
-    error_buffer: *mut *mut c_char,
-    docs_limit: usize,
-    with_highlights: bool,
-) -> *mut SearchResult {
-    let result = || -> Result<*mut SearchResult, TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
+    payload_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) -> u64 {
+    let result = || -> Result<Opstamp, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let payload = payload_from_ptr(payload_ptr)?;
+        delete_all(context, payload)
+    };
 
-        search(
-            field_ids_ptr,
-            field_weights_ptr,
-            field_ids_len,
+    match catch_context_panic(context_ptr, result) {
+        Ok(opstamp) => opstamp,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Replaces the indexed documents having the id of a new document, read from `id_field_id`,
+/// and commits, returning the opstamp of the commit. The documents are consumed.
+/// With a `version_field_id` other than `NO_VERSION` a document only replaces the indexed ones
+/// when its version is newer, and is skipped otherwise.
+/// A non-null `payload_ptr` is stored with the commit in the index meta.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_upsert_documents(
+    context_ptr: *mut TantivyContext,
+    id_field_id: c_uint,
+    docs_ptr: *mut *mut Document,
+    docs_len: usize,
+    version_field_id: c_uint,
+    payload_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) -> u64 {
+    let result = || -> Result<Opstamp, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let payload = payload_from_ptr(payload_ptr)?;
+        upsert_documents(docs_ptr, docs_len, id_field_id, version_field_id, context, payload)
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(opstamp) => opstamp,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Stages documents without committing them, returning the opstamp of the last one.
+/// The documents are consumed. They become searchable after `context_commit`.
+/// Failing documents are handled as in `context_add_and_consume_documents`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_add_documents(
+    context_ptr: *mut TantivyContext,
+    docs_ptr: *mut *mut Document,
+    docs_len: usize,
+    indexing_policy: u8,
+    failures_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
+) -> u64 {
+    let result = || -> Result<Opstamp, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let policy = IndexingPolicy::from_const(indexing_policy)?;
+        let (opstamp, failures) = add_documents(docs_ptr, docs_len, context, policy)?;
+        set_failures(&failures, failures_buffer)?;
+        Ok(opstamp)
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(opstamp) => opstamp,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Stages the deletion of the documents having one of the terms in the field without
+/// committing it, returning the opstamp of the last deletion.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_delete_terms(
+    context_ptr: *mut TantivyContext,
+    field_id: c_uint,
+    delete_ids_ptr: *mut *const c_char,
+    delete_ids_len: usize,
+    error_buffer: *mut ErrorBuffer,
+) -> u64 {
+    let result = || -> Result<Opstamp, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let mut writer = context.writer()?;
+        delete_terms(
+            delete_ids_ptr,
+            delete_ids_len,
+            context.settings().utf8_lenient,
+            &mut writer,
+            field_id,
+        )
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(opstamp) => opstamp,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Commits all staged operations, returning the opstamp of the commit.
+/// A non-null `payload_ptr` is stored with the commit in the index meta.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_commit(
+    context_ptr: *mut TantivyContext,
+    payload_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) -> u64 {
+    let result = || -> Result<Opstamp, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let payload = payload_from_ptr(payload_ptr)?;
+        let mut writer = context.writer()?;
+        commit(&mut writer, payload, "Failed to commit")
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(opstamp) => opstamp,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Drops all operations staged since the last commit, returning the opstamp of that commit.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_rollback(
+    context_ptr: *mut TantivyContext,
+    error_buffer: *mut ErrorBuffer,
+) -> u64 {
+    let result = || -> Result<Opstamp, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        context.writer()?.rollback()
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(opstamp) => opstamp,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_num_docs(
+    context_ptr: *mut TantivyContext,
+    error_buffer: *mut ErrorBuffer,
+) -> u64 {
+    let result = || -> Result<u64, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        Ok(context.searcher()?.num_docs())
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(num_docs) => num_docs,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Returns statistics of the searchable segments as json: document and segment counts,
+/// per-segment document counts, the opstamp of the last commit on disk and space usage in bytes,
+/// in total and per field name. The string must be freed with `string_free`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_stats(
+    context_ptr: *mut TantivyContext,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut c_char {
+    let result = || -> Result<String, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let searcher = context.searcher()?;
+        let stats = IndexStats::collect(&context.index, &searcher)?;
+        serde_json::to_string(&stats).map_err(|err| TantivyGoError::Internal(err.to_string()))
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(json) => match CString::new(json) {
+            Ok(cstr) => cstr.into_raw(),
+            Err(err) => {
+                set_error(&TantivyGoError::from(err), error_buffer);
+                ptr::null_mut()
+            }
+        },
+        Err(err) => {
+            set_error(&err, error_buffer);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Pins the searcher of the current reader generation so that several searches see the same
+/// documents, whatever is committed or reloaded meanwhile. The handle can be passed to the search
+/// functions and must be freed with `pinned_searcher_free`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_acquire_searcher(
+    context_ptr: *mut TantivyContext,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut PinnedSearcher {
+    let result = || -> Result<*mut PinnedSearcher, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        Ok(Box::into_raw(Box::new(PinnedSearcher {
+            searcher: context.searcher()?,
+        })))
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(searcher) => searcher,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the reader generation id of a pinned searcher.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn pinned_searcher_get_generation(
+    searcher_ptr: *mut PinnedSearcher,
+    error_buffer: *mut ErrorBuffer,
+) -> u64 {
+    let result = || -> Result<u64, TantivyGoError> {
+        Ok(assert_ref(searcher_ptr)?.generation())
+    };
+
+    match catch_panic(result) {
+        Ok(generation) => generation,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn pinned_searcher_free(searcher_ptr: *mut PinnedSearcher) {
+    drop_any(searcher_ptr)
+}
+
+/// A non-null `searcher_ptr` runs the search on that pinned searcher instead of the current one.
+/// `options_ptr` holds the `SearchOptions` as json, the defaults when null.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_search(
+    context_ptr: *mut TantivyContext,
+    searcher_ptr: *mut PinnedSearcher,
+    field_ids_ptr: *mut c_uint,
+    field_weights_ptr: *mut c_float,
+    field_ids_len: usize,
+    query_ptr: *const c_char,
+    options_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut SearchResult {
+    let result = || -> Result<*mut SearchResult, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let searcher = searcher_from_ptr(context, searcher_ptr)?;
+        let options = search_options_from_ptr(options_ptr)?;
+        let page = options.page(searcher.schema())?;
+
+        search(
+            field_ids_ptr,
+            field_weights_ptr,
+            field_ids_len,
             query_ptr,
-            docs_limit,
+            &page,
             context,
-            with_highlights,
+            &searcher,
+            options.with_highlights,
         )
     };
 
-    match result() {
+    match catch_context_panic(context_ptr, result) {
+        Ok(search_result) => search_result,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Like `context_search`, with the query given as the JSON query DSL.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_search_json(
+    context_ptr: *mut TantivyContext,
+    searcher_ptr: *mut PinnedSearcher,
+    query_ptr: *const c_char,
+    options_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut SearchResult {
+    let result = || -> Result<*mut SearchResult, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let searcher = searcher_from_ptr(context, searcher_ptr)?;
+        let options = search_options_from_ptr(options_ptr)?;
+        let page = options.page(searcher.schema())?;
+
+        search_json(query_ptr, &page, context, &searcher, options.with_highlights)
+    };
+
+    match catch_context_panic(context_ptr, result) {
         Ok(search_result) => search_result,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Counts the documents matching a query without collecting them.
+/// With `query_is_json` the query is parsed as the JSON query DSL and the fields are ignored.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_count(
+    context_ptr: *mut TantivyContext,
+    searcher_ptr: *mut PinnedSearcher,
+    field_ids_ptr: *mut c_uint,
+    field_weights_ptr: *mut c_float,
+    field_ids_len: usize,
+    query_ptr: *const c_char,
+    query_is_json: bool,
+    error_buffer: *mut ErrorBuffer,
+) -> usize {
+    let result = || -> Result<usize, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let searcher = searcher_from_ptr(context, searcher_ptr)?;
+
+        count(
+            field_ids_ptr,
+            field_weights_ptr,
+            field_ids_len,
+            query_ptr,
+            query_is_json,
+            context,
+            &searcher,
+        )
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(count) => count,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Runs an aggregation request in the tantivy aggregation JSON format (terms, range, histogram,
+/// date_histogram, stats, ...) over the documents matching a query.
+/// With `query_is_json` the query is parsed as the JSON query DSL and the fields are ignored.
+/// Returns the aggregation results as JSON, to be freed with `string_free`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_aggregate(
+    context_ptr: *mut TantivyContext,
+    searcher_ptr: *mut PinnedSearcher,
+    field_ids_ptr: *mut c_uint,
+    field_weights_ptr: *mut c_float,
+    field_ids_len: usize,
+    query_ptr: *const c_char,
+    query_is_json: bool,
+    aggregation_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut c_char {
+    let result = || -> Result<String, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let searcher = searcher_from_ptr(context, searcher_ptr)?;
+
+        aggregate(
+            field_ids_ptr,
+            field_weights_ptr,
+            field_ids_len,
+            query_ptr,
+            query_is_json,
+            aggregation_ptr,
+            context,
+            &searcher,
+        )
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(json) => match CString::new(json) {
+            Ok(cstr) => cstr.into_raw(),
+            Err(err) => {
+                set_error(&TantivyGoError::from(err), error_buffer);
+                ptr::null_mut()
+            }
+        },
+        Err(err) => {
+            set_error(&err, error_buffer);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Performs a search and returns only the values of the given fast fields of the top documents
+/// (no full document loading). Fast fields may be str, numeric, bool or date fields.
+/// With `query_is_json` the query is parsed as the JSON query DSL and the fields are ignored.
+/// `options_ptr` holds the `SearchOptions` as json, without facets, highlights or siblings.
+/// The result must be freed with `fast_field_result_free`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_search_fast_fields(
+    context_ptr: *mut TantivyContext,
+    searcher_ptr: *mut PinnedSearcher,
+    field_ids_ptr: *mut c_uint,
+    field_weights_ptr: *mut c_float,
+    field_ids_len: usize,
+    query_ptr: *const c_char,
+    query_is_json: bool,
+    fast_field_ids_ptr: *mut c_uint,
+    fast_field_ids_len: usize,
+    options_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut FastFieldResult {
+    let result = || -> Result<*mut FastFieldResult, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        let searcher = searcher_from_ptr(context, searcher_ptr)?;
+        let options = search_options_from_ptr(options_ptr)?;
+        if options.facets.is_some()
+            || options.with_highlights
+            || options.collapse.as_ref().is_some_and(|collapse| collapse.with_siblings)
+        {
+            return Err(TantivyGoError::InvalidArgument(
+                "Fast field searches take no facets, highlights or collapsed siblings".to_string(),
+            ));
+        }
+        let page = options.page(searcher.schema())?;
+
+        search_fast_fields(
+            field_ids_ptr,
+            field_weights_ptr,
+            field_ids_len,
+            query_ptr,
+            query_is_json,
+            fast_field_ids_ptr,
+            fast_field_ids_len,
+            &page,
+            context,
+            &searcher,
+        )
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(fast_field_result) => fast_field_result,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the number of documents (rows) of a fast field result.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn fast_field_result_get_size(
+    result_ptr: *mut FastFieldResult,
+    error_buffer: *mut ErrorBuffer,
+) -> usize {
+    let result = || -> Result<usize, TantivyGoError> {
+        let result = assert_pointer(result_ptr)?;
+        Ok(result.scores.len())
+    };
+
+    match catch_panic(result) {
+        Ok(size) => size,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Returns the number of documents matching the query, which may exceed the returned size.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn fast_field_result_get_total_hits(
+    result_ptr: *mut FastFieldResult,
+    error_buffer: *mut ErrorBuffer,
+) -> usize {
+    let result = || -> Result<usize, TantivyGoError> {
+        let result = assert_pointer(result_ptr)?;
+        Ok(result.total_hits)
+    };
+
+    match catch_panic(result) {
+        Ok(total_hits) => total_hits,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Returns the cursor of the last document, or null when the result is empty.
+/// The string must be freed with `string_free`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn fast_field_result_get_cursor(
+    result_ptr: *mut FastFieldResult,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut c_char {
+    let result = || -> Result<Option<String>, TantivyGoError> {
+        let result = assert_pointer(result_ptr)?;
+        Ok(result.cursor.clone())
+    };
+
+    match catch_panic(result) {
+        Ok(Some(cursor)) => match CString::new(cursor) {
+            Ok(cstr) => cstr.into_raw(),
+            Err(err) => {
+                set_error(&TantivyGoError::from(err), error_buffer);
+                ptr::null_mut()
+            }
+        },
+        Ok(None) => ptr::null_mut(),
+        Err(err) => {
+            set_error(&err, error_buffer);
             ptr::null_mut()
         }
     }
@@ -394,174 +1256,209 @@ pub extern "C" fn context_search(
 
 #[logcall]
 #[no_mangle]
-pub extern "C" fn context_search_json(
-    context_ptr: *mut TantivyContext,
-    query_ptr: *const c_char,
-    error_buffer: *mut *mut c_char,
-    docs_limit: usize,
-    with_highlights: bool,
-) -> *mut SearchResult {
-    let result = || -> Result<*mut SearchResult, TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
-
-        search_json(query_ptr, docs_limit, context, with_highlights)
+pub extern "C" fn fast_field_result_get_score(
+    result_ptr: *mut FastFieldResult,
+    row: usize,
+    error_buffer: *mut ErrorBuffer,
+) -> c_float {
+    let result = || -> Result<c_float, TantivyGoError> {
+        let result = assert_pointer(result_ptr)?;
+        result
+            .scores
+            .get(row)
+            .copied()
+            .ok_or_else(|| {
+                TantivyGoError::InvalidArgument(format!("Fast field row {} is out of range", row))
+            })
     };
 
-    match result() {
-        Ok(search_result) => search_result,
+    match catch_panic(result) {
+        Ok(score) => score,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
-            ptr::null_mut()
+            set_error(&err, error_buffer);
+            0.0
         }
     }
 }
 
-/// Performs a search and returns only fast field values (no full document loading).
-/// Returns the number of results found. Results are written to pre-allocated output arrays.
+/// Returns the `FAST_FIELD_TYPE_*` code of a value, `FAST_FIELD_TYPE_NONE` when the
+/// document has no value. `column` is the position of the field in the requested fast fields.
 #[logcall]
 #[no_mangle]
-pub extern "C" fn context_search_fast_field(
-    context_ptr: *mut TantivyContext,
-    field_ids_ptr: *mut c_uint,
-    field_weights_ptr: *mut c_float,
-    field_ids_len: usize,
-    query_ptr: *const c_char,
-    fast_field_id: c_uint,
-    docs_limit: usize,
-    out_scores_ptr: *mut c_float,
-    out_values_ptr: *mut *mut c_char,
-    error_buffer: *mut *mut c_char,
-) -> usize {
-    let result = || -> Result<usize, TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
-
-        if out_scores_ptr.is_null() || out_values_ptr.is_null() {
-            return Err(TantivyGoError("Output pointers are null".to_string()));
-        }
-
-        let (scores, values) = search_fast_field(
-            field_ids_ptr,
-            field_weights_ptr,
-            field_ids_len,
-            query_ptr,
-            fast_field_id,
-            docs_limit,
-            context,
-        )?;
-
-        let count = scores.len();
-        if count == 0 {
-            return Ok(0);
-        }
-
-        let out_scores = unsafe { std::slice::from_raw_parts_mut(out_scores_ptr, count) };
-        for (i, score) in scores.into_iter().enumerate() {
-            out_scores[i] = score;
-        }
-
-        let out_values = unsafe { std::slice::from_raw_parts_mut(out_values_ptr, count) };
-        for (i, value) in values.into_iter().enumerate() {
-            match value {
-                Some(s) => match CString::new(s) {
-                    Ok(cstr) => out_values[i] = cstr.into_raw(),
-                    Err(_) => out_values[i] = ptr::null_mut(),
-                },
-                None => out_values[i] = ptr::null_mut(),
-            }
-        }
-
-        Ok(count)
+pub extern "C" fn fast_field_result_get_type(
+    result_ptr: *mut FastFieldResult,
+    row: usize,
+    column: usize,
+    error_buffer: *mut ErrorBuffer,
+) -> u8 {
+    let result = || -> Result<u8, TantivyGoError> {
+        let result = assert_pointer(result_ptr)?;
+        Ok(fast_field_value(result, row, column)?
+            .map_or(FAST_FIELD_TYPE_NONE, FastFieldValue::type_code))
     };
 
-    match result() {
-        Ok(count) => count,
+    match catch_panic(result) {
+        Ok(type_code) => type_code,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
-            0
+            set_error(&err, error_buffer);
+            FAST_FIELD_TYPE_NONE
         }
     }
 }
 
-/// Free an array of strings returned by context_search_fast_field
-#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// Returns a str value, to be freed with `string_free`.
 #[logcall]
 #[no_mangle]
-pub extern "C" fn fast_field_values_free(values_ptr: *mut *mut c_char, count: usize) {
-    if values_ptr.is_null() || count == 0 {
-        return;
-    }
+pub extern "C" fn fast_field_result_get_str(
+    result_ptr: *mut FastFieldResult,
+    row: usize,
+    column: usize,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut c_char {
+    let result = || -> Result<CString, TantivyGoError> {
+        let value = typed_fast_field_value(result_ptr, row, column, "str", |value| match value {
+            FastFieldValue::Str(value) => Some(value.clone()),
+            _ => None,
+        })?;
+        CString::new(value).map_err(TantivyGoError::from)
+    };
 
-    let values = unsafe { std::slice::from_raw_parts_mut(values_ptr, count) };
-    for ptr in values.iter_mut() {
-        if !ptr.is_null() {
-            unsafe {
-                drop(CString::from_raw(*ptr));
-            }
-            *ptr = ptr::null_mut();
+    match catch_panic(result) {
+        Ok(value) => value.into_raw(),
+        Err(err) => {
+            set_error(&err, error_buffer);
+            ptr::null_mut()
         }
     }
 }
 
-/// Performs a search using JSON query and returns only fast field values (no full document loading).
-/// Returns the number of results found. Results are written to pre-allocated output arrays.
 #[logcall]
 #[no_mangle]
-pub extern "C" fn context_search_fast_field_json(
-    context_ptr: *mut TantivyContext,
-    query_ptr: *const c_char,
-    fast_field_id: c_uint,
-    docs_limit: usize,
-    out_scores_ptr: *mut c_float,
-    out_values_ptr: *mut *mut c_char,
-    error_buffer: *mut *mut c_char,
-) -> usize {
-    let result = || -> Result<usize, TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
-
-        if out_scores_ptr.is_null() || out_values_ptr.is_null() {
-            return Err(TantivyGoError("Output pointers are null".to_string()));
+pub extern "C" fn fast_field_result_get_u64(
+    result_ptr: *mut FastFieldResult,
+    row: usize,
+    column: usize,
+    error_buffer: *mut ErrorBuffer,
+) -> u64 {
+    let value = catch_panic(|| {
+        typed_fast_field_value(result_ptr, row, column, "u64", |value| match value {
+            FastFieldValue::U64(value) => Some(*value),
+            _ => None,
+        })
+    });
+
+    match value {
+        Ok(value) => value,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
         }
+    }
+}
 
-        let (scores, values) = search_fast_field_json(
-            query_ptr,
-            fast_field_id,
-            docs_limit,
-            context,
-        )?;
-
-        let count = scores.len();
-        if count == 0 {
-            return Ok(0);
+#[logcall]
+#[no_mangle]
+pub extern "C" fn fast_field_result_get_i64(
+    result_ptr: *mut FastFieldResult,
+    row: usize,
+    column: usize,
+    error_buffer: *mut ErrorBuffer,
+) -> i64 {
+    let value = catch_panic(|| {
+        typed_fast_field_value(result_ptr, row, column, "i64", |value| match value {
+            FastFieldValue::I64(value) => Some(*value),
+            _ => None,
+        })
+    });
+
+    match value {
+        Ok(value) => value,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0
         }
+    }
+}
 
-        let out_scores = unsafe { std::slice::from_raw_parts_mut(out_scores_ptr, count) };
-        for (i, score) in scores.into_iter().enumerate() {
-            out_scores[i] = score;
+#[logcall]
+#[no_mangle]
+pub extern "C" fn fast_field_result_get_f64(
+    result_ptr: *mut FastFieldResult,
+    row: usize,
+    column: usize,
+    error_buffer: *mut ErrorBuffer,
+) -> f64 {
+    let value = catch_panic(|| {
+        typed_fast_field_value(result_ptr, row, column, "f64", |value| match value {
+            FastFieldValue::F64(value) => Some(*value),
+            _ => None,
+        })
+    });
+
+    match value {
+        Ok(value) => value,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            0.0
         }
+    }
+}
 
-        let out_values = unsafe { std::slice::from_raw_parts_mut(out_values_ptr, count) };
-        for (i, value) in values.into_iter().enumerate() {
-            match value {
-                Some(s) => match CString::new(s) {
-                    Ok(cstr) => out_values[i] = cstr.into_raw(),
-                    Err(_) => out_values[i] = ptr::null_mut(),
-                },
-                None => out_values[i] = ptr::null_mut(),
-            }
+#[logcall]
+#[no_mangle]
+pub extern "C" fn fast_field_result_get_bool(
+    result_ptr: *mut FastFieldResult,
+    row: usize,
+    column: usize,
+    error_buffer: *mut ErrorBuffer,
+) -> bool {
+    let value = catch_panic(|| {
+        typed_fast_field_value(result_ptr, row, column, "bool", |value| match value {
+            FastFieldValue::Bool(value) => Some(*value),
+            _ => None,
+        })
+    });
+
+    match value {
+        Ok(value) => value,
+        Err(err) => {
+            set_error(&err, error_buffer);
+            false
         }
+    }
+}
 
-        Ok(count)
-    };
-
-    match result() {
-        Ok(count) => count,
+/// Returns a date value as nanoseconds since the Unix epoch.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn fast_field_result_get_date(
+    result_ptr: *mut FastFieldResult,
+    row: usize,
+    column: usize,
+    error_buffer: *mut ErrorBuffer,
+) -> i64 {
+    let value = catch_panic(|| {
+        typed_fast_field_value(result_ptr, row, column, "date", |value| match value {
+            FastFieldValue::Date(value) => Some(*value),
+            _ => None,
+        })
+    });
+
+    match value {
+        Ok(value) => value,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
             0
         }
     }
 }
 
+#[logcall]
+#[no_mangle]
+pub extern "C" fn fast_field_result_free(result_ptr: *mut FastFieldResult) {
+    drop_any(result_ptr)
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[logcall]
 #[no_mangle]
@@ -573,38 +1470,124 @@ pub extern "C" fn context_free(context_ptr: *mut TantivyContext) {
 #[no_mangle]
 pub extern "C" fn search_result_get_size(
     result_ptr: *mut SearchResult,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) -> usize {
     let result = || -> Result<usize, TantivyGoError> {
         let result = assert_pointer(result_ptr)?;
         Ok(result.size)
     };
 
-    match result() {
+    match catch_panic(result) {
         Ok(size) => size,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
+            0
+        }
+    }
+}
+
+/// Returns the number of documents matching the query, which may exceed the returned size.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn search_result_get_total_hits(
+    result_ptr: *mut SearchResult,
+    error_buffer: *mut ErrorBuffer,
+) -> usize {
+    let result = || -> Result<usize, TantivyGoError> {
+        let result = assert_pointer(result_ptr)?;
+        Ok(result.total_hits)
+    };
+
+    match catch_panic(result) {
+        Ok(total_hits) => total_hits,
+        Err(err) => {
+            set_error(&err, error_buffer);
             0
         }
     }
 }
 
+/// Returns the cursor of the last document, to be passed as `search_after_ptr` of the next
+/// search, or null when the result is empty. The string must be freed with `string_free`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn search_result_get_cursor(
+    result_ptr: *mut SearchResult,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut c_char {
+    let result = || -> Result<Option<String>, TantivyGoError> {
+        let result = assert_pointer(result_ptr)?;
+        Ok(result.cursor.clone())
+    };
+
+    match catch_panic(result) {
+        Ok(Some(cursor)) => match CString::new(cursor) {
+            Ok(cstr) => cstr.into_raw(),
+            Err(err) => {
+                set_error(&TantivyGoError::from(err), error_buffer);
+                ptr::null_mut()
+            }
+        },
+        Ok(None) => ptr::null_mut(),
+        Err(err) => {
+            set_error(&err, error_buffer);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the facet counts of the search as a json object mapping every requested prefix to
+/// the counts of its children, or null when no facet was requested.
+/// The string must be freed with `string_free`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn search_result_get_facet_counts(
+    result_ptr: *mut SearchResult,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut c_char {
+    let result = || -> Result<Option<String>, TantivyGoError> {
+        let result = assert_pointer(result_ptr)?;
+        result
+            .facet_counts
+            .as_ref()
+            .map(|counts| {
+                serde_json::to_string(counts).map_err(|err| TantivyGoError::Internal(err.to_string()))
+            })
+            .transpose()
+    };
+
+    match catch_panic(result) {
+        Ok(Some(counts)) => match CString::new(counts) {
+            Ok(cstr) => cstr.into_raw(),
+            Err(err) => {
+                set_error(&TantivyGoError::from(err), error_buffer);
+                ptr::null_mut()
+            }
+        },
+        Ok(None) => ptr::null_mut(),
+        Err(err) => {
+            set_error(&err, error_buffer);
+            ptr::null_mut()
+        }
+    }
+}
+
 #[logcall]
 #[no_mangle]
 pub extern "C" fn search_result_get_doc(
     result_ptr: *mut SearchResult,
     index: usize,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) -> *mut Document {
     let result = || -> Result<*mut Document, TantivyGoError> {
         let result = assert_pointer(result_ptr)?;
         get_doc(index, result)
     };
 
-    match result() {
+    match catch_panic(result) {
         Ok(doc) => doc,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
             ptr::null_mut()
         }
     }
@@ -620,32 +1603,37 @@ pub extern "C" fn search_result_free(result_ptr: *mut SearchResult) {
 #[logcall]
 #[no_mangle]
 pub extern "C" fn document_create() -> *mut Document {
-    Box::into_raw(Box::new(Document {
-        tantivy_doc: TantivyDocument::new(),
-        highlights: vec![],
-        score: 0.0,
-    }))
+    log_panic(ptr::null_mut(), || {
+        Box::into_raw(Box::new(Document {
+            tantivy_doc: TantivyDocument::new(),
+            highlights: vec![],
+            score: 0.0,
+            collapsed: None,
+        }))
+    })
 }
 
 #[logcall]
 #[no_mangle]
 pub extern "C" fn document_add_field(
     doc_ptr: *mut Document,
+    context_ptr: *mut TantivyContext,
     field_id: c_uint,
     field_value_ptr: *const c_char,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) {
     let result = || -> Result<(), TantivyGoError> {
         let doc = assert_pointer(doc_ptr)?;
-        let field_value = assert_str(field_value_ptr)?;
+        let context = assert_ref(context_ptr)?;
+        let field_value = context_str(context, field_value_ptr)?;
 
         add_field(doc, field_id, &field_value)
     };
 
-    match result() {
+    match catch_panic(result) {
         Ok(_) => {}
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
         }
     }
 }
@@ -654,27 +1642,171 @@ pub extern "C" fn document_add_field(
 #[no_mangle]
 pub extern "C" fn document_add_fields(
     doc_ptr: *mut Document,
+    context_ptr: *mut TantivyContext,
     field_ids_ptr: *mut c_uint,
     field_ids_len: usize,
     field_value_ptr: *const c_char,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) {
     let result = || -> Result<(), TantivyGoError> {
         let doc = assert_pointer(doc_ptr)?;
         let field_ids = assert_pointer(field_ids_ptr)?;
-        let field_value = assert_str(field_value_ptr)?;
+        let context = assert_ref(context_ptr)?;
+        let field_value = context_str(context, field_value_ptr)?;
 
         add_fields(doc, field_ids, field_ids_len, &field_value)
     };
 
-    match result() {
+    match catch_panic(result) {
         Ok(_) => {}
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
         }
     }
 }
 
+#[logcall]
+#[no_mangle]
+pub extern "C" fn document_add_i64(
+    doc_ptr: *mut Document,
+    field_id: c_uint,
+    field_value: i64,
+    error_buffer: *mut ErrorBuffer,
+) {
+    let result = || -> Result<(), TantivyGoError> {
+        let doc = assert_pointer(doc_ptr)?;
+
+        add_i64_value(doc, field_id, field_value)
+    };
+
+    if let Err(err) = catch_panic(result) {
+        set_error(&err, error_buffer);
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn document_add_u64(
+    doc_ptr: *mut Document,
+    field_id: c_uint,
+    field_value: u64,
+    error_buffer: *mut ErrorBuffer,
+) {
+    let result = || -> Result<(), TantivyGoError> {
+        let doc = assert_pointer(doc_ptr)?;
+
+        add_u64_value(doc, field_id, field_value)
+    };
+
+    if let Err(err) = catch_panic(result) {
+        set_error(&err, error_buffer);
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn document_add_f64(
+    doc_ptr: *mut Document,
+    field_id: c_uint,
+    field_value: f64,
+    error_buffer: *mut ErrorBuffer,
+) {
+    let result = || -> Result<(), TantivyGoError> {
+        let doc = assert_pointer(doc_ptr)?;
+
+        add_f64_value(doc, field_id, field_value)
+    };
+
+    if let Err(err) = catch_panic(result) {
+        set_error(&err, error_buffer);
+    }
+}
+
+#[logcall]
+#[no_mangle]
+pub extern "C" fn document_add_bool(
+    doc_ptr: *mut Document,
+    field_id: c_uint,
+    field_value: bool,
+    error_buffer: *mut ErrorBuffer,
+) {
+    let result = || -> Result<(), TantivyGoError> {
+        let doc = assert_pointer(doc_ptr)?;
+
+        add_bool_value(doc, field_id, field_value)
+    };
+
+    if let Err(err) = catch_panic(result) {
+        set_error(&err, error_buffer);
+    }
+}
+
+/// Adds a date value given as nanoseconds since the Unix epoch.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn document_add_date(
+    doc_ptr: *mut Document,
+    field_id: c_uint,
+    timestamp_nanos: i64,
+    error_buffer: *mut ErrorBuffer,
+) {
+    let result = || -> Result<(), TantivyGoError> {
+        let doc = assert_pointer(doc_ptr)?;
+
+        add_date_value(doc, field_id, timestamp_nanos)
+    };
+
+    if let Err(err) = catch_panic(result) {
+        set_error(&err, error_buffer);
+    }
+}
+
+/// Adds a facet given as a path like `/space/abc/type/note`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn document_add_facet(
+    doc_ptr: *mut Document,
+    context_ptr: *mut TantivyContext,
+    field_id: c_uint,
+    facet_path_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) {
+    let result = || -> Result<(), TantivyGoError> {
+        let doc = assert_pointer(doc_ptr)?;
+        let context = assert_ref(context_ptr)?;
+        let facet_path = context_str(context, facet_path_ptr)?;
+
+        add_facet_value(doc, field_id, &facet_path)
+    };
+
+    if let Err(err) = catch_panic(result) {
+        set_error(&err, error_buffer);
+    }
+}
+
+/// Adds a json object field value given as a serialized json object.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn document_add_json(
+    doc_ptr: *mut Document,
+    context_ptr: *mut TantivyContext,
+    field_id: c_uint,
+    json_ptr: *const c_char,
+    error_buffer: *mut ErrorBuffer,
+) {
+    let result = || -> Result<(), TantivyGoError> {
+        let doc = assert_pointer(doc_ptr)?;
+        let context = assert_ref(context_ptr)?;
+        let json = context_str(context, json_ptr)?;
+
+        add_json_value(doc, field_id, &json)
+    };
+
+    if let Err(err) = catch_panic(result) {
+        set_error(&err, error_buffer);
+    }
+}
+
 #[logcall]
 #[no_mangle]
 pub extern "C" fn document_as_json(
@@ -682,7 +1814,7 @@ pub extern "C" fn document_as_json(
     include_field_ids_ptr: *mut c_uint,
     include_field_ids_len: usize,
     schema_ptr: *mut Schema,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) -> *mut c_char {
     let result = || -> Result<String, TantivyGoError> {
         let doc = assert_pointer(doc_ptr)?;
@@ -691,16 +1823,16 @@ pub extern "C" fn document_as_json(
         convert_document_as_json(include_field_ids_ptr, include_field_ids_len, doc, schema)
     };
 
-    match result() {
+    match catch_panic(result) {
         Ok(json) => match CString::new(json) {
             Ok(cstr) => cstr.into_raw(),
             Err(err) => {
-                set_error(&err.to_string(), error_buffer);
+                set_error(&TantivyGoError::from(err), error_buffer);
                 ptr::null_mut()
             }
         },
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
             ptr::null_mut()
         }
     }
@@ -718,18 +1850,24 @@ pub extern "C" fn document_free(doc_ptr: *mut Document) {
 #[no_mangle]
 pub extern "C" fn string_free(s: *mut c_char) {
     if !s.is_null() {
-        unsafe {
+        log_panic((), || unsafe {
             drop(CString::from_raw(s));
-        }
+        })
     }
 }
 
+/// Initializes logging. `clear_on_panic` and `utf8_lenient` are the defaults of the contexts
+/// created afterwards, which may override them in their options.
+///
+/// # Safety
+///
+/// `log_level_ptr` must be a valid nul-terminated string.
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[logcall]
 #[no_mangle]
 pub unsafe extern "C" fn init_lib(
     log_level_ptr: *const c_char,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
     clear_on_panic: bool,
     utf8_lenient: bool,
 ) {
@@ -739,15 +1877,16 @@ pub unsafe extern "C" fn init_lib(
         Ok(())
     };
 
-    match result() {
+    match catch_panic(result) {
         Ok(_) => (),
-        Err(err) => set_error(&err.to_string(), error_buffer),
+        Err(err) => set_error(&err, error_buffer),
     }
 }
 
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[logcall]
 #[no_mangle]
-pub extern "C" fn context_wait_and_free(context_ptr: *mut TantivyContext, error_buffer: *mut *mut c_char) {
+pub extern "C" fn context_wait_and_free(context_ptr: *mut TantivyContext, error_buffer: *mut ErrorBuffer) {
     if context_ptr.is_null() {
         return;
     }
@@ -757,51 +1896,77 @@ pub extern "C" fn context_wait_and_free(context_ptr: *mut TantivyContext, error_
         let context = unsafe { Box::from_raw(context_ptr) };
         
         // Call wait_merging_threads on the writer
-        context.writer.wait_merging_threads().map_err(|err| {
-            TantivyGoError::from_err("Failed to wait for merging threads", &err.to_string())
-        })?;
+        if let Some(writer) = context.into_writer() {
+            writer.wait_merging_threads().map_err(|err| {
+                TantivyGoError::from_tantivy("Failed to wait for merging threads", &err)
+            })?;
+        }
         
         // Box drops automatically when this function ends
         Ok(())
     };
 
-    if let Err(err) = result() {
-        set_error(&err.to_string(), error_buffer);
+    if let Err(err) = catch_panic(result) {
+        set_error(&err, error_buffer);
     }
     // Don't call drop_any - we've already taken ownership with Box::from_raw
 }
 
+/// Returns the payload of the last commit, or null when it has none.
+/// The string must be freed with `string_free`.
+#[logcall]
+#[no_mangle]
+pub extern "C" fn context_last_commit_payload(
+    context_ptr: *mut TantivyContext,
+    error_buffer: *mut ErrorBuffer,
+) -> *mut c_char {
+    let result = || -> Result<Option<String>, TantivyGoError> {
+        let context = assert_ref(context_ptr)?;
+        last_commit_payload(&context.index)
+    };
+
+    match catch_context_panic(context_ptr, result) {
+        Ok(Some(payload)) => match CString::new(payload) {
+            Ok(payload) => payload.into_raw(),
+            Err(err) => {
+                set_error(&TantivyGoError::from(err), error_buffer);
+                ptr::null_mut()
+            }
+        },
+        Ok(None) => ptr::null_mut(),
+        Err(err) => {
+            set_error(&err, error_buffer);
+            ptr::null_mut()
+        }
+    }
+}
+
 #[logcall]
 #[no_mangle]
 pub extern "C" fn context_commit_opstamp(context_ptr: *mut TantivyContext) -> u64 {
     let result = || -> Result<u64, TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
-        Ok(context.writer.commit_opstamp())
+        let context = assert_ref(context_ptr)?;
+        Ok(context.writer()?.commit_opstamp())
     };
 
-    match result() {
-        Ok(opstamp) => opstamp,
-        Err(_) => 0,
-    }
+    catch_context_panic(context_ptr, result).unwrap_or_default()
 }
 
 #[logcall]
 #[no_mangle]
 pub extern "C" fn context_reload_reader(
     context_ptr: *mut TantivyContext,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) {
     let result = || -> Result<(), TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
-        // Force a reload by actually calling the reader method
-        let _reader = context.reader();
-        Ok(())
+        let context = assert_ref(context_ptr)?;
+        context.reload_reader()
     };
 
-    match result() {
+    match catch_context_panic(context_ptr, result) {
         Ok(_) => {},
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
         }
     }
 }
@@ -810,10 +1975,12 @@ pub extern "C" fn context_reload_reader(
 #[no_mangle]
 pub extern "C" fn context_garbage_collect_files(
     context_ptr: *mut TantivyContext,
-    error_buffer: *mut *mut c_char,
+    error_buffer: *mut ErrorBuffer,
 ) -> u64 {
     let result = || -> Result<u64, TantivyGoError> {
-        let context = assert_pointer(context_ptr)?;
+        let context = assert_ref(context_ptr)?;
+        // Keep commits out while collecting
+        let _writer = context.writer()?;
         
         // Get living files by checking searchable segments
         let mut living_files = std::collections::HashSet::new();
@@ -825,18 +1992,18 @@ pub extern "C" fn context_garbage_collect_files(
             }
         }
         
-        // Get mutable reference to directory and perform garbage collection
-        let directory = context.index.directory_mut();
+        // Get a mutable handle on the directory and perform garbage collection
+        let mut directory = context.index.directory().clone();
         match directory.garbage_collect(|| living_files.clone()) {
             Ok(gc_result) => Ok(gc_result.deleted_files.len() as u64),
-            Err(err) => Err(TantivyGoError::from_err("Garbage collection failed", &err.to_string())),
+            Err(err) => Err(TantivyGoError::from_tantivy("Garbage collection failed", &err)),
         }
     };
 
-    match result() {
+    match catch_context_panic(context_ptr, result) {
         Ok(deleted_count) => deleted_count,
         Err(err) => {
-            set_error(&err.to_string(), error_buffer);
+            set_error(&err, error_buffer);
             0
         }
     }
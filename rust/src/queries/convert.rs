@@ -1,29 +1,106 @@
-use crate::queries::{FinalQuery, GoQuery, QueryElement, QueryModifier};
-use crate::tantivy_util::{extract_terms, TantivyGoError};
+use crate::queries::{FinalQuery, GoQuery, PatternQuery, QueryElement, QueryModifier, RangeValue};
+use crate::tantivy_util::{extract_terms, parse_facet, TantivyGoError};
+use std::ops::Bound;
 use tantivy::query::Occur::{Must, Should};
 use tantivy::query::{
-    AllQuery as TAllQuery, BooleanQuery, BoostQuery, Occur, PhrasePrefixQuery, PhraseQuery, Query,
-    TermQuery,
+    AllQuery as TAllQuery, BooleanQuery, BoostQuery, EmptyQuery, FuzzyTermQuery, Occur,
+    PhrasePrefixQuery, PhraseQuery, Query, RangeQuery, TermQuery,
 };
-use tantivy::schema::{IndexRecordOption, Schema};
-use tantivy::{Index, Score};
+use tantivy::schema::{Field, FieldType, IndexRecordOption, Schema};
+use tantivy::{DateTime, Index, Score, Term};
 
-fn contains_all_query(subqueries: &[QueryElement]) -> bool {
+// Longer patterns are rejected before compiling, the compiled automaton is capped by `PatternQuery`
+const MAX_PATTERN_LEN: usize = 1024;
+
+// A query with its occurrence in the enclosing boolean query
+type Clause = (Occur, Box<dyn Query>);
+
+// Queries that do not refer to texts, so texts may be empty
+fn contains_textless_query(subqueries: &[QueryElement]) -> bool {
     subqueries.iter().any(|elem| match &elem.query {
-        Some(GoQuery::AllQuery { .. }) => true,
-        Some(GoQuery::BoolQuery { subqueries, .. }) => contains_all_query(subqueries),
+        Some(GoQuery::AllQuery { .. }) | Some(GoQuery::RangeQuery { .. }) => true,
+        Some(GoQuery::BoolQuery { subqueries, .. }) => contains_textless_query(subqueries),
         _ => false,
     })
 }
 
+// Translates a `*`/`?` glob into a regex, escaping everything else
+fn wildcard_to_regex(pattern: &str) -> String {
+    let mut regex = String::with_capacity(pattern.len() * 2);
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '\\' | '.' | '+' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' | '#'
+            | '&' | '-' | '~' => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            _ => regex.push(c),
+        }
+    }
+    regex
+}
+
+// Compiles `pattern` against the term dictionary of a text field, bypassing its tokenizer
+fn pattern_query(
+    field: Field,
+    field_type: &FieldType,
+    json_path: &str,
+    pattern: &str,
+) -> Result<Box<dyn Query>, TantivyGoError> {
+    match field_type {
+        FieldType::Str(options) if options.get_indexing_options().is_some() && json_path.is_empty() => {}
+        _ => return Err(TantivyGoError::QueryParse(
+            "Regex and wildcard queries need an indexed text field".to_string(),
+        )),
+    }
+    if pattern.len() > MAX_PATTERN_LEN {
+        return Err(TantivyGoError::QueryParse(format!(
+            "Regex pattern is too expensive: longer than {} bytes",
+            MAX_PATTERN_LEN
+        )));
+    }
+    Ok(Box::new(PatternQuery::new(pattern, field)?))
+}
+
+fn range_term(field: Field, field_type: &FieldType, value: &RangeValue) -> Result<Term, TantivyGoError> {
+    match (field_type, value) {
+        (FieldType::I64(_), RangeValue::I64(v)) => Ok(Term::from_field_i64(field, *v)),
+        (FieldType::U64(_), RangeValue::U64(v)) => Ok(Term::from_field_u64(field, *v)),
+        (FieldType::F64(_), RangeValue::F64(v)) => Ok(Term::from_field_f64(field, *v)),
+        (FieldType::Date(_), RangeValue::Date(v)) => Ok(Term::from_field_date_for_search(
+            field,
+            DateTime::from_timestamp_nanos(*v),
+        )),
+        _ => Err(TantivyGoError::QueryParse(format!(
+            "Range value {:?} does not match the field type {:?}",
+            value,
+            field_type.value_type()
+        ))),
+    }
+}
+
+fn range_bound(
+    field: Field,
+    field_type: &FieldType,
+    bound: &Bound<RangeValue>,
+) -> Result<Bound<Term>, TantivyGoError> {
+    match bound {
+        Bound::Included(value) => Ok(Bound::Included(range_term(field, field_type, value)?)),
+        Bound::Excluded(value) => Ok(Bound::Excluded(range_term(field, field_type, value)?)),
+        Bound::Unbounded => Ok(Bound::Unbounded),
+    }
+}
+
 pub fn convert_to_tantivy(
     index: &Index,
     parsed: FinalQuery,
     schema: &Schema,
 ) -> Result<Box<dyn Query>, TantivyGoError> {
-    let has_all_query = contains_all_query(&parsed.query.subqueries);
-    if !has_all_query && (parsed.fields.is_empty() || parsed.texts.is_empty()) {
-        return Err(TantivyGoError(
+    let has_textless_query = contains_textless_query(&parsed.query.subqueries);
+    if !has_textless_query && (parsed.fields.is_empty() || parsed.texts.is_empty()) {
+        return Err(TantivyGoError::QueryParse(
             "Fields or texts cannot be empty".to_string(),
         ));
     }
@@ -37,53 +114,41 @@ pub fn convert_to_tantivy(
         schema: &Schema,
         texts: &[String],
         fields: &[String],
-    ) -> Result<Option<(Occur, Box<dyn Query>)>, TantivyGoError> {
+    ) -> Result<Option<Clause>, TantivyGoError> {
         let occur = match element.modifier {
             QueryModifier::Must => Occur::Must,
             QueryModifier::Should => Occur::Should,
             QueryModifier::MustNot => Occur::MustNot,
         };
 
-        let get_field_and_text = |fi: usize, ti: usize| -> Result<(_, _), TantivyGoError> {
+        // Field names may address a path inside a json field, e.g. `relations.status`
+        let get_field_and_text = |fi: usize, ti: usize| -> Result<(_, _, _), TantivyGoError> {
             let f_name = fields.get(fi)
-                .ok_or_else(|| TantivyGoError("Invalid field index".into()))?;
+                .ok_or_else(|| TantivyGoError::QueryParse("Invalid field index".into()))?;
             let txt = texts.get(ti)
-                .ok_or_else(|| TantivyGoError("Invalid text index".into()))?;
-            let f = schema.get_field(f_name)
-                .map_err(|_e| TantivyGoError("Invalid field name".into()))?;
-            Ok((f, txt.as_str()))
+                .ok_or_else(|| TantivyGoError::QueryParse("Invalid text index".into()))?;
+            let (f, path) = schema.find_field(f_name)
+                .ok_or_else(|| TantivyGoError::UnknownField(format!("Unknown field {}", f_name)))?;
+            Ok((f, path, txt.as_str()))
         };
 
         // Wrap extract_terms: handle zero-term errors differently for Must vs Should/MustNot
-        let get_terms = |f, txt| -> Result<Vec<(usize, tantivy::Term)>, TantivyGoError> {
-            match extract_terms(index, f, txt) {
+        let get_terms = |f, path, txt| -> Result<Vec<(usize, tantivy::Term)>, TantivyGoError> {
+            match extract_terms(index, f, path, txt) {
                 Ok(v) => Ok(v),
-                Err(ref e) if e.0.contains("Zero terms were extracted") => Ok(Vec::new()),
+                Err(TantivyGoError::NoTerms(_)) => Ok(Vec::new()),
                 Err(e) => Err(e),
             }
         };
 
         // Create a query that matches no documents - for empty Must clauses
-        let create_impossible_query = || -> Box<dyn Query> {
-            // Create a term that won't match any document
-            let term = tantivy::Term::from_field_text(schema.get_field("_id").unwrap_or_else(|_| {
-                // If no _id field exists, use the first field from the schema
-                if let Some(field) = schema.fields().next() {
-                    field.0
-                } else {
-                    // This should never happen as schemas should have at least one field
-                    panic!("Schema has no fields");
-                }
-            }), "__impossible_term_that_wont_match_anything__");
-            
-            Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
-        };
+        let create_impossible_query = || -> Box<dyn Query> { Box::new(EmptyQuery) };
 
         if let Some(go_q) = &element.query {
             let built = match go_q {
                 GoQuery::PhraseQuery { field_index, text_index, boost } => {
-                    let (f, txt) = get_field_and_text(*field_index, *text_index)?;
-                    let terms = get_terms(f, txt)?;
+                    let (f, path, txt) = get_field_and_text(*field_index, *text_index)?;
+                    let terms = get_terms(f, path, txt)?;
                     if terms.is_empty() {
                         // For empty Must clauses, return a query that matches nothing
                         if element.modifier == QueryModifier::Must {
@@ -101,8 +166,8 @@ pub fn convert_to_tantivy(
                     }
                 }
                 GoQuery::PhrasePrefixQuery { field_index, text_index, boost } => {
-                    let (f, txt) = get_field_and_text(*field_index, *text_index)?;
-                    let terms = get_terms(f, txt)?;
+                    let (f, path, txt) = get_field_and_text(*field_index, *text_index)?;
+                    let terms = get_terms(f, path, txt)?;
                     if terms.is_empty() {
                         if element.modifier == QueryModifier::Must {
                             Some(try_boost(occur, *boost, create_impossible_query()))
@@ -114,8 +179,8 @@ pub fn convert_to_tantivy(
                     }
                 }
                 GoQuery::TermQuery { field_index, text_index, boost } => {
-                    let (f, txt) = get_field_and_text(*field_index, *text_index)?;
-                    let terms = get_terms(f, txt)?;
+                    let (f, path, txt) = get_field_and_text(*field_index, *text_index)?;
+                    let terms = get_terms(f, path, txt)?;
                     if terms.is_empty() {
                         if element.modifier == QueryModifier::Must {
                             Some(try_boost(occur, *boost, create_impossible_query()))
@@ -129,8 +194,8 @@ pub fn convert_to_tantivy(
                     }
                 }
                 GoQuery::TermPrefixQuery { field_index, text_index, boost } => {
-                    let (f, txt) = get_field_and_text(*field_index, *text_index)?;
-                    let terms = get_terms(f, txt)?;
+                    let (f, path, txt) = get_field_and_text(*field_index, *text_index)?;
+                    let terms = get_terms(f, path, txt)?;
                     if terms.is_empty() {
                         if element.modifier == QueryModifier::Must {
                             Some(try_boost(occur, *boost, create_impossible_query()))
@@ -143,8 +208,8 @@ pub fn convert_to_tantivy(
                     }
                 }
                 GoQuery::EveryTermQuery { field_index, text_index, boost } => {
-                    let (f, txt) = get_field_and_text(*field_index, *text_index)?;
-                    let terms = get_terms(f, txt)?;
+                    let (f, path, txt) = get_field_and_text(*field_index, *text_index)?;
+                    let terms = get_terms(f, path, txt)?;
                     if terms.is_empty() {
                         if element.modifier == QueryModifier::Must {
                             Some(try_boost(occur, *boost, create_impossible_query()))
@@ -161,8 +226,8 @@ pub fn convert_to_tantivy(
                     }
                 }
                 GoQuery::OneOfTermQuery { field_index, text_index, boost } => {
-                    let (f, txt) = get_field_and_text(*field_index, *text_index)?;
-                    let terms = get_terms(f, txt)?;
+                    let (f, path, txt) = get_field_and_text(*field_index, *text_index)?;
+                    let terms = get_terms(f, path, txt)?;
                     if terms.is_empty() {
                         if element.modifier == QueryModifier::Must {
                             Some(try_boost(occur, *boost, create_impossible_query()))
@@ -180,9 +245,74 @@ pub fn convert_to_tantivy(
                         Some(try_boost(occur, *boost, Box::new(BooleanQuery::new(subs))))
                     }
                 }
+                GoQuery::FuzzyTermQuery { field_index, text_index, distance, transposition, boost }
+                | GoQuery::FuzzyPrefixQuery { field_index, text_index, distance, transposition, boost } => {
+                    let (f, path, txt) = get_field_and_text(*field_index, *text_index)?;
+                    let terms = get_terms(f, path, txt)?;
+                    let is_prefix = matches!(go_q, GoQuery::FuzzyPrefixQuery { .. });
+                    let fuzzy_query = |term| -> Box<dyn Query> {
+                        if is_prefix {
+                            Box::new(FuzzyTermQuery::new_prefix(term, *distance, *transposition))
+                        } else {
+                            Box::new(FuzzyTermQuery::new(term, *distance, *transposition))
+                        }
+                    };
+                    if terms.is_empty() {
+                        if element.modifier == QueryModifier::Must {
+                            Some(try_boost(occur, *boost, create_impossible_query()))
+                        } else {
+                            return Ok(None);
+                        }
+                    } else if terms.len() == 1 {
+                        Some(try_boost(occur, *boost, fuzzy_query(terms[0].1.clone())))
+                    } else {
+                        let mut subs = Vec::new();
+                        for (_pos, term) in terms {
+                            subs.push(try_boost(Must, 1.0, fuzzy_query(term)));
+                        }
+                        Some(try_boost(occur, *boost, Box::new(BooleanQuery::new(subs))))
+                    }
+                }
+                GoQuery::RegexQuery { field_index, text_index, boost }
+                | GoQuery::WildcardQuery { field_index, text_index, boost } => {
+                    let (f, path, txt) = get_field_and_text(*field_index, *text_index)?;
+                    let field_type = schema.get_field_entry(f).field_type();
+                    let query = if matches!(go_q, GoQuery::WildcardQuery { .. }) {
+                        pattern_query(f, field_type, path, &wildcard_to_regex(txt))?
+                    } else {
+                        pattern_query(f, field_type, path, txt)?
+                    };
+                    Some(try_boost(occur, *boost, query))
+                }
+                GoQuery::FacetQuery { field_index, text_index, boost } => {
+                    let (f, path, txt) = get_field_and_text(*field_index, *text_index)?;
+                    if !matches!(schema.get_field_entry(f).field_type(), FieldType::Facet(_))
+                        || !path.is_empty()
+                    {
+                        return Err(TantivyGoError::QueryParse("Facet queries need a facet field".into()));
+                    }
+                    // Facets are indexed with all their ancestors, so the term matches the subtree
+                    let term = Term::from_facet(f, &parse_facet(txt)?);
+                    Some(try_boost(occur, *boost,
+                        Box::new(TermQuery::new(term, IndexRecordOption::Basic))))
+                }
                 GoQuery::AllQuery { boost } => {
                     Some(try_boost(occur, *boost, Box::new(TAllQuery)))
                 }
+                GoQuery::RangeQuery { field_index, lower, upper, boost } => {
+                    if *lower == Bound::Unbounded && *upper == Bound::Unbounded {
+                        return Err(TantivyGoError::QueryParse("Range query needs at least one bound".into()));
+                    }
+                    let f_name = fields.get(*field_index)
+                        .ok_or_else(|| TantivyGoError::QueryParse("Invalid field index".into()))?;
+                    let f = schema.get_field(f_name)
+                        .map_err(|_e| TantivyGoError::UnknownField(format!("Unknown field {}", f_name)))?;
+                    let field_type = schema.get_field_entry(f).field_type();
+                    Some(try_boost(occur, *boost, Box::new(RangeQuery::new(
+                        range_bound(f, field_type, lower)?,
+                        range_bound(f, field_type, upper)?,
+                    ))))
+                }
                 GoQuery::BoolQuery { subqueries, boost } => {
                     let mut child = Vec::new();
                     for sq in subqueries {
@@ -204,12 +334,12 @@ pub fn convert_to_tantivy(
             return Ok(built);
         }
 
-        Err(TantivyGoError("Query is None in QueryElement".into()))
+        Err(TantivyGoError::QueryParse("Query is None in QueryElement".into()))
     }
 
     // wrap boost
     fn try_boost(occur: Occur, boost: f32, q: Box<dyn Query>) -> (Occur, Box<dyn Query>) {
-        if (boost - 1.0).abs() < std::f32::EPSILON {
+        if (boost - 1.0).abs() < f32::EPSILON {
             (occur, q)
         } else {
             (occur, Box::new(BoostQuery::new(q, boost as Score)))
@@ -224,7 +354,7 @@ pub fn convert_to_tantivy(
         }
     }
     if top.is_empty() {
-        return Err(TantivyGoError("No usable clauses in query".into()));
+        return Err(TantivyGoError::NoTerms("No usable clauses in query".into()));
     }
     Ok(Box::new(BooleanQuery::from(top)))
 }
@@ -236,7 +366,7 @@ pub fn parse_query_from_json(
 ) -> Result<Box<dyn Query>, TantivyGoError> {
     match serde_json::from_str(json) {
         Ok(parsed) => convert_to_tantivy(index, parsed, schema),
-        Err(e) => Err(TantivyGoError(e.to_string())),
+        Err(e) => Err(TantivyGoError::QueryParse(e.to_string())),
     }
 }
 
@@ -244,16 +374,22 @@ pub fn parse_query_from_json(
 mod tests {
     use crate::queries::convert::convert_to_tantivy;
     use crate::queries::models::BoolQuery;
-    use crate::queries::{FinalQuery, GoQuery, QueryElement, QueryModifier};
+    use crate::queries::{FinalQuery, GoQuery, PatternQuery, QueryElement, QueryModifier, RangeValue};
     use std::fs;
+    use std::ops::Bound;
     use tantivy::query::BooleanQuery;
     use tantivy::query::PhraseQuery as TPhraseQuery;
     use tantivy::query::TermQuery as TTermQuery;
-    use tantivy::query::{BoostQuery, Occur as TO};
+    use tantivy::query::{BoostQuery, EmptyQuery, Occur as TO};
     use tantivy::query::{PhrasePrefixQuery as TPhrasePrefixQuery, Query};
-    use tantivy::schema::{Field, IndexRecordOption, Schema, TextFieldIndexing, STORED, TEXT};
+    use tantivy::query::RangeQuery as TRangeQuery;
+    use tantivy::query::FuzzyTermQuery as TFuzzyTermQuery;
+    use tantivy::schema::{
+        Facet, FacetOptions, Field, IndexRecordOption, JsonObjectOptions, Schema,
+        TextFieldIndexing, FAST, INDEXED, STORED, STRING, TEXT,
+    };
     use tantivy::tokenizer::{SimpleTokenizer, TextAnalyzer};
-    use tantivy::{Index, Term};
+    use tantivy::{DateTime, Index, Term};
 
     fn expected_query() -> FinalQuery {
         FinalQuery {
@@ -416,10 +552,10 @@ mod tests {
                 .set_tokenizer("simple")
                 .set_index_option(IndexRecordOption::WithFreqsAndPositions),
         );
-        let f1 = schema_b.add_text_field("f1", opts.clone());
-        let f2 = schema_b.add_text_field("f2", opts.clone());
-        let f3 = schema_b.add_text_field("f3", opts.clone());
-        let f4 = schema_b.add_text_field("f4", opts);
+        schema_b.add_text_field("f1", opts.clone());
+        schema_b.add_text_field("f2", opts.clone());
+        schema_b.add_text_field("f3", opts.clone());
+        schema_b.add_text_field("f4", opts);
         let schema = schema_b.build();
 
         // Create index and register tokenizer
@@ -456,8 +592,8 @@ mod tests {
         
         let debug = format!("{:?}", dq);
         assert!(debug.contains("hello"));
-        // The impossible query term should also be in the debug output
-        assert!(debug.contains("__impossible_term_that_wont_match_anything__"));
+        // The empty Must clause matches nothing
+        assert!(subs[0].1.as_any().is::<EmptyQuery>());
     }
 
     #[test]
@@ -607,6 +743,272 @@ mod tests {
         assert_eq!(format!("{parsed:#?}"), format!("{expected:#?}"));
     }
 
+    #[test]
+    fn test_convert_json_path() {
+        let mut schema_builder = Schema::builder();
+        let relations = schema_builder.add_json_field(
+            "relations",
+            JsonObjectOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("simple")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        index.tokenizers().register(
+            "simple",
+            TextAnalyzer::builder(SimpleTokenizer::default()).build(),
+        );
+
+        let fq = FinalQuery {
+            texts: vec!["done".into()],
+            fields: vec!["relations.status".into()],
+            query: BoolQuery { subqueries: vec![
+                QueryElement { query: Some(GoQuery::TermQuery { field_index: 0, text_index: 0, boost: 1.0 }), modifier: QueryModifier::Must },
+            ]},
+        };
+
+        let parsed = convert_to_tantivy(&index, fq, &schema).expect("conversion failed");
+        let mut term = Term::from_field_json_path(relations, "status", false);
+        term.append_type_and_str("done");
+        let expected = BooleanQuery::new(vec![(
+            TO::Must,
+            Box::new(TTermQuery::new(term, IndexRecordOption::WithFreqs)),
+        )]);
+
+        assert_eq!(format!("{parsed:#?}"), format!("{expected:#?}"));
+    }
+
+    #[test]
+    fn test_convert_range_query() {
+        let mut schema_builder = Schema::builder();
+        let size = schema_builder.add_u64_field("size", INDEXED | FAST);
+        let modified = schema_builder.add_date_field("modified", INDEXED | FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+
+        let json = r#"{
+            "texts": [],
+            "fields": ["size", "modified"],
+            "query": {"subqueries": [
+                {"query_type": 8, "query_modifier": 0, "query": {
+                    "field_index": 0, "value_type": 1,
+                    "lower": {"value": 10, "inclusive": true},
+                    "upper": {"value": 20, "inclusive": false}
+                }},
+                {"query_type": 8, "query_modifier": 0, "query": {
+                    "field_index": 1, "value_type": 3,
+                    "lower": {"value": 1000000000}
+                }}
+            ]}
+        }"#;
+
+        let parsed: FinalQuery = serde_json::from_str(json).expect("Json was not parsed");
+        assert_eq!(
+            parsed.query.subqueries[0].query,
+            Some(GoQuery::RangeQuery {
+                field_index: 0,
+                lower: Bound::Included(RangeValue::U64(10)),
+                upper: Bound::Excluded(RangeValue::U64(20)),
+                boost: 1.0,
+            })
+        );
+
+        let query = convert_to_tantivy(&index, parsed, &schema).expect("conversion failed");
+        let expected = BooleanQuery::new(vec![
+            (
+                TO::Must,
+                Box::new(TRangeQuery::new(
+                    Bound::Included(Term::from_field_u64(size, 10)),
+                    Bound::Excluded(Term::from_field_u64(size, 20)),
+                )),
+            ),
+            (
+                TO::Must,
+                Box::new(TRangeQuery::new(
+                    Bound::Included(Term::from_field_date_for_search(
+                        modified,
+                        DateTime::from_timestamp_nanos(1_000_000_000),
+                    )),
+                    Bound::Unbounded,
+                )),
+            ),
+        ]);
+        assert_eq!(format!("{query:#?}"), format!("{expected:#?}"));
+    }
+
+    #[test]
+    fn test_convert_range_query_errors() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("size", INDEXED | FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+
+        let range = |lower, upper| FinalQuery {
+            texts: vec![],
+            fields: vec!["size".into()],
+            query: BoolQuery { subqueries: vec![
+                QueryElement { query: Some(GoQuery::RangeQuery { field_index: 0, lower, upper, boost: 1.0 }), modifier: QueryModifier::Must },
+            ]},
+        };
+
+        let err = convert_to_tantivy(&index, range(Bound::Unbounded, Bound::Unbounded), &schema)
+            .expect_err("unbounded range must fail");
+        assert!(err.message().contains("at least one bound"));
+
+        let err = convert_to_tantivy(&index, range(Bound::Included(RangeValue::I64(-1)), Bound::Unbounded), &schema)
+            .expect_err("mismatched type must fail");
+        assert!(err.message().contains("does not match the field type"));
+    }
+
+    #[test]
+    fn test_convert_fuzzy_query() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+
+        let json = r#"{
+            "texts": ["helo", "wrld again"],
+            "fields": ["title"],
+            "query": {"subqueries": [
+                {"query_type": 9, "query_modifier": 0, "query": {
+                    "field_index": 0, "text_index": 0, "distance": 2, "transposition": false
+                }},
+                {"query_type": 10, "query_modifier": 1, "query": {
+                    "field_index": 0, "text_index": 1
+                }}
+            ]}
+        }"#;
+
+        let parsed: FinalQuery = serde_json::from_str(json).expect("Json was not parsed");
+        assert_eq!(
+            parsed.query.subqueries[1].query,
+            Some(GoQuery::FuzzyPrefixQuery {
+                field_index: 0,
+                text_index: 1,
+                distance: 1,
+                transposition: true,
+                boost: 1.0,
+            })
+        );
+
+        let query = convert_to_tantivy(&index, parsed, &schema).expect("conversion failed");
+        let term = |w| Term::from_field_text(title, w);
+        let expected = BooleanQuery::new(vec![
+            (
+                TO::Must,
+                Box::new(TFuzzyTermQuery::new(term("helo"), 2, false)),
+            ),
+            (
+                TO::Should,
+                Box::new(BooleanQuery::new(vec![
+                    (TO::Must, Box::new(TFuzzyTermQuery::new_prefix(term("wrld"), 1, true)) as Box<dyn Query>),
+                    (TO::Must, Box::new(TFuzzyTermQuery::new_prefix(term("again"), 1, true))),
+                ])),
+            ),
+        ]);
+        assert_eq!(format!("{query:#?}"), format!("{expected:#?}"));
+
+        let json = r#"{"texts": ["a"], "fields": ["title"], "query": {"subqueries": [
+            {"query_type": 9, "query_modifier": 0, "query": {"field_index": 0, "text_index": 0, "distance": 3}}
+        ]}}"#;
+        let err = serde_json::from_str::<FinalQuery>(json).expect_err("distance 3 must fail");
+        assert!(err.to_string().contains("Invalid distance 3"));
+    }
+
+    #[test]
+    fn test_convert_pattern_query() {
+        let mut schema_builder = Schema::builder();
+        let path = schema_builder.add_text_field("path", STRING);
+        schema_builder.add_u64_field("size", INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+
+        let pattern = |query_type: u64, pattern: &str, field: &str| -> FinalQuery {
+            let json = serde_json::json!({
+                "texts": [pattern],
+                "fields": [field],
+                "query": {"subqueries": [
+                    {"query_type": query_type, "query_modifier": 0, "query": {"field_index": 0, "text_index": 0}}
+                ]}
+            });
+            serde_json::from_value(json).expect("Json was not parsed")
+        };
+
+        let query = convert_to_tantivy(&index, pattern(11, "2024-.*", "path"), &schema)
+            .expect("conversion failed");
+        let expected = BooleanQuery::new(vec![(
+            TO::Must,
+            Box::new(PatternQuery::new("2024-.*", path).unwrap()),
+        )]);
+        assert_eq!(format!("{query:#?}"), format!("{expected:#?}"));
+
+        let query = convert_to_tantivy(&index, pattern(12, "*.pdf", "path"), &schema)
+            .expect("conversion failed");
+        let expected = BooleanQuery::new(vec![(
+            TO::Must,
+            Box::new(PatternQuery::new(r".*\.pdf", path).unwrap()),
+        )]);
+        assert_eq!(format!("{query:#?}"), format!("{expected:#?}"));
+
+        let err = convert_to_tantivy(&index, pattern(11, "report(", "path"), &schema)
+            .expect_err("invalid pattern must fail");
+        assert!(err.message().starts_with("Invalid regex pattern"));
+
+        let err = convert_to_tantivy(&index, pattern(11, "(a|b)*a(a|b){20}", "path"), &schema)
+            .expect_err("huge automaton must fail");
+        assert!(err.message().starts_with("Regex pattern is too expensive"));
+
+        let err = convert_to_tantivy(&index, pattern(12, &"?".repeat(2000), "path"), &schema)
+            .expect_err("long pattern must fail");
+        assert!(err.message().starts_with("Regex pattern is too expensive"));
+
+        let err = convert_to_tantivy(&index, pattern(11, "1.*", "size"), &schema)
+            .expect_err("numeric field must fail");
+        assert!(err.message().contains("indexed text field"));
+    }
+
+    #[test]
+    fn test_convert_facet_query() {
+        let mut schema_builder = Schema::builder();
+        let tags = schema_builder.add_facet_field("tags", FacetOptions::default());
+        schema_builder.add_text_field("path", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+
+        let facet = |path: &str, field: &str| -> FinalQuery {
+            let json = serde_json::json!({
+                "texts": [path],
+                "fields": [field],
+                "query": {"subqueries": [
+                    {"query_type": 13, "query_modifier": 0, "query": {"field_index": 0, "text_index": 0}}
+                ]}
+            });
+            serde_json::from_value(json).expect("Json was not parsed")
+        };
+
+        let query = convert_to_tantivy(&index, facet("/space/abc", "tags"), &schema)
+            .expect("conversion failed");
+        let expected = BooleanQuery::new(vec![(
+            TO::Must,
+            Box::new(TTermQuery::new(
+                Term::from_facet(tags, &Facet::from("/space/abc")),
+                IndexRecordOption::Basic,
+            )),
+        )]);
+        assert_eq!(format!("{query:#?}"), format!("{expected:#?}"));
+
+        let err = convert_to_tantivy(&index, facet("space", "tags"), &schema)
+            .expect_err("relative path must fail");
+        assert!(err.message().starts_with("Invalid facet path"));
+
+        let err = convert_to_tantivy(&index, facet("/space", "path"), &schema)
+            .expect_err("text field must fail");
+        assert!(err.message().contains("facet field"));
+    }
+
     fn make_terms(field: Field, words: Vec<&str>) -> Vec<Term> {
         words
             .into_iter()
@@ -1,10 +1,14 @@
 mod models;
 mod serializers;
 mod convert;
+mod pattern;
 
 pub use self::models::QueryType;
 pub use self::models::QueryModifier;
 pub use self::models::FinalQuery;
 pub use self::models::QueryElement;
 pub use self::models::GoQuery;
+pub use self::models::RangeValue;
+pub use self::models::RangeValueType;
 pub use self::convert::parse_query_from_json;
+pub use self::pattern::PatternQuery;
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::ops::Bound;
 
+// The variant names are the query types of the json format
+#[allow(clippy::enum_variant_names)]
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryType {
@@ -11,6 +14,12 @@ pub enum QueryType {
     EveryTermQuery,
     OneOfTermQuery,
     AllQuery,
+    RangeQuery,
+    FuzzyTermQuery,
+    FuzzyPrefixQuery,
+    RegexQuery,
+    WildcardQuery,
+    FacetQuery,
 }
 
 #[derive(Serialize, Debug, PartialEq)]
@@ -21,6 +30,7 @@ pub enum QueryModifier {
     MustNot,
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum GoQuery {
@@ -61,6 +71,60 @@ pub enum GoQuery {
     AllQuery {
         boost: f32,
     },
+    RangeQuery {
+        field_index: usize,
+        lower: Bound<RangeValue>,
+        upper: Bound<RangeValue>,
+        boost: f32,
+    },
+    FuzzyTermQuery {
+        field_index: usize,
+        text_index: usize,
+        distance: u8,
+        transposition: bool,
+        boost: f32,
+    },
+    FuzzyPrefixQuery {
+        field_index: usize,
+        text_index: usize,
+        distance: u8,
+        transposition: bool,
+        boost: f32,
+    },
+    RegexQuery {
+        field_index: usize,
+        text_index: usize,
+        boost: f32,
+    },
+    WildcardQuery {
+        field_index: usize,
+        text_index: usize,
+        boost: f32,
+    },
+    FacetQuery {
+        field_index: usize,
+        text_index: usize,
+        boost: f32,
+    },
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeValueType {
+    I64,
+    U64,
+    F64,
+    Date,
+}
+
+/// A typed range bound value. Dates are nanoseconds since the Unix epoch.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeValue {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Date(i64),
 }
 
 #[derive(Serialize, Debug, PartialEq)]
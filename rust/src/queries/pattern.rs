@@ -0,0 +1,146 @@
+use std::fmt;
+use std::sync::Arc;
+use regex_automata::dfa::dense::{self, BuildError, DFA};
+use regex_automata::dfa::{Automaton as _, StartKind};
+use regex_automata::nfa::thompson;
+use regex_automata::util::primitives::StateID;
+use regex_automata::util::start;
+use regex_automata::{Anchored, MatchKind};
+use tantivy::query::{AutomatonWeight, EnableScoring, Query, Weight};
+use tantivy::schema::Field;
+use tantivy_fst::Automaton;
+
+use crate::tantivy_util::TantivyGoError;
+
+// Bytes the compiled regex may take, as NFA and then as DFA. Determinizing is what gets slow
+const NFA_SIZE_LIMIT: usize = 10 << 20;
+const DFA_SIZE_LIMIT: usize = 2 << 20;
+
+/// Matches the terms of a field that the regex `pattern` matches entirely.
+/// Unlike `RegexQuery`, the automaton is built with explicit size limits,
+/// so that exceeding them can be told apart from an invalid pattern.
+#[derive(Clone)]
+pub struct PatternQuery {
+    pattern: String,
+    field: Field,
+    automaton: Arc<PatternAutomaton>,
+}
+
+impl PatternQuery {
+    pub fn new(pattern: &str, field: Field) -> Result<PatternQuery, TantivyGoError> {
+        let dfa = dense::Builder::new()
+            .configure(
+                dense::Config::new()
+                    .match_kind(MatchKind::All)
+                    .start_kind(StartKind::Anchored)
+                    .dfa_size_limit(Some(DFA_SIZE_LIMIT))
+                    .determinize_size_limit(Some(DFA_SIZE_LIMIT)),
+            )
+            .thompson(thompson::Config::new().nfa_size_limit(Some(NFA_SIZE_LIMIT)))
+            .build(pattern)
+            .map_err(|err| pattern_error(&err))?;
+        let start = dfa
+            .start_state(&start::Config::new().anchored(Anchored::Yes))
+            .map_err(|err| TantivyGoError::QueryParse(format!("Invalid regex pattern: {}", err)))?;
+        Ok(PatternQuery {
+            pattern: pattern.to_string(),
+            field,
+            automaton: Arc::new(PatternAutomaton { dfa, start }),
+        })
+    }
+}
+
+fn pattern_error(err: &BuildError) -> TantivyGoError {
+    let nfa_too_big = std::error::Error::source(err)
+        .and_then(|source| source.downcast_ref::<thompson::BuildError>())
+        .is_some_and(|source| source.size_limit().is_some());
+    if err.is_size_limit_exceeded() || nfa_too_big {
+        TantivyGoError::QueryParse(format!("Regex pattern is too expensive: {}", err))
+    } else {
+        TantivyGoError::QueryParse(format!("Invalid regex pattern: {}", err))
+    }
+}
+
+impl fmt::Debug for PatternQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PatternQuery")
+            .field("pattern", &self.pattern)
+            .field("field", &self.field)
+            .finish()
+    }
+}
+
+impl Query for PatternQuery {
+    fn weight(&self, _enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let weight: AutomatonWeight<PatternAutomaton> =
+            AutomatonWeight::new(self.field, self.automaton.clone());
+        Ok(Box::new(weight))
+    }
+}
+
+// Anchored DFA walked over the bytes of the terms, a term matches when its end is a match
+struct PatternAutomaton {
+    dfa: DFA<Vec<u32>>,
+    start: StateID,
+}
+
+impl Automaton for PatternAutomaton {
+    type State = StateID;
+
+    fn start(&self) -> StateID {
+        self.start
+    }
+
+    fn is_match(&self, state: &StateID) -> bool {
+        self.dfa.is_match_state(self.dfa.next_eoi_state(*state))
+    }
+
+    fn can_match(&self, state: &StateID) -> bool {
+        !self.dfa.is_dead_state(*state)
+    }
+
+    fn accept(&self, state: &StateID, byte: u8) -> StateID {
+        self.dfa.next_state(*state, byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::Count;
+    use tantivy::schema::{Schema, STRING};
+    use tantivy::{doc, Index};
+
+    #[test]
+    fn test_pattern_query_matches_whole_terms() {
+        let mut schema_builder = Schema::builder();
+        let path = schema_builder.add_text_field("path", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for value in ["ab", "abc", "xab", "ünïcode"] {
+            writer.add_document(doc!(path => value)).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let count = |pattern: &str| {
+            let query = PatternQuery::new(pattern, path).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("ab"), 1);
+        assert_eq!(count("ab.*"), 2);
+        assert_eq!(count(".*ab"), 2);
+        assert_eq!(count("a|ab|abc"), 2);
+        assert_eq!(count(".n.code"), 1);
+        assert_eq!(count("b"), 0);
+    }
+
+    #[test]
+    fn test_pattern_query_errors() {
+        let field = Field::from_field_id(0);
+        let err = PatternQuery::new("report(", field).unwrap_err();
+        assert!(err.message().starts_with("Invalid regex pattern"));
+        let err = PatternQuery::new("(a|b)*a(a|b){20}", field).unwrap_err();
+        assert!(err.message().starts_with("Regex pattern is too expensive"));
+    }
+}
@@ -1,8 +1,9 @@
 use crate::queries::models::{GoQuery, QueryElement, QueryModifier};
-use crate::queries::QueryType;
+use crate::queries::{QueryType, RangeValue, RangeValueType};
 use serde::de::Visitor;
 use serde::{de, Deserialize, Deserializer};
 use std::fmt;
+use std::ops::Bound;
 
 impl QueryType {
     fn from_u64(value: u64) -> Option<Self> {
@@ -15,11 +16,38 @@ impl QueryType {
             5 => Some(QueryType::EveryTermQuery),
             6 => Some(QueryType::OneOfTermQuery),
             7 => Some(QueryType::AllQuery),
+            8 => Some(QueryType::RangeQuery),
+            9 => Some(QueryType::FuzzyTermQuery),
+            10 => Some(QueryType::FuzzyPrefixQuery),
+            11 => Some(QueryType::RegexQuery),
+            12 => Some(QueryType::WildcardQuery),
+            13 => Some(QueryType::FacetQuery),
             _ => None,
         }
     }
 }
 
+impl RangeValueType {
+    fn from_u64(value: u64) -> Option<Self> {
+        match value {
+            0 => Some(RangeValueType::I64),
+            1 => Some(RangeValueType::U64),
+            2 => Some(RangeValueType::F64),
+            3 => Some(RangeValueType::Date),
+            _ => None,
+        }
+    }
+
+    fn parse_value(&self, value: &serde_json::Value) -> Option<RangeValue> {
+        match self {
+            RangeValueType::I64 => value.as_i64().map(RangeValue::I64),
+            RangeValueType::U64 => value.as_u64().map(RangeValue::U64),
+            RangeValueType::F64 => value.as_f64().map(RangeValue::F64),
+            RangeValueType::Date => value.as_i64().map(RangeValue::Date),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for QueryType {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -93,6 +121,59 @@ fn extract_query_data <'a, D : Deserializer<'a>>(
         .ok_or_else(|| de::Error::missing_field("query"))
 }
 
+// A missing or null bound is unbounded, `inclusive` defaults to true
+fn extract_range_bound<'a, D: Deserializer<'a>>(
+    query_data: &serde_json::Map<String, serde_json::Value>,
+    name: &'static str,
+    value_type: RangeValueType,
+) -> Result<Bound<RangeValue>, D::Error> {
+    let bound = match query_data.get(name) {
+        None | Some(serde_json::Value::Null) => return Ok(Bound::Unbounded),
+        Some(bound) => bound,
+    };
+    let value = bound
+        .get("value")
+        .ok_or_else(|| de::Error::missing_field("value"))?;
+    let value = value_type
+        .parse_value(value)
+        .ok_or_else(|| de::Error::custom(format!("Invalid {} value for {:?}", name, value_type)))?;
+    let inclusive = bound
+        .get("inclusive")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    if inclusive {
+        Ok(Bound::Included(value))
+    } else {
+        Ok(Bound::Excluded(value))
+    }
+}
+
+// Tantivy only ships Levenshtein automatons for distances 0 to 2
+const MAX_FUZZY_DISTANCE: u64 = 2;
+
+fn extract_fuzzy_options<'a, D: Deserializer<'a>>(
+    query_data: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(u8, bool), D::Error> {
+    let distance = query_data
+        .get("distance")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1);
+    if distance > MAX_FUZZY_DISTANCE {
+        return Err(de::Error::custom(format!(
+            "Invalid distance {}, must be between 0 and {}",
+            distance, MAX_FUZZY_DISTANCE
+        )));
+    }
+
+    let transposition = query_data
+        .get("transposition")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    Ok((distance as u8, transposition))
+}
+
 impl<'de> Deserialize<'de> for QueryElement {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -153,7 +234,8 @@ impl<'de> Deserialize<'de> for QueryElement {
                 })
             }
             QueryType::PhraseQuery | QueryType::PhrasePrefixQuery | QueryType::TermPrefixQuery
-            | QueryType::TermQuery | QueryType::EveryTermQuery | QueryType::OneOfTermQuery => {
+            | QueryType::TermQuery | QueryType::EveryTermQuery | QueryType::OneOfTermQuery
+            | QueryType::RegexQuery | QueryType::WildcardQuery | QueryType::FacetQuery => {
                 let query_data = extract_query_data::<D>(&map)?;
                 let (field_index, text_index, boost) = extract_query_indices_and_boost(query_data);
 
@@ -188,6 +270,21 @@ impl<'de> Deserialize<'de> for QueryElement {
                         text_index,
                         boost,
                     },
+                    QueryType::RegexQuery => GoQuery::RegexQuery {
+                        field_index,
+                        text_index,
+                        boost,
+                    },
+                    QueryType::WildcardQuery => GoQuery::WildcardQuery {
+                        field_index,
+                        text_index,
+                        boost,
+                    },
+                    QueryType::FacetQuery => GoQuery::FacetQuery {
+                        field_index,
+                        text_index,
+                        boost,
+                    },
                     _ => return Err(de::Error::custom("Unknown query type")),
                 })
             }
@@ -199,6 +296,45 @@ impl<'de> Deserialize<'de> for QueryElement {
                     .unwrap_or(1.0);
                 Some(GoQuery::AllQuery { boost })
             }
+            QueryType::FuzzyTermQuery | QueryType::FuzzyPrefixQuery => {
+                let query_data = extract_query_data::<D>(&map)?;
+                let (field_index, text_index, boost) = extract_query_indices_and_boost(query_data);
+                let (distance, transposition) = extract_fuzzy_options::<D>(query_data)?;
+
+                Some(match query_type {
+                    QueryType::FuzzyTermQuery => GoQuery::FuzzyTermQuery {
+                        field_index,
+                        text_index,
+                        distance,
+                        transposition,
+                        boost,
+                    },
+                    QueryType::FuzzyPrefixQuery => GoQuery::FuzzyPrefixQuery {
+                        field_index,
+                        text_index,
+                        distance,
+                        transposition,
+                        boost,
+                    },
+                    _ => return Err(de::Error::custom("Unknown query type")),
+                })
+            }
+            QueryType::RangeQuery => {
+                let query_data = extract_query_data::<D>(&map)?;
+                let (field_index, _, boost) = extract_query_indices_and_boost(query_data);
+                let value_type = query_data
+                    .get("value_type")
+                    .ok_or_else(|| serde::de::Error::missing_field("value_type"))?
+                    .as_u64()
+                    .and_then(RangeValueType::from_u64)
+                    .ok_or_else(|| serde::de::Error::custom("Invalid value_type"))?;
+                Some(GoQuery::RangeQuery {
+                    field_index,
+                    lower: extract_range_bound::<D>(query_data, "lower", value_type)?,
+                    upper: extract_range_bound::<D>(query_data, "upper", value_type)?,
+                    boost,
+                })
+            }
         };
 
         Ok(QueryElement { query, modifier })
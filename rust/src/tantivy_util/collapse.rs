@@ -0,0 +1,242 @@
+use std::collections::{HashMap, HashSet};
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::query::Query;
+use tantivy::schema::{Field, FieldType, Schema};
+use tantivy::{DocAddress, DocId, Score, Searcher, SegmentOrdinal, SegmentReader};
+
+use crate::tantivy_util::fast_field::FastColumn;
+use crate::tantivy_util::{
+    collect_top_docs, read_fast_field_values, SearchPage, TantivyGoError, TopDocsPage,
+};
+
+// Initial number of top docs fetched per requested group
+const COLLAPSE_WINDOW_FACTOR: usize = 4;
+
+/// Keeps only the best hit per value of a string or u64 fast field.
+/// With `with_siblings` the other hits of every returned group are counted,
+/// and up to `sibling_limit` of the best scoring ones are kept.
+#[derive(Clone)]
+pub struct Collapse {
+    pub field: Field,
+    pub with_siblings: bool,
+    pub sibling_limit: usize,
+}
+
+/// Hits collapsed into the returned hit of a group, the best scoring first.
+#[derive(Clone, Default)]
+pub struct CollapsedSiblings {
+    pub count: usize,
+    pub top_docs: Vec<(Score, DocAddress)>,
+}
+
+impl CollapsedSiblings {
+    fn push(&mut self, score: Score, doc_address: DocAddress, limit: usize) {
+        self.count += 1;
+        if limit == 0 {
+            return;
+        }
+        self.top_docs.push((score, doc_address));
+        if self.top_docs.len() >= limit * 2 {
+            self.truncate(limit);
+        }
+    }
+
+    fn merge(&mut self, other: CollapsedSiblings, limit: usize) {
+        self.count += other.count;
+        self.top_docs.extend(other.top_docs);
+        self.truncate(limit);
+    }
+
+    fn truncate(&mut self, limit: usize) {
+        self.top_docs
+            .sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        self.top_docs.truncate(limit);
+    }
+}
+
+/// Collects the top groups of `page`, one hit per value of the collapse field.
+/// Hits without a value are groups of their own. `offset` and `docs_limit` count groups,
+/// while `total_hits` still counts every matching document.
+pub(crate) fn collapse_top_docs(
+    searcher: &Searcher,
+    schema: &Schema,
+    query: &dyn Query,
+    page: &SearchPage,
+    collapse: &Collapse,
+) -> Result<TopDocsPage, TantivyGoError> {
+    let entry = schema.get_field_entry(collapse.field);
+    match entry.field_type() {
+        FieldType::Str(options) if options.is_fast() => {}
+        FieldType::U64(options) if options.is_fast() => {}
+        _ => {
+            return Err(TantivyGoError::InvalidArgument(format!(
+                "Field '{}' must be a string or u64 fast field to collapse by",
+                entry.name()
+            )))
+        }
+    }
+    if page.search_after.is_some() {
+        return Err(TantivyGoError::InvalidArgument(
+            "Search cursor can not be combined with collapse".to_string(),
+        ));
+    }
+
+    // The first hits of every group within the top `window` hits are the first hits of the
+    // groups overall, so the window grows until it holds enough groups or every hit.
+    let wanted = page.offset + page.docs_limit;
+    let mut window = wanted.saturating_mul(COLLAPSE_WINDOW_FACTOR);
+    let mut facets = page.facets.clone();
+    let mut counts = None;
+    let mut groups = loop {
+        let window_page = SearchPage {
+            docs_limit: window,
+            offset: 0,
+            sort: page.sort.clone(),
+            search_after: None,
+            facets: facets.take(),
+            collapse: None,
+        };
+        let top_docs = collect_top_docs(searcher, schema, query, &window_page)?;
+        let (total_hits, _) =
+            counts.get_or_insert((top_docs.total_hits, top_docs.facet_counts));
+
+        let doc_addresses: Vec<DocAddress> = top_docs.docs.iter().map(|(_, a)| *a).collect();
+        let keys = read_fast_field_values(searcher, schema, &[collapse.field], &doc_addresses)?
+            .remove(0)
+            .into_iter()
+            .map(|value| value.map(|value| value.to_string()));
+        let mut seen = HashSet::new();
+        let groups: Vec<((Score, DocAddress), Option<String>)> = top_docs
+            .docs
+            .iter()
+            .copied()
+            .zip(keys)
+            .filter(|(_, key)| key.as_ref().is_none_or(|key| seen.insert(key.clone())))
+            .collect();
+
+        if groups.len() >= wanted || top_docs.docs.len() < window || window >= *total_hits {
+            break groups;
+        }
+        window = window.saturating_mul(COLLAPSE_WINDOW_FACTOR);
+    };
+    let (total_hits, facet_counts) = counts.unwrap_or_default();
+
+    groups.drain(..page.offset.min(groups.len()));
+    groups.truncate(page.docs_limit);
+
+    let collapsed = if collapse.with_siblings {
+        let collector = SiblingCollector {
+            field: collapse.field,
+            schema: schema.clone(),
+            groups: groups
+                .iter()
+                .map(|((_, doc_address), key)| (key.clone(), *doc_address))
+                .collect(),
+            limit: collapse.sibling_limit,
+        };
+        let siblings = searcher
+            .search(query, &collector)
+            .map_err(|err| TantivyGoError::from_tantivy("Search err", &err))?;
+        Some(siblings)
+    } else {
+        None
+    };
+
+    Ok(TopDocsPage {
+        docs: groups.into_iter().map(|(hit, _)| hit).collect(),
+        cursor: None,
+        total_hits,
+        facet_counts,
+        collapsed,
+    })
+}
+
+// Counts the siblings of the returned groups, keeping the best scoring ones
+struct SiblingCollector {
+    field: Field,
+    schema: Schema,
+    // Key and returned hit of every group, hits without a key have no siblings
+    groups: Vec<(Option<String>, DocAddress)>,
+    limit: usize,
+}
+
+struct SiblingSegmentCollector {
+    column: FastColumn,
+    segment_ord: SegmentOrdinal,
+    // Group index by value id of the collapse field in this segment
+    group_by_value: HashMap<u64, usize>,
+    hits: Vec<DocAddress>,
+    siblings: Vec<CollapsedSiblings>,
+    limit: usize,
+}
+
+impl Collector for SiblingCollector {
+    type Fruit = Vec<CollapsedSiblings>;
+    type Child = SiblingSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_ord: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let column = FastColumn::open(segment, &self.schema, self.field)
+            .map_err(|err| tantivy::TantivyError::InternalError(err.to_string()))?;
+        let group_by_value = self
+            .groups
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, (key, _))| {
+                key.as_ref()
+                    .and_then(|key| column.find_value_id(key))
+                    .map(|value_id| (value_id, idx))
+            })
+            .collect();
+        Ok(SiblingSegmentCollector {
+            column,
+            segment_ord,
+            group_by_value,
+            hits: self.groups.iter().map(|(_, doc_address)| *doc_address).collect(),
+            siblings: vec![CollapsedSiblings::default(); self.groups.len()],
+            limit: self.limit,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.limit > 0
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> tantivy::Result<Self::Fruit> {
+        let mut merged = vec![CollapsedSiblings::default(); self.groups.len()];
+        for fruit in segment_fruits {
+            for (group, siblings) in merged.iter_mut().zip(fruit) {
+                group.merge(siblings, self.limit);
+            }
+        }
+        Ok(merged)
+    }
+}
+
+impl SegmentCollector for SiblingSegmentCollector {
+    type Fruit = Vec<CollapsedSiblings>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let Some(group) = self
+            .column
+            .value_id(doc)
+            .and_then(|value_id| self.group_by_value.get(&value_id))
+        else {
+            return;
+        };
+        let doc_address = DocAddress::new(self.segment_ord, doc);
+        if self.hits[*group] != doc_address {
+            self.siblings[*group].push(score, doc_address, self.limit);
+        }
+    }
+
+    fn harvest(mut self) -> Self::Fruit {
+        for siblings in &mut self.siblings {
+            siblings.truncate(self.limit);
+        }
+        self.siblings
+    }
+}
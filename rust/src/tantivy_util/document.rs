@@ -1,7 +1,7 @@
-use crate::tantivy_util::{extract_text_from_owned_value, Document, TantivyGoError};
+use crate::tantivy_util::{extract_json_from_value, Document, TantivyGoError};
 use std::borrow::Cow;
 use std::collections::HashMap;
-use tantivy::schema::{Field, Value};
+use tantivy::schema::Field;
 
 pub fn convert_document_to_json<'a>(
     doc: &mut Document,
@@ -18,21 +18,20 @@ pub fn convert_document_to_json<'a>(
     })?;
     result_json.insert(Cow::from("highlights"), highlights);
 
+    if let Some(collapsed) = &doc.collapsed {
+        let collapsed = serde_json::to_value(collapsed).map_err(|err| {
+            TantivyGoError::from_err("Failed to serialize collapsed hits", &err.to_string())
+        })?;
+        result_json.insert(Cow::from("collapsed"), collapsed);
+    }
+
     for (field_value, doc) in doc.tantivy_doc.field_values() {
         let key = match field_to_name.get(&field_value) {
             Some(value) => value,
             None => continue,
         };
 
-        let leaf = match doc.as_leaf() {
-            Some(value) => value,
-            None => continue,
-        };
-
-        let value = extract_text_from_owned_value(&leaf)?;
-        let json_value = serde_json::to_value(value).map_err(|err| {
-            TantivyGoError::from_err("Failed to serialize field value", &err.to_string())
-        })?;
+        let json_value = extract_json_from_value(&doc)?;
         result_json.insert(Cow::Borrowed(key), json_value);
     }
 
@@ -0,0 +1,82 @@
+use crate::tantivy_util::{schema_field, TantivyGoError};
+use std::collections::BTreeMap;
+use tantivy::collector::{FacetCollector, FacetCounts, FruitHandle, MultiCollector, MultiFruit};
+use tantivy::schema::{Facet, Field, FieldType, Schema};
+
+/// Counts of the direct children of every requested prefix, keyed by facet path.
+pub type FacetPrefixCounts = BTreeMap<String, BTreeMap<String, u64>>;
+
+/// Facet prefixes whose children are counted next to the top documents of a search.
+#[derive(Clone)]
+pub struct FacetRequest {
+    pub field: Field,
+    pub prefixes: Vec<Facet>,
+}
+
+impl FacetRequest {
+    pub fn new(
+        schema: &Schema,
+        field_id: u32,
+        prefixes: &[String],
+    ) -> Result<Self, TantivyGoError> {
+        Ok(FacetRequest {
+            field: schema_field(schema, field_id)?,
+            prefixes: prefixes
+                .iter()
+                .map(|prefix| parse_facet(prefix))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Parses a facet path like `/space/abc/type/note`.
+pub fn parse_facet(path: &str) -> Result<Facet, TantivyGoError> {
+    Facet::from_text(path)
+        .map_err(|err| TantivyGoError::InvalidArgument(format!("Invalid facet path: {}", err)))
+}
+
+/// Handles to the counts of every requested prefix in the fruit of a facet collector.
+pub(crate) struct FacetCountHandles(Vec<(Facet, FruitHandle<FacetCounts>)>);
+
+impl FacetCountHandles {
+    pub(crate) fn counts(self, fruit: &mut MultiFruit) -> FacetPrefixCounts {
+        self.0
+            .into_iter()
+            .map(|(prefix, handle)| {
+                let counts = handle.extract(fruit);
+                let children = counts
+                    .get(prefix.clone())
+                    .map(|(facet, count)| (facet.to_path_string(), count))
+                    .collect();
+                (prefix.to_path_string(), children)
+            })
+            .collect()
+    }
+}
+
+/// Builds a collector counting the children of the requested prefixes.
+/// Every prefix gets its own `FacetCollector`, so prefixes may be nested in each other.
+pub(crate) fn facet_collector(
+    schema: &Schema,
+    request: &FacetRequest,
+) -> Result<(MultiCollector<'static>, FacetCountHandles), TantivyGoError> {
+    let entry = schema.get_field_entry(request.field);
+    if !matches!(entry.field_type(), FieldType::Facet(_)) {
+        return Err(TantivyGoError::InvalidArgument(format!(
+            "Field '{}' is not a facet field",
+            entry.name()
+        )));
+    }
+
+    let mut collector = MultiCollector::new();
+    let handles = request
+        .prefixes
+        .iter()
+        .map(|prefix| {
+            let mut facet_collector = FacetCollector::for_field(entry.name());
+            facet_collector.add_facet(prefix.clone());
+            (prefix.clone(), collector.add_collector(facet_collector))
+        })
+        .collect();
+    Ok((collector, FacetCountHandles(handles)))
+}
@@ -1,23 +1,160 @@
 use std::collections::HashMap;
-use tantivy::columnar::StrColumn;
-use tantivy::schema::{Field, Schema};
-use tantivy::{DocAddress, Searcher};
+use std::fmt;
+use tantivy::columnar::{Column, StrColumn};
+use tantivy::schema::{Field, FieldType, Schema};
+use tantivy::{DateTime, DocAddress, DocId, Searcher, SegmentReader};
 
 use crate::tantivy_util::TantivyGoError;
 
-/// Reads fast field values for doc addresses, grouped by segment for efficiency.
+/// Type codes of fast field values, `FAST_FIELD_TYPE_NONE` for documents without a value.
+pub const FAST_FIELD_TYPE_NONE: u8 = 0;
+pub const FAST_FIELD_TYPE_STR: u8 = 1;
+pub const FAST_FIELD_TYPE_U64: u8 = 2;
+pub const FAST_FIELD_TYPE_I64: u8 = 3;
+pub const FAST_FIELD_TYPE_F64: u8 = 4;
+pub const FAST_FIELD_TYPE_BOOL: u8 = 5;
+pub const FAST_FIELD_TYPE_DATE: u8 = 6;
+
+/// First value of a fast field for a document. Dates are nanoseconds since the Unix epoch.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub enum FastFieldValue {
+    Str(String),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Bool(bool),
+    Date(i64),
+}
+
+impl FastFieldValue {
+    pub fn type_code(&self) -> u8 {
+        match self {
+            FastFieldValue::Str(_) => FAST_FIELD_TYPE_STR,
+            FastFieldValue::U64(_) => FAST_FIELD_TYPE_U64,
+            FastFieldValue::I64(_) => FAST_FIELD_TYPE_I64,
+            FastFieldValue::F64(_) => FAST_FIELD_TYPE_F64,
+            FastFieldValue::Bool(_) => FAST_FIELD_TYPE_BOOL,
+            FastFieldValue::Date(_) => FAST_FIELD_TYPE_DATE,
+        }
+    }
+}
+
+impl fmt::Display for FastFieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FastFieldValue::Str(value) => write!(f, "{}", value),
+            FastFieldValue::U64(value) => write!(f, "{}", value),
+            FastFieldValue::I64(value) => write!(f, "{}", value),
+            FastFieldValue::F64(value) => write!(f, "{}", value),
+            FastFieldValue::Bool(value) => write!(f, "{}", value),
+            FastFieldValue::Date(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Per-segment column of a fast field.
+pub(crate) enum FastColumn {
+    Str(StrColumn),
+    U64(Column<u64>),
+    I64(Column<i64>),
+    F64(Column<f64>),
+    Bool(Column<bool>),
+    Date(Column<DateTime>),
+    // A segment without any value for the field has no column at all
+    Missing,
+}
+
+impl FastColumn {
+    pub(crate) fn open(
+        segment_reader: &SegmentReader,
+        schema: &Schema,
+        field: Field,
+    ) -> Result<FastColumn, TantivyGoError> {
+        let entry = schema.get_field_entry(field);
+        let field_name = entry.name();
+        if !entry.is_fast() {
+            return Err(TantivyGoError::InvalidArgument(format!(
+                "Field '{}' is not a fast field",
+                field_name
+            )));
+        }
+        let fast_fields = segment_reader.fast_fields();
+        let column_err = |e: tantivy::TantivyError| {
+            TantivyGoError::from_tantivy(&format!("Failed to get fast field '{}'", field_name), &e)
+        };
+
+        let column = match entry.field_type() {
+            FieldType::Str(_) => fast_fields.str(field_name).map(|c| c.map(FastColumn::Str)),
+            FieldType::U64(_) => fast_fields.column_opt(field_name).map(|c| c.map(FastColumn::U64)),
+            FieldType::I64(_) => fast_fields.column_opt(field_name).map(|c| c.map(FastColumn::I64)),
+            FieldType::F64(_) => fast_fields.column_opt(field_name).map(|c| c.map(FastColumn::F64)),
+            FieldType::Bool(_) => fast_fields.column_opt(field_name).map(|c| c.map(FastColumn::Bool)),
+            FieldType::Date(_) => fast_fields.column_opt(field_name).map(|c| c.map(FastColumn::Date)),
+            _ => {
+                return Err(TantivyGoError::InvalidArgument(format!(
+                    "Field '{}' must be a str, numeric, bool or date fast field",
+                    field_name
+                )))
+            }
+        };
+        Ok(column.map_err(column_err)?.unwrap_or(FastColumn::Missing))
+    }
+
+    /// Identifier of the first value of `doc`, comparable within the segment.
+    /// Only string and u64 columns have one.
+    pub(crate) fn value_id(&self, doc: DocId) -> Option<u64> {
+        match self {
+            FastColumn::Str(column) => column.term_ords(doc).next(),
+            FastColumn::U64(column) => column.first(doc),
+            _ => None,
+        }
+    }
+
+    /// Identifier of `value` in the segment, none when no document of the segment has it.
+    pub(crate) fn find_value_id(&self, value: &str) -> Option<u64> {
+        match self {
+            FastColumn::Str(column) => column.dictionary().term_ord(value).ok().flatten(),
+            FastColumn::U64(_) => value.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn value(&self, doc: DocId, buffer: &mut String) -> Option<FastFieldValue> {
+        match self {
+            FastColumn::Str(column) => {
+                let ord = column.term_ords(doc).next()?;
+                buffer.clear();
+                if column.ord_to_str(ord, buffer).is_ok() && !buffer.is_empty() {
+                    Some(FastFieldValue::Str(buffer.clone()))
+                } else {
+                    None
+                }
+            }
+            FastColumn::U64(column) => column.first(doc).map(FastFieldValue::U64),
+            FastColumn::I64(column) => column.first(doc).map(FastFieldValue::I64),
+            FastColumn::F64(column) => column.first(doc).map(FastFieldValue::F64),
+            FastColumn::Bool(column) => column.first(doc).map(FastFieldValue::Bool),
+            FastColumn::Date(column) => column
+                .first(doc)
+                .map(|date| FastFieldValue::Date(date.into_timestamp_nanos())),
+            FastColumn::Missing => None,
+        }
+    }
+}
+
+/// Reads the values of several fast fields for doc addresses, grouped by segment for efficiency.
+/// Returns one column per field, in the order of `fields`.
 pub fn read_fast_field_values(
     searcher: &Searcher,
     schema: &Schema,
-    field: Field,
+    fields: &[Field],
     doc_addresses: &[DocAddress],
-) -> Result<Vec<Option<String>>, TantivyGoError> {
+) -> Result<Vec<Vec<Option<FastFieldValue>>>, TantivyGoError> {
+    let mut results = vec![vec![None; doc_addresses.len()]; fields.len()];
     if doc_addresses.is_empty() {
-        return Ok(vec![]);
+        return Ok(results);
     }
 
-    let field_name = schema.get_field_name(field);
-
     let mut segment_groups: HashMap<u32, Vec<(usize, u32)>> = HashMap::new();
     for (idx, addr) in doc_addresses.iter().enumerate() {
         segment_groups
@@ -26,29 +163,14 @@ pub fn read_fast_field_values(
             .push((idx, addr.doc_id));
     }
 
-    let mut results: Vec<Option<String>> = vec![None; doc_addresses.len()];
     let mut buffer = String::new();
 
     for (segment_ord, docs) in segment_groups {
         let segment_reader = searcher.segment_reader(segment_ord);
-        let fast_fields = segment_reader.fast_fields();
-
-        let str_column: StrColumn = fast_fields
-            .str(field_name)
-            .map_err(|e| TantivyGoError::from_err(
-                &format!("Failed to get fast field '{}'", field_name),
-                &e.to_string(),
-            ))?
-            .ok_or_else(|| TantivyGoError(
-                format!("Fast field '{}' not found in segment {}", field_name, segment_ord),
-            ))?;
-
-        for (result_idx, doc_id) in docs {
-            buffer.clear();
-            if let Some(ord) = str_column.term_ords(doc_id).next() {
-                if str_column.ord_to_str(ord, &mut buffer).is_ok() && !buffer.is_empty() {
-                    results[result_idx] = Some(buffer.clone());
-                }
+        for (field, values) in fields.iter().zip(results.iter_mut()) {
+            let column = FastColumn::open(segment_reader, schema, *field)?;
+            for (result_idx, doc_id) in &docs {
+                values[*result_idx] = column.value(*doc_id, &mut buffer);
             }
         }
     }
@@ -1,21 +1,24 @@
 use crate::tantivy_util::{Fragment, Highlight};
 use tantivy::query::Query;
-use tantivy::schema::Schema;
+use tantivy::schema::{FieldType, Schema};
 use tantivy::{Searcher, TantivyDocument, TantivyError};
 use tantivy::snippet::SnippetGenerator;
 
 pub fn find_highlights(
     with_highlights: bool,
     searcher: &Searcher,
-    query: &Box<dyn Query>,
+    query: &dyn Query,
     doc: &TantivyDocument,
     schema: Schema,
 ) -> Result<Vec<Highlight>, TantivyError> {
     let mut highlights: Vec<Highlight> = vec![];
     if with_highlights {
         for (field_value, _) in doc.field_values() {
+            if !matches!(schema.get_field_entry(field_value).field_type(), FieldType::Str(_)) {
+                continue;
+            }
             let snippet_generator = SnippetGenerator::create(
-                &searcher, query, field_value)?;
+                searcher, query, field_value)?;
             let snippet = snippet_generator.snippet_from_doc(doc);
             let highlighted: Vec<(usize, usize)> = snippet.highlighted()
                 .to_owned()
@@ -0,0 +1,217 @@
+use serde::Serialize;
+use tantivy::schema::{FieldType, Value};
+use tantivy::{Index, IndexWriter, Opstamp, TantivyDocument};
+
+use crate::tantivy_util::{schema_field, TantivyGoError};
+
+/// Indexing policy that fails the whole batch on the first failing document.
+/// Invalid documents are found before anything of the batch is staged. When the index writer
+/// fails midway the caller rolls back the staged operations, see `CheckedDocuments::stage`.
+pub const INDEXING_FAIL_FAST: u8 = 0;
+/// Indexing policy that skips failing documents and indexes the others.
+pub const INDEXING_BEST_EFFORT: u8 = 1;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum IndexingPolicy {
+    FailFast,
+    BestEffort,
+}
+
+impl IndexingPolicy {
+    pub fn from_const(policy: u8) -> Result<IndexingPolicy, TantivyGoError> {
+        match policy {
+            INDEXING_FAIL_FAST => Ok(IndexingPolicy::FailFast),
+            INDEXING_BEST_EFFORT => Ok(IndexingPolicy::BestEffort),
+            _ => Err(TantivyGoError::InvalidArgument(format!("Unknown indexing policy {}", policy))),
+        }
+    }
+}
+
+/// Document of a batch that could not be indexed, by its position in the batch.
+#[derive(Clone, Serialize)]
+pub struct DocumentFailure {
+    pub index: usize,
+    pub error: String,
+}
+
+impl From<DocumentFailure> for TantivyGoError {
+    fn from(failure: DocumentFailure) -> Self {
+        TantivyGoError::InvalidDocument(format!(
+            "Failed to add document {}: {}",
+            failure.index, failure.error
+        ))
+    }
+}
+
+/// Documents of a batch checked against the index, ready to be staged.
+pub struct CheckedDocuments {
+    docs: Vec<Option<TantivyDocument>>,
+    failures: Vec<DocumentFailure>,
+    policy: IndexingPolicy,
+}
+
+impl CheckedDocuments {
+    /// Checks every document, failing with the first invalid one with `IndexingPolicy::FailFast`.
+    pub fn check(
+        index: &Index,
+        docs: Vec<TantivyDocument>,
+        policy: IndexingPolicy,
+    ) -> Result<CheckedDocuments, TantivyGoError> {
+        let mut failures = Vec::new();
+        let docs = docs
+            .into_iter()
+            .enumerate()
+            .map(|(idx, doc)| match check_document(index, &doc) {
+                Ok(()) => Ok(Some(doc)),
+                Err(err) => {
+                    let failure = DocumentFailure {
+                        index: idx,
+                        error: err.to_string(),
+                    };
+                    if policy == IndexingPolicy::FailFast {
+                        return Err(failure.into());
+                    }
+                    failures.push(failure);
+                    Ok(None)
+                }
+            })
+            .collect::<Result<_, TantivyGoError>>()?;
+        Ok(CheckedDocuments {
+            docs,
+            failures,
+            policy,
+        })
+    }
+
+    /// Stages the valid documents, returning the opstamp of the last one and the failed documents.
+    /// With `IndexingPolicy::FailFast` a failure of the index writer stops staging, leaving the
+    /// documents staged before it for the caller to roll back.
+    pub fn stage(
+        self,
+        writer: &mut IndexWriter,
+    ) -> Result<(Opstamp, Vec<DocumentFailure>), TantivyGoError> {
+        let mut failures = self.failures;
+        let mut opstamp = 0;
+        for (idx, doc) in self.docs.into_iter().enumerate() {
+            let Some(doc) = doc else {
+                continue;
+            };
+            match writer.add_document(doc) {
+                Ok(doc_opstamp) => opstamp = doc_opstamp,
+                Err(err) if self.policy == IndexingPolicy::FailFast => {
+                    return Err(TantivyGoError::from_tantivy(
+                        &format!("Failed to add document {}", idx),
+                        &err,
+                    ));
+                }
+                Err(err) => failures.push(DocumentFailure {
+                    index: idx,
+                    error: err.to_string(),
+                }),
+            }
+        }
+        failures.sort_by_key(|failure| failure.index);
+        Ok((opstamp, failures))
+    }
+}
+
+/// Checks that the fields of a document are in the schema, that the values of its indexed fields
+/// match the field types and that the tokenizers of its text fields are registered. Either would otherwise fail the indexing
+/// threads and lose the whole batch.
+pub fn check_document(index: &Index, doc: &TantivyDocument) -> Result<(), TantivyGoError> {
+    let schema = index.schema();
+    for (field, value) in doc.field_values() {
+        // documents are built without the schema, their field ids are only checked here
+        let entry = schema.get_field_entry(schema_field(&schema, field.field_id())?);
+        if !entry.is_indexed() {
+            continue;
+        }
+        let (matches, tokenizer) = match entry.field_type() {
+            FieldType::Str(options) => (
+                true,
+                options
+                    .get_indexing_options()
+                    .map(|indexing| indexing.tokenizer()),
+            ),
+            FieldType::JsonObject(options) => (
+                true,
+                options
+                    .get_text_indexing_options()
+                    .map(|indexing| indexing.tokenizer()),
+            ),
+            FieldType::U64(_) => (value.as_u64().is_some(), None),
+            FieldType::I64(_) => (value.as_i64().is_some(), None),
+            FieldType::F64(_) => (value.as_f64().is_some(), None),
+            FieldType::Bool(_) => (value.as_bool().is_some(), None),
+            FieldType::Date(_) => (value.as_datetime().is_some(), None),
+            FieldType::Facet(_) => (value.as_facet().is_some(), None),
+            FieldType::Bytes(_) => (value.as_bytes().is_some(), None),
+            FieldType::IpAddr(_) => (value.as_ip_addr().is_some(), None),
+        };
+        if !matches {
+            return Err(TantivyGoError::InvalidDocument(format!(
+                "Expected a {:?} for field '{}'",
+                entry.field_type().value_type(),
+                entry.name()
+            )));
+        }
+        if let Some(tokenizer) = tokenizer {
+            if index.tokenizers().get(tokenizer).is_none() {
+                return Err(TantivyGoError::InvalidArgument(format!(
+                    "Tokenizer '{}' of field '{}' is not registered",
+                    tokenizer,
+                    entry.name()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::schema::{Schema, TextFieldIndexing, TextOptions};
+    use tantivy::tokenizer::{SimpleTokenizer, Tokenizer};
+
+    /// Kills the indexing thread on the text "boom".
+    #[derive(Clone, Default)]
+    struct PanickingTokenizer(SimpleTokenizer);
+
+    impl Tokenizer for PanickingTokenizer {
+        type TokenStream<'a> = <SimpleTokenizer as Tokenizer>::TokenStream<'a>;
+
+        fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+            if text == "boom" {
+                panic!("tokenizer failure");
+            }
+            self.0.token_stream(text)
+        }
+    }
+
+    #[test]
+    fn test_fail_fast_keeps_documents_staged_before_a_writer_failure() {
+        let mut builder = Schema::builder();
+        let indexing = TextFieldIndexing::default().set_tokenizer("panicking");
+        let body =
+            builder.add_text_field("body", TextOptions::default().set_indexing_options(indexing));
+        let index = Index::create_in_ram(builder.build());
+        index.tokenizers().register("panicking", PanickingTokenizer::default());
+        let mut writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+
+        // More documents than the indexing pipeline holds, so staging outlives the thread
+        let docs: Vec<TantivyDocument> = std::iter::once("boom")
+            .chain(std::iter::repeat_n("text", 20_000))
+            .map(|text| {
+                let mut doc = TantivyDocument::default();
+                doc.add_text(body, text);
+                doc
+            })
+            .collect();
+        let checked = CheckedDocuments::check(&index, docs, IndexingPolicy::FailFast).unwrap();
+        let err = checked.stage(&mut writer).err().expect("the writer should fail");
+
+        assert!(err.to_string().starts_with("Failed to add document"));
+        assert!(!err.to_string().starts_with("Failed to add document 0:"));
+    }
+}
@@ -7,23 +7,72 @@ mod util;
 mod scheme_builder;
 mod document;
 mod fast_field;
+mod sort;
+mod facet;
+mod collapse;
+mod upsert;
+mod indexing;
+mod options;
+mod writer;
+mod stats;
 
 pub use self::edge_ngram_tokenizer::EdgeNgramTokenizer;
 pub use self::models::Document;
 pub use self::models::TantivyContext;
+pub use self::models::ContextWriter;
+pub use self::models::PinnedSearcher;
 pub use self::models::Fragment;
 pub use self::models::Highlight;
+pub use self::models::CollapsedHits;
 pub use self::models::SearchResult;
+pub use self::models::FastFieldResult;
 pub use self::highlights::find_highlights;
 pub use self::document::convert_document_to_json;
 pub use self::scheme_builder::add_text_field;
+pub use self::scheme_builder::add_i64_field;
+pub use self::scheme_builder::add_u64_field;
+pub use self::scheme_builder::add_f64_field;
+pub use self::scheme_builder::add_bool_field;
+pub use self::scheme_builder::add_date_field;
+pub use self::scheme_builder::add_json_field;
+pub use self::scheme_builder::add_facet_field;
+pub use self::scheme_builder::DATE_PRECISION_SECONDS;
+pub use self::scheme_builder::DATE_PRECISION_MILLISECONDS;
+pub use self::scheme_builder::DATE_PRECISION_MICROSECONDS;
+pub use self::scheme_builder::DATE_PRECISION_NANOSECONDS;
 pub use self::tokenizer::register_edge_ngram_tokenizer;
 pub use self::tokenizer::register_simple_tokenizer;
 pub use self::tokenizer::register_jieba_tokenizer;
 pub use self::tokenizer::register_raw_tokenizer;
 pub use self::tokenizer::register_ngram_tokenizer;
-pub use self::util::extract_text_from_owned_value;
+pub use self::util::extract_json_from_value;
 pub use self::util::extract_terms;
+pub use self::util::schema_field;
 pub use self::util::TantivyGoError;
 pub use self::util::DOCUMENT_BUDGET_BYTES;
 pub use self::fast_field::read_fast_field_values;
+pub use self::fast_field::FastFieldValue;
+pub use self::fast_field::FAST_FIELD_TYPE_NONE;
+pub use self::facet::parse_facet;
+pub use self::facet::FacetPrefixCounts;
+pub use self::facet::FacetRequest;
+pub use self::collapse::Collapse;
+pub use self::collapse::CollapsedSiblings;
+pub use self::upsert::Upsert;
+pub use self::upsert::NO_VERSION;
+pub use self::indexing::check_document;
+pub use self::indexing::CheckedDocuments;
+pub use self::indexing::DocumentFailure;
+pub use self::indexing::IndexingPolicy;
+pub use self::stats::IndexStats;
+pub use self::writer::force_merge;
+pub use self::writer::purge_deletes;
+pub use self::options::ContextOptions;
+pub use self::options::MergePolicyOptions;
+pub use self::options::ReaderReload;
+pub use self::options::SearchOptions;
+pub use self::sort::collect_top_docs;
+pub use self::sort::SearchCursor;
+pub use self::sort::SearchPage;
+pub use self::sort::SortBy;
+pub use self::sort::TopDocsPage;
\ No newline at end of file
@@ -1,25 +1,205 @@
+use crate::config::{clear_index_dir, register_context, ContextRegistration, ContextSettings};
+use crate::tantivy_util::{
+    ContextOptions, FacetPrefixCounts, FastFieldValue, ReaderReload, TantivyGoError,
+};
 use serde::Serialize;
-use tantivy::{Index, IndexReader, IndexWriter, TantivyDocument};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tantivy::indexer::{LogMergePolicy, NoMergePolicy};
+use tantivy::{
+    Index, IndexReader, IndexWriter, Opstamp, ReloadPolicy, Searcher, TantivyDocument,
+};
 
+const CONTEXT_IS_POISONED: &str = "Context is poisoned by a panic in an earlier call";
+const WRITER_IS_CLOSED: &str = "Index writer is closed";
 
+/// Index with a shared reader side and an exclusive writer side,
+/// so that searches may run concurrently with each other and with a commit.
 pub struct TantivyContext {
     pub index: Index,
-    pub writer: IndexWriter,
-    reader: IndexReader,
+    writer: Mutex<ContextWriter>,
+    reader: Arc<IndexReader>,
+    reader_reload: ReaderReload,
+    registration: ContextRegistration,
+    poisoned: AtomicBool,
 }
 
 impl TantivyContext {
-    pub fn new(index: Index, writer: IndexWriter, reader: IndexReader) -> TantivyContext {
+    pub fn new(
+        index: Index,
+        writer: IndexWriter,
+        reader: IndexReader,
+        options: ContextOptions,
+        merge_policy: LogMergePolicy,
+        settings: ContextSettings,
+    ) -> TantivyContext {
+        writer.set_merge_policy(Box::new(merge_policy.clone()));
+        let reader_reload = options.reader_reload;
         TantivyContext {
+            writer: Mutex::new(ContextWriter {
+                index: index.clone(),
+                options,
+                index_writer: Some(writer),
+                merge_policy,
+                merging_paused: false,
+            }),
             index,
-            writer,
-            reader
+            reader: Arc::new(reader),
+            reader_reload,
+            registration: register_context(settings),
+            poisoned: AtomicBool::new(false),
         }
     }
 
-    pub fn reader(&mut self) -> &IndexReader {
-        let _ = self.reader.reload();
-        return &self.reader;
+    pub fn settings(&self) -> &ContextSettings {
+        self.registration.settings()
+    }
+
+    /// Fails every later call on the context after one of its calls panicked, since the index
+    /// may be left in any state. With `clear_on_panic` the index directory is removed.
+    pub fn poison(&self) {
+        if !self.poisoned.swap(true, Ordering::SeqCst) && self.settings().clear_on_panic {
+            clear_index_dir(self.settings());
+        }
+    }
+
+    pub fn check_poisoned(&self) -> Result<(), TantivyGoError> {
+        if self.poisoned.load(Ordering::SeqCst) {
+            return Err(TantivyGoError::ContextPoisoned(CONTEXT_IS_POISONED.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Searcher over the last loaded commit, it does not block nor is blocked by the writer.
+    /// With `ReaderReload::OnSearch` the last commit is loaded first, failing if it can not be.
+    pub fn searcher(&self) -> Result<Searcher, TantivyGoError> {
+        if self.reader_reload == ReaderReload::OnSearch {
+            self.reload_reader()?;
+        }
+        Ok(self.reader.searcher())
+    }
+
+    /// Searcher over the last commit on disk, opened aside so that searches keep the commit their
+    /// reload policy loaded. Hold the writer lock to keep another commit from landing meanwhile.
+    pub fn last_commit_searcher(&self) -> Result<Searcher, TantivyGoError> {
+        let reader: IndexReader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(|err| TantivyGoError::from_tantivy("Failed to create the reader", &err))?;
+        Ok(reader.searcher())
+    }
+
+    /// Loads the last commit, whatever the reload policy.
+    pub fn reload_reader(&self) -> Result<(), TantivyGoError> {
+        self.reader
+            .reload()
+            .map_err(|err| TantivyGoError::from_tantivy("Failed to reload reader", &err))
+    }
+
+    /// Locks the writer side until the guard is dropped.
+    /// The index writer is reopened first when a rollback failed to reopen it.
+    pub fn writer(&self) -> Result<MutexGuard<'_, ContextWriter>, TantivyGoError> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|err| TantivyGoError::from_err("Index writer is poisoned", &err.to_string()))?;
+        if writer.index_writer.is_none() {
+            writer.open()?;
+        }
+        Ok(writer)
+    }
+
+    /// Releases the context, returning its index writer, also when a writer call panicked.
+    pub fn into_writer(self) -> Option<IndexWriter> {
+        self.writer
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .index_writer
+    }
+}
+
+/// Index writer of a context with its merge policy, see `TantivyContext::writer`.
+/// The index writer is only missing between dropping and reopening it in a rollback.
+pub struct ContextWriter {
+    index: Index,
+    options: ContextOptions,
+    index_writer: Option<IndexWriter>,
+    merge_policy: LogMergePolicy,
+    merging_paused: bool,
+}
+
+impl ContextWriter {
+    /// Drops every operation staged since the last commit, returning the opstamp of that commit.
+    /// The index writer is dropped and reopened, as `IndexWriter::rollback` may keep operations
+    /// staged, see https://github.com/quickwit-oss/tantivy/issues/2666.
+    pub fn rollback(&mut self) -> Result<Opstamp, TantivyGoError> {
+        // The index lock is released by dropping the writer, before the new one takes it
+        drop(self.index_writer.take());
+        self.open()?;
+        Ok(self.commit_opstamp())
+    }
+
+    fn open(&mut self) -> Result<(), TantivyGoError> {
+        let writer = self.options.create_writer(&self.index)?;
+        if self.merging_paused {
+            writer.set_merge_policy(Box::new(NoMergePolicy));
+        } else {
+            writer.set_merge_policy(Box::new(self.merge_policy.clone()));
+        }
+        self.index_writer = Some(writer);
+        Ok(())
+    }
+
+    /// Replaces the merge policy, applied to the writer once merging is resumed when paused.
+    pub fn set_merge_policy(&mut self, merge_policy: LogMergePolicy) {
+        if !self.merging_paused {
+            self.index_writer().set_merge_policy(Box::new(merge_policy.clone()));
+        }
+        self.merge_policy = merge_policy;
+    }
+
+    /// Stops scheduling background merges. Running merges are not interrupted.
+    pub fn pause_merging(&mut self) {
+        self.index_writer().set_merge_policy(Box::new(NoMergePolicy));
+        self.merging_paused = true;
+    }
+
+    pub fn resume_merging(&mut self) {
+        self.index_writer().set_merge_policy(Box::new(self.merge_policy.clone()));
+        self.merging_paused = false;
+    }
+
+    fn index_writer(&self) -> &IndexWriter {
+        self.index_writer.as_ref().expect(WRITER_IS_CLOSED)
+    }
+}
+
+impl Deref for ContextWriter {
+    type Target = IndexWriter;
+
+    fn deref(&self) -> &IndexWriter {
+        self.index_writer()
+    }
+}
+
+impl DerefMut for ContextWriter {
+    fn deref_mut(&mut self) -> &mut IndexWriter {
+        self.index_writer.as_mut().expect(WRITER_IS_CLOSED)
+    }
+}
+
+/// Searcher handed to the host, so that several searches see the same commit.
+pub struct PinnedSearcher {
+    pub searcher: Searcher,
+}
+
+impl PinnedSearcher {
+    /// Identifies the set of segments the searcher sees.
+    pub fn generation(&self) -> u64 {
+        self.searcher.generation().generation_id()
     }
 }
 
@@ -28,6 +208,14 @@ pub struct Document {
     pub tantivy_doc: TantivyDocument,
     pub highlights: Vec<Highlight>,
     pub score: f32,
+    pub collapsed: Option<CollapsedHits>,
+}
+
+/// Hits collapsed into a search result document, with the highlights of the best scoring ones.
+#[derive(Clone, Serialize)]
+pub struct CollapsedHits {
+    pub count: usize,
+    pub highlights: Vec<Highlight>,
 }
 
 #[derive(Clone, Serialize)]
@@ -45,4 +233,15 @@ pub struct Fragment {
 pub struct SearchResult {
     pub documents: Vec<Document>,
     pub size: usize,
+    pub total_hits: usize,
+    pub cursor: Option<String>,
+    pub facet_counts: Option<FacetPrefixCounts>,
+}
+
+/// Fast field values of the top documents of a search, one column per requested field.
+pub struct FastFieldResult {
+    pub scores: Vec<f32>,
+    pub columns: Vec<Vec<Option<FastFieldValue>>>,
+    pub cursor: Option<String>,
+    pub total_hits: usize,
 }
\ No newline at end of file
@@ -0,0 +1,224 @@
+use serde::Deserialize;
+use tantivy::indexer::LogMergePolicy;
+use tantivy::schema::Schema;
+use tantivy::{Index, IndexWriter, ReloadPolicy};
+
+use crate::config::{lib_defaults, ContextSettings};
+use crate::tantivy_util::{
+    schema_field, Collapse, FacetRequest, SearchCursor, SearchPage, SortBy, TantivyGoError,
+    DOCUMENT_BUDGET_BYTES,
+};
+
+/// Options of the index writer and reader of a context, given as json.
+/// Missing options keep the defaults.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContextOptions {
+    /// Memory budget shared by all indexing threads, `DOCUMENT_BUDGET_BYTES` by default.
+    pub memory_budget_bytes: Option<usize>,
+    /// Number of indexing threads, picked by tantivy from the number of cpus by default.
+    pub num_threads: Option<usize>,
+    pub merge_policy: Option<MergePolicyOptions>,
+    pub reader_reload: ReaderReload,
+    /// The `init_lib` default when not set.
+    pub utf8_lenient: Option<bool>,
+    /// The `init_lib` default when not set.
+    pub clear_on_panic: Option<bool>,
+}
+
+/// When the reader of a context picks up new commits.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReaderReload {
+    /// Before every search.
+    #[default]
+    OnSearch,
+    /// Shortly after every commit, watching the index meta.
+    OnCommitWithDelay,
+    /// Only when the reader is explicitly reloaded.
+    Manual,
+}
+
+impl ReaderReload {
+    pub fn reload_policy(self) -> ReloadPolicy {
+        match self {
+            ReaderReload::OnCommitWithDelay => ReloadPolicy::OnCommitWithDelay,
+            ReaderReload::OnSearch | ReaderReload::Manual => ReloadPolicy::Manual,
+        }
+    }
+}
+
+impl ContextOptions {
+    pub fn parse(json: &str) -> Result<ContextOptions, TantivyGoError> {
+        serde_json::from_str(json)
+            .map_err(|err| TantivyGoError::InvalidArgument(format!("Invalid context options: {}", err)))
+    }
+
+    /// Settings of the context at `fts_path`, completed by the `init_lib` defaults.
+    pub fn settings(&self, fts_path: String) -> ContextSettings {
+        let defaults = lib_defaults();
+        ContextSettings {
+            fts_path,
+            utf8_lenient: self.utf8_lenient.unwrap_or(defaults.utf8_lenient),
+            clear_on_panic: self.clear_on_panic.unwrap_or(defaults.clear_on_panic),
+        }
+    }
+
+    /// Creates the writer of the index, its merge policy is set by `TantivyContext::new`.
+    pub fn create_writer(&self, index: &Index) -> Result<IndexWriter, TantivyGoError> {
+        let memory_budget = self.memory_budget_bytes.unwrap_or(DOCUMENT_BUDGET_BYTES);
+        // tantivy panics on zero threads
+        if self.num_threads == Some(0) {
+            return Err(TantivyGoError::InvalidArgument(
+                "Number of indexing threads must be at least 1".to_string(),
+            ));
+        }
+        match self.num_threads {
+            Some(num_threads) => index.writer_with_num_threads(num_threads, memory_budget),
+            None => index.writer(memory_budget),
+        }
+        .map_err(|err| TantivyGoError::from_tantivy("Failed to create index writer", &err))
+    }
+
+    /// The merge policy of the writer, the tantivy default when none is set.
+    pub fn log_merge_policy(&self) -> Result<LogMergePolicy, TantivyGoError> {
+        match &self.merge_policy {
+            Some(merge_policy) => merge_policy.to_log_merge_policy(),
+            None => Ok(LogMergePolicy::default()),
+        }
+    }
+}
+
+/// Parameters of the `LogMergePolicy`, given as json. Missing parameters keep tantivy defaults.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MergePolicyOptions {
+    pub min_num_segments: Option<usize>,
+    pub max_docs_before_merge: Option<usize>,
+    pub min_layer_size: Option<u32>,
+    pub level_log_size: Option<f64>,
+    pub del_docs_ratio_before_merge: Option<f32>,
+}
+
+impl MergePolicyOptions {
+    pub fn parse(json: &str) -> Result<MergePolicyOptions, TantivyGoError> {
+        serde_json::from_str(json)
+            .map_err(|err| TantivyGoError::InvalidArgument(format!("Invalid merge policy: {}", err)))
+    }
+
+    pub fn to_log_merge_policy(&self) -> Result<LogMergePolicy, TantivyGoError> {
+        let mut policy = LogMergePolicy::default();
+        if let Some(min_num_segments) = self.min_num_segments {
+            policy.set_min_num_segments(min_num_segments);
+        }
+        if let Some(max_docs_before_merge) = self.max_docs_before_merge {
+            policy.set_max_docs_before_merge(max_docs_before_merge);
+        }
+        if let Some(min_layer_size) = self.min_layer_size {
+            policy.set_min_layer_size(min_layer_size);
+        }
+        if let Some(level_log_size) = self.level_log_size {
+            if level_log_size <= 0.0 {
+                return Err(TantivyGoError::InvalidArgument(format!(
+                    "Level log size {} must be positive",
+                    level_log_size
+                )));
+            }
+            policy.set_level_log_size(level_log_size);
+        }
+        if let Some(ratio) = self.del_docs_ratio_before_merge {
+            // tantivy panics on a ratio out of (0, 1]
+            if ratio <= 0.0 || ratio > 1.0 {
+                return Err(TantivyGoError::InvalidArgument(format!(
+                    "Deleted docs ratio {} must be in (0, 1]",
+                    ratio
+                )));
+            }
+            policy.set_del_docs_ratio_before_merge(ratio);
+        }
+        Ok(policy)
+    }
+}
+
+/// Options of a search, given as json. Missing options keep the defaults.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchOptions {
+    pub docs_limit: usize,
+    pub offset: usize,
+    /// Fields to sort by in order, by descending score when empty.
+    pub sort: Vec<SortOptions>,
+    /// Cursor of the last document of the previous page.
+    pub search_after: Option<String>,
+    pub facets: Option<FacetOptions>,
+    pub collapse: Option<CollapseOptions>,
+    pub with_highlights: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SortOptions {
+    /// A fast field id or `SORT_BY_SCORE`.
+    pub field_id: u32,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// Facet prefixes whose children are counted over all matches.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FacetOptions {
+    pub field_id: u32,
+    pub prefixes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CollapseOptions {
+    pub field_id: u32,
+    #[serde(default)]
+    pub with_siblings: bool,
+    #[serde(default)]
+    pub sibling_limit: usize,
+}
+
+impl SearchOptions {
+    pub fn parse(json: &str) -> Result<SearchOptions, TantivyGoError> {
+        serde_json::from_str(json)
+            .map_err(|err| TantivyGoError::InvalidArgument(format!("Invalid search options: {}", err)))
+    }
+
+    /// The page to search, with the fields resolved against `schema`.
+    pub fn page(&self, schema: &Schema) -> Result<SearchPage, TantivyGoError> {
+        let search_after = match self.search_after.as_deref() {
+            None | Some("") => None,
+            Some(cursor) => Some(SearchCursor::parse(cursor)?),
+        };
+        let facets = match &self.facets {
+            Some(facets) if !facets.prefixes.is_empty() => {
+                Some(FacetRequest::new(schema, facets.field_id, &facets.prefixes)?)
+            }
+            _ => None,
+        };
+        let collapse = match &self.collapse {
+            Some(collapse) => Some(Collapse {
+                field: schema_field(schema, collapse.field_id)?,
+                with_siblings: collapse.with_siblings,
+                sibling_limit: collapse.sibling_limit,
+            }),
+            None => None,
+        };
+        Ok(SearchPage {
+            docs_limit: self.docs_limit,
+            offset: self.offset,
+            sort: self
+                .sort
+                .iter()
+                .map(|sort| SortBy::new(schema, sort.field_id, sort.descending))
+                .collect::<Result<_, _>>()?,
+            search_after,
+            facets,
+            collapse,
+        })
+    }
+}
@@ -1,4 +1,10 @@
-use tantivy::schema::{FAST, IndexRecordOption, SchemaBuilder, STORED, STRING, TEXT, TextFieldIndexing};
+use tantivy::schema::{DateOptions, DateTimePrecision, FacetOptions, FAST, IndexRecordOption, JsonObjectOptions, NumericOptions, SchemaBuilder, STORED, STRING, TEXT, TextFieldIndexing};
+
+/// Precision of the indexed and fast values of a date field, the tantivy default.
+pub const DATE_PRECISION_SECONDS: u8 = 0;
+pub const DATE_PRECISION_MILLISECONDS: u8 = 1;
+pub const DATE_PRECISION_MICROSECONDS: u8 = 2;
+pub const DATE_PRECISION_NANOSECONDS: u8 = 3;
 
 pub fn add_text_field(
     stored: bool,
@@ -19,3 +25,92 @@ pub fn add_text_field(
     );
     builder.add_text_field(field_name, text_options).field_id()
 }
+
+fn numeric_options(stored: bool, indexed: bool, is_fast: bool) -> NumericOptions {
+    let mut numeric_options = NumericOptions::default();
+    numeric_options = if stored { numeric_options.set_stored() } else { numeric_options };
+    numeric_options = if indexed { numeric_options.set_indexed() } else { numeric_options };
+    numeric_options = if is_fast { numeric_options.set_fast() } else { numeric_options };
+    numeric_options
+}
+
+pub fn add_i64_field(
+    stored: bool,
+    indexed: bool,
+    is_fast: bool,
+    builder: &mut SchemaBuilder,
+    field_name: &str,
+) -> u32 {
+    builder.add_i64_field(field_name, numeric_options(stored, indexed, is_fast)).field_id()
+}
+
+pub fn add_u64_field(
+    stored: bool,
+    indexed: bool,
+    is_fast: bool,
+    builder: &mut SchemaBuilder,
+    field_name: &str,
+) -> u32 {
+    builder.add_u64_field(field_name, numeric_options(stored, indexed, is_fast)).field_id()
+}
+
+pub fn add_f64_field(
+    stored: bool,
+    indexed: bool,
+    is_fast: bool,
+    builder: &mut SchemaBuilder,
+    field_name: &str,
+) -> u32 {
+    builder.add_f64_field(field_name, numeric_options(stored, indexed, is_fast)).field_id()
+}
+
+pub fn add_bool_field(
+    stored: bool,
+    indexed: bool,
+    is_fast: bool,
+    builder: &mut SchemaBuilder,
+    field_name: &str,
+) -> u32 {
+    builder.add_bool_field(field_name, numeric_options(stored, indexed, is_fast)).field_id()
+}
+
+pub fn add_date_field(
+    stored: bool,
+    indexed: bool,
+    is_fast: bool,
+    precision: DateTimePrecision,
+    builder: &mut SchemaBuilder,
+    field_name: &str,
+) -> u32 {
+    let mut date_options = DateOptions::default().set_precision(precision);
+    date_options = if stored { date_options.set_stored() } else { date_options };
+    date_options = if indexed { date_options.set_indexed() } else { date_options };
+    date_options = if is_fast { date_options.set_fast() } else { date_options };
+    builder.add_date_field(field_name, date_options).field_id()
+}
+
+pub fn add_json_field(
+    stored: bool,
+    is_fast: bool,
+    expand_dots: bool,
+    builder: &mut SchemaBuilder,
+    tokenizer_name: &str,
+    field_name: &str,
+    index_record_option: IndexRecordOption,
+) -> u32 {
+    let mut json_options = JsonObjectOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(tokenizer_name)
+            .set_index_option(index_record_option)
+    );
+    json_options = if stored { json_options.set_stored() } else { json_options };
+    json_options = if is_fast { json_options.set_fast(None) } else { json_options };
+    json_options = if expand_dots { json_options.set_expand_dots_enabled() } else { json_options };
+    builder.add_json_field(field_name, json_options).field_id()
+}
+
+pub fn add_facet_field(stored: bool, builder: &mut SchemaBuilder, field_name: &str) -> u32 {
+    let mut facet_options = FacetOptions::default();
+    facet_options = if stored { facet_options.set_stored() } else { facet_options };
+    builder.add_facet_field(field_name, facet_options).field_id()
+}
@@ -0,0 +1,441 @@
+use std::cmp::Ordering;
+use std::fmt;
+use tantivy::collector::{
+    Collector, Count, MultiCollector, MultiFruit, ScoreSegmentTweaker, ScoreTweaker,
+    SegmentCollector, TopDocs,
+};
+use tantivy::columnar::Column;
+use tantivy::query::Query;
+use tantivy::schema::{Field, FieldType, Schema};
+use tantivy::{
+    f64_to_u64, i64_to_u64, DocAddress, DocId, Score, Searcher, SegmentOrdinal, SegmentReader,
+};
+
+use crate::tantivy_util::collapse::collapse_top_docs;
+use crate::tantivy_util::facet::facet_collector;
+use crate::tantivy_util::{
+    schema_field, Collapse, CollapsedSiblings, FacetPrefixCounts, FacetRequest, TantivyGoError,
+};
+
+/// Sort field id that stands for the relevance score instead of a fast field.
+pub const SORT_BY_SCORE: u32 = u32::MAX;
+
+#[derive(Clone, Copy, Debug)]
+pub enum SortField {
+    Score,
+    Fast(Field),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SortBy {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl SortBy {
+    pub fn new(
+        schema: &Schema,
+        field_id: u32,
+        descending: bool,
+    ) -> Result<SortBy, TantivyGoError> {
+        let field = if field_id == SORT_BY_SCORE {
+            SortField::Score
+        } else {
+            SortField::Fast(schema_field(schema, field_id)?)
+        };
+        Ok(SortBy { field, descending })
+    }
+}
+
+/// Sort keys of a document, mapped so that the greater key comes first.
+/// Documents without a value for a key are placed last whatever the order.
+#[derive(Clone)]
+struct SortKey {
+    values: Vec<Option<u64>>,
+    score: Score,
+}
+
+impl PartialEq for SortKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
+
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.values.partial_cmp(&other.values)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum FastType {
+    I64,
+    U64,
+    F64,
+    Bool,
+    Date,
+}
+
+struct SortKeyTweaker {
+    keys: Vec<(Option<(String, FastType)>, bool)>,
+}
+
+enum SegmentColumn {
+    Score,
+    I64(Column<i64>),
+    U64(Column<u64>),
+    F64(Column<f64>),
+    Bool(Column<bool>),
+    Date(Column<tantivy::DateTime>),
+    Missing,
+}
+
+impl SegmentColumn {
+    fn value(&self, doc: DocId, score: Score) -> Option<u64> {
+        match self {
+            SegmentColumn::Score => Some(f64_to_u64(score as f64)),
+            SegmentColumn::I64(column) => column.first(doc).map(i64_to_u64),
+            SegmentColumn::U64(column) => column.first(doc),
+            SegmentColumn::F64(column) => column.first(doc).map(f64_to_u64),
+            SegmentColumn::Bool(column) => column.first(doc).map(u64::from),
+            SegmentColumn::Date(column) => column
+                .first(doc)
+                .map(|date| i64_to_u64(date.into_timestamp_nanos())),
+            SegmentColumn::Missing => None,
+        }
+    }
+}
+
+struct SortKeySegmentTweaker {
+    columns: Vec<(SegmentColumn, bool)>,
+}
+
+impl ScoreTweaker<SortKey> for SortKeyTweaker {
+    type Child = SortKeySegmentTweaker;
+
+    fn segment_tweaker(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        let fast_fields = segment_reader.fast_fields();
+        let mut columns = Vec::with_capacity(self.keys.len());
+        for (key, descending) in &self.keys {
+            // A segment without any value for the field has no column at all
+            let column = match key {
+                None => Some(SegmentColumn::Score),
+                Some((name, FastType::I64)) => fast_fields.column_opt(name)?.map(SegmentColumn::I64),
+                Some((name, FastType::U64)) => fast_fields.column_opt(name)?.map(SegmentColumn::U64),
+                Some((name, FastType::F64)) => fast_fields.column_opt(name)?.map(SegmentColumn::F64),
+                Some((name, FastType::Bool)) => fast_fields.column_opt(name)?.map(SegmentColumn::Bool),
+                Some((name, FastType::Date)) => fast_fields.column_opt(name)?.map(SegmentColumn::Date),
+            }
+            .unwrap_or(SegmentColumn::Missing);
+            columns.push((column, *descending));
+        }
+        Ok(SortKeySegmentTweaker { columns })
+    }
+}
+
+impl ScoreSegmentTweaker<SortKey> for SortKeySegmentTweaker {
+    fn score(&mut self, doc: DocId, score: Score) -> SortKey {
+        let values = self
+            .columns
+            .iter()
+            .map(|(column, descending)| {
+                column.value(doc, score).map(|value| {
+                    if *descending {
+                        value
+                    } else {
+                        u64::MAX - value
+                    }
+                })
+            })
+            .collect();
+        SortKey { values, score }
+    }
+}
+
+fn sort_key_tweaker(schema: &Schema, sort: &[SortBy]) -> Result<SortKeyTweaker, TantivyGoError> {
+    let mut keys = Vec::with_capacity(sort.len());
+    for sort_by in sort {
+        let key = match sort_by.field {
+            SortField::Score => None,
+            SortField::Fast(field) => {
+                let entry = schema.get_field_entry(field);
+                let fast_type = match entry.field_type() {
+                    FieldType::I64(options) if options.is_fast() => FastType::I64,
+                    FieldType::U64(options) if options.is_fast() => FastType::U64,
+                    FieldType::F64(options) if options.is_fast() => FastType::F64,
+                    FieldType::Bool(options) if options.is_fast() => FastType::Bool,
+                    FieldType::Date(options) if options.is_fast() => FastType::Date,
+                    _ => {
+                        return Err(TantivyGoError::InvalidArgument(format!(
+                            "Field '{}' must be a numeric, bool or date fast field to sort by",
+                            entry.name()
+                        )))
+                    }
+                };
+                Some((entry.name().to_string(), fast_type))
+            }
+        };
+        keys.push((key, sort_by.descending));
+    }
+    Ok(SortKeyTweaker { keys })
+}
+
+/// Position of a document in a sorted result list, used to fetch the documents that follow it.
+/// It is only meaningful for the same sort and the same searcher generation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchCursor {
+    key: Vec<Option<u64>>,
+    doc_address: DocAddress,
+}
+
+impl fmt::Display for SearchCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.doc_address.segment_ord, self.doc_address.doc_id)?;
+        for value in &self.key {
+            match value {
+                Some(value) => write!(f, ".{}", value)?,
+                None => write!(f, ".-")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SearchCursor {
+    pub fn parse(cursor: &str) -> Result<SearchCursor, TantivyGoError> {
+        let invalid = || TantivyGoError::InvalidArgument(format!("Invalid search cursor '{}'", cursor));
+        let mut parts = cursor.split('.');
+        let segment_ord = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        let doc_id = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        let key = parts
+            .map(|part| match part {
+                "-" => Ok(None),
+                _ => part.parse().map(Some).map_err(|_| invalid()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SearchCursor {
+            key,
+            doc_address: DocAddress::new(segment_ord, doc_id),
+        })
+    }
+
+    // Documents come in descending key order, ties in ascending address order
+    fn is_before(&self, key: &[Option<u64>], doc_address: DocAddress) -> bool {
+        match key.partial_cmp(self.key.as_slice()) {
+            Some(Ordering::Less) => true,
+            Some(Ordering::Equal) => doc_address > self.doc_address,
+            _ => false,
+        }
+    }
+}
+
+fn compare_top_docs(left: &(SortKey, DocAddress), right: &(SortKey, DocAddress)) -> Ordering {
+    right
+        .0
+        .partial_cmp(&left.0)
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| left.1.cmp(&right.1))
+}
+
+/// Keeps the best `limit` documents that come after a cursor.
+struct AfterCursorCollector {
+    tweaker: SortKeyTweaker,
+    cursor: SearchCursor,
+    limit: usize,
+}
+
+struct AfterCursorSegmentCollector {
+    tweaker: SortKeySegmentTweaker,
+    segment_ord: SegmentOrdinal,
+    cursor: SearchCursor,
+    limit: usize,
+    top_docs: Vec<(SortKey, DocAddress)>,
+}
+
+impl AfterCursorSegmentCollector {
+    fn truncate(&mut self) {
+        self.top_docs.sort_by(compare_top_docs);
+        self.top_docs.truncate(self.limit);
+    }
+}
+
+impl Collector for AfterCursorCollector {
+    type Fruit = Vec<(SortKey, DocAddress)>;
+    type Child = AfterCursorSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        Ok(AfterCursorSegmentCollector {
+            tweaker: self.tweaker.segment_tweaker(segment)?,
+            segment_ord: segment_local_id,
+            cursor: self.cursor.clone(),
+            limit: self.limit,
+            top_docs: Vec::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(SortKey, DocAddress)>>,
+    ) -> tantivy::Result<Self::Fruit> {
+        let mut top_docs: Vec<_> = segment_fruits.into_iter().flatten().collect();
+        top_docs.sort_by(compare_top_docs);
+        top_docs.truncate(self.limit);
+        Ok(top_docs)
+    }
+}
+
+impl SegmentCollector for AfterCursorSegmentCollector {
+    type Fruit = Vec<(SortKey, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let key = self.tweaker.score(doc, score);
+        let doc_address = DocAddress::new(self.segment_ord, doc);
+        if self.cursor.is_before(&key.values, doc_address) {
+            self.top_docs.push((key, doc_address));
+            if self.top_docs.len() >= self.limit.saturating_mul(2) {
+                self.truncate();
+            }
+        }
+    }
+
+    fn harvest(mut self) -> Self::Fruit {
+        self.truncate();
+        self.top_docs
+    }
+}
+
+/// Which documents of the result list a search returns.
+/// `docs_limit` documents are taken after skipping `offset` documents, which are counted from
+/// `search_after` when a cursor is given. An empty `sort` orders by descending score.
+/// With `facets` the children of the requested facet prefixes are counted over all matches.
+/// With `collapse` only the best hit per value of a fast field is returned.
+pub struct SearchPage {
+    pub docs_limit: usize,
+    pub offset: usize,
+    pub sort: Vec<SortBy>,
+    pub search_after: Option<SearchCursor>,
+    pub facets: Option<FacetRequest>,
+    pub collapse: Option<Collapse>,
+}
+
+/// Top documents of a search page, with the cursor of the last one,
+/// the number of documents matching the query and the requested facet counts.
+/// When collapsing with siblings, `collapsed` holds the siblings of every document.
+pub struct TopDocsPage {
+    pub docs: Vec<(Score, DocAddress)>,
+    pub cursor: Option<SearchCursor>,
+    pub total_hits: usize,
+    pub facet_counts: Option<FacetPrefixCounts>,
+    pub collapsed: Option<Vec<CollapsedSiblings>>,
+}
+
+// Runs `collector` next to the hit count and the optional facet collector in a single pass
+fn search_with_counts<C: Collector>(
+    searcher: &Searcher,
+    query: &dyn Query,
+    facets: Option<MultiCollector>,
+    collector: C,
+) -> Result<(usize, Option<MultiFruit>, C::Fruit), TantivyGoError> {
+    searcher
+        .search(query, &(Count, facets, collector))
+        .map_err(|err| TantivyGoError::from_tantivy("Search err", &err))
+}
+
+/// Collects the documents of `page`. Ties are broken by ascending doc address,
+/// so the order is stable between calls.
+pub fn collect_top_docs(
+    searcher: &Searcher,
+    schema: &Schema,
+    query: &dyn Query,
+    page: &SearchPage,
+) -> Result<TopDocsPage, TantivyGoError> {
+    // tantivy panics on a zero limit
+    if page.docs_limit == 0 {
+        return Err(TantivyGoError::InvalidArgument(
+            "Docs limit must be at least 1".to_string(),
+        ));
+    }
+    if page.docs_limit.checked_add(page.offset).is_none() {
+        return Err(TantivyGoError::InvalidArgument(format!(
+            "Docs limit {} and offset {} are too large",
+            page.docs_limit, page.offset
+        )));
+    }
+    if let Some(collapse) = &page.collapse {
+        return collapse_top_docs(searcher, schema, query, page, collapse);
+    }
+
+    let by_score = [SortBy { field: SortField::Score, descending: true }];
+    let sort = if page.sort.is_empty() { &by_score[..] } else { &page.sort[..] };
+    // tantivy allocates for the whole limit upfront, while no page holds more than every document
+    let max_docs = (searcher.num_docs() as usize).max(1);
+    let (docs_limit, offset) = (page.docs_limit.min(max_docs), page.offset.min(max_docs));
+    let (facets, facet_handles) = match &page.facets {
+        Some(request) => {
+            let (collector, handles) = facet_collector(schema, request)?;
+            (Some(collector), Some(handles))
+        }
+        None => (None, None),
+    };
+
+    let (total_hits, facet_fruit, top_docs): (usize, _, Vec<(SortKey, DocAddress)>) =
+        match &page.search_after {
+            Some(cursor) => {
+                if cursor.key.len() != sort.len() {
+                    return Err(TantivyGoError::InvalidArgument(
+                        "Search cursor does not match the sort order".to_string(),
+                    ));
+                }
+                let collector = AfterCursorCollector {
+                    tweaker: sort_key_tweaker(schema, sort)?,
+                    cursor: cursor.clone(),
+                    limit: docs_limit + offset,
+                };
+                let (total_hits, facet_fruit, mut top_docs) =
+                    search_with_counts(searcher, query, facets, collector)?;
+                top_docs.drain(..offset.min(top_docs.len()));
+                (total_hits, facet_fruit, top_docs)
+            }
+            None if matches!(sort, [SortBy { field: SortField::Score, descending: true }]) => {
+                let collector = TopDocs::with_limit(docs_limit).and_offset(offset);
+                let (total_hits, facet_fruit, top_docs) =
+                    search_with_counts(searcher, query, facets, collector)?;
+                let top_docs = top_docs
+                    .into_iter()
+                    .map(|(score, doc_address)| {
+                        let values = vec![Some(f64_to_u64(score as f64))];
+                        (SortKey { values, score }, doc_address)
+                    })
+                    .collect();
+                (total_hits, facet_fruit, top_docs)
+            }
+            None => {
+                let collector = TopDocs::with_limit(docs_limit)
+                    .and_offset(offset)
+                    .tweak_score(sort_key_tweaker(schema, sort)?);
+                search_with_counts(searcher, query, facets, collector)?
+            }
+        };
+
+    let facet_counts = match (facet_handles, facet_fruit) {
+        (Some(handles), Some(mut fruit)) => Some(handles.counts(&mut fruit)),
+        _ => None,
+    };
+    let cursor = top_docs.last().map(|(key, doc_address)| SearchCursor {
+        key: key.values.clone(),
+        doc_address: *doc_address,
+    });
+    let docs = top_docs
+        .into_iter()
+        .map(|(key, doc_address)| (key.score, doc_address))
+        .collect();
+    Ok(TopDocsPage { docs, cursor, total_hits, facet_counts, collapsed: None })
+}
@@ -0,0 +1,89 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tantivy::schema::Schema;
+use tantivy::space_usage::PerFieldSpaceUsage;
+use tantivy::{Index, Opstamp, Searcher};
+
+use crate::tantivy_util::TantivyGoError;
+
+/// Statistics of the searchable segments of an index, sizes in bytes.
+#[derive(Serialize)]
+pub struct IndexStats {
+    pub num_docs: u64,
+    pub num_deleted_docs: u64,
+    pub num_segments: usize,
+    pub segments: Vec<SegmentStats>,
+    /// Opstamp of the last commit on disk, which the searcher may not have loaded yet:
+    /// a searcher does not know the opstamp of the commit it was loaded from.
+    pub commit_opstamp: Opstamp,
+    pub total_bytes: u64,
+    /// Bytes of the indexed, positions, fast field and norm data of every field, by field name.
+    pub field_bytes: BTreeMap<String, u64>,
+    pub store_bytes: u64,
+}
+
+#[derive(Serialize)]
+pub struct SegmentStats {
+    pub id: String,
+    pub num_docs: u32,
+    pub num_deleted_docs: u32,
+    pub total_bytes: u64,
+}
+
+impl IndexStats {
+    pub fn collect(index: &Index, searcher: &Searcher) -> Result<IndexStats, TantivyGoError> {
+        let schema = index.schema();
+        let commit_opstamp = index
+            .load_metas()
+            .map_err(|err| TantivyGoError::from_tantivy("Failed to load index meta", &err))?
+            .opstamp;
+        let space_usage = searcher
+            .space_usage()
+            .map_err(|err| TantivyGoError::Io(format!("Failed to compute space usage: {}", err)))?;
+
+        let mut field_bytes = BTreeMap::new();
+        let mut store_bytes = 0;
+        let mut segments = Vec::with_capacity(space_usage.segments().len());
+        for (reader, usage) in searcher.segment_readers().iter().zip(space_usage.segments()) {
+            for per_field in [
+                usage.termdict(),
+                usage.postings(),
+                usage.positions(),
+                usage.fast_fields(),
+                usage.fieldnorms(),
+            ] {
+                add_field_bytes(&mut field_bytes, &schema, per_field);
+            }
+            store_bytes += usage.store().total().get_bytes();
+            segments.push(SegmentStats {
+                id: reader.segment_id().uuid_string(),
+                num_docs: reader.num_docs(),
+                num_deleted_docs: reader.num_deleted_docs(),
+                total_bytes: usage.total().get_bytes(),
+            });
+        }
+
+        Ok(IndexStats {
+            num_docs: searcher.num_docs(),
+            num_deleted_docs: segments.iter().map(|segment| segment.num_deleted_docs as u64).sum(),
+            num_segments: segments.len(),
+            segments,
+            commit_opstamp,
+            total_bytes: space_usage.total().get_bytes(),
+            field_bytes,
+            store_bytes,
+        })
+    }
+}
+
+fn add_field_bytes(
+    field_bytes: &mut BTreeMap<String, u64>,
+    schema: &Schema,
+    per_field: &PerFieldSpaceUsage,
+) {
+    for (field, usage) in per_field.fields() {
+        *field_bytes
+            .entry(schema.get_field_name(*field).to_string())
+            .or_insert(0) += usage.total().get_bytes();
+    }
+}
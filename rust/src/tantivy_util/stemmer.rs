@@ -40,7 +40,7 @@ lazy_static! {
 pub fn create_stemmer(lang: &str) -> Result<Stemmer, TantivyGoError> {
     let stemmer_language = LANGUAGES
         .get(lang)
-        .ok_or_else(|| TantivyGoError(format!("{lang} is an unsupported language")))?;
+        .ok_or_else(|| TantivyGoError::InvalidArgument(format!("{lang} is an unsupported language")))?;
 
     Ok(Stemmer::new(stemmer_language.to_owned()))
 }
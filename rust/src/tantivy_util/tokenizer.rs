@@ -1,5 +1,5 @@
 use tantivy::{Index};
-use tantivy::tokenizer::{AsciiFoldingFilter, LowerCaser, NgramTokenizer, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer, TextAnalyzer};
+use tantivy::tokenizer::{AsciiFoldingFilter, LowerCaser, NgramTokenizer, RawTokenizer, RemoveLongFilter, SimpleTokenizer, TextAnalyzer};
 use crate::tantivy_util::{EdgeNgramTokenizer, TantivyGoError};
 use crate::tantivy_util::stemmer::create_stemmer;
 
@@ -55,7 +55,7 @@ pub fn register_jieba_tokenizer(
     let text_analyzer = TextAnalyzer::builder(tantivy_jieba::JiebaTokenizer {})
         .filter(RemoveLongFilter::limit(text_limit))
         .filter(LowerCaser)
-        .filter(Stemmer::default())
+        .filter(tantivy::tokenizer::Stemmer::default())
         .build();
 
     register_tokenizer(index, tokenizer_name, text_analyzer);
@@ -63,9 +63,9 @@ pub fn register_jieba_tokenizer(
 
 #[cfg(not(feature = "jieba"))]
 pub fn register_jieba_tokenizer(
-    text_limit: usize,
-    index: &Index,
-    tokenizer_name: &str,
+    _text_limit: usize,
+    _index: &Index,
+    _tokenizer_name: &str,
 ) {
     panic!("Jieba support not compiled in")
 }
@@ -86,7 +86,7 @@ pub fn register_ngram_tokenizer(
         min_gram,
         max_gram,
         prefix_only,
-    ).map_err(|e|TantivyGoError::from_err("ngram tokenizer", &e.to_string()))?;
+    ).map_err(|e|TantivyGoError::InvalidArgument(format!("ngram tokenizer: {}", e)))?;
 
     let text_analyzer = TextAnalyzer::builder(tokenizer)
         .filter(LowerCaser)
@@ -0,0 +1,243 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use tantivy::schema::{Field, FieldType, IndexRecordOption, Schema, Value};
+use tantivy::{
+    DocAddress, DocSet, Index, IndexWriter, Searcher, TantivyDocument, Term, TERMINATED,
+};
+
+use crate::tantivy_util::{
+    check_document, extract_terms, read_fast_field_values, FastFieldValue, TantivyGoError,
+};
+
+/// Version field id that stands for no version check.
+pub const NO_VERSION: u32 = u32::MAX;
+
+/// Replaces the indexed documents having the id of a new document, read from `id_field`.
+/// With `version_field` a document only replaces the indexed ones when its version is newer.
+#[derive(Clone)]
+pub struct Upsert {
+    pub id_field: Field,
+    pub version_field: Option<Field>,
+}
+
+impl Upsert {
+    /// Stages the deletion of the previous versions and the addition of the new documents.
+    /// Within the batch the last document of an id wins, or the newest one with a version field.
+    /// Versions are compared against the commit loaded by `searcher`,
+    /// not against operations already staged in `writer`.
+    /// Fails before staging anything when a document is invalid.
+    /// Returns the number of skipped documents.
+    pub fn stage(
+        &self,
+        writer: &mut IndexWriter,
+        searcher: &Searcher,
+        docs: Vec<TantivyDocument>,
+    ) -> Result<usize, TantivyGoError> {
+        let schema = searcher.schema();
+        self.validate(schema)?;
+
+        // Document index and version of the winning document of every id
+        let mut winners: HashMap<Term, (usize, Option<FastFieldValue>)> = HashMap::new();
+        let mut terms = Vec::with_capacity(docs.len());
+        for (idx, doc) in docs.iter().enumerate() {
+            check_document(searcher.index(), doc).map_err(|err| {
+                TantivyGoError::InvalidDocument(format!("Failed to add document {}: {}", idx, err))
+            })?;
+            let term = self.id_term(searcher.index(), schema, doc, idx)?;
+            let version = match self.version_field {
+                Some(field) => Some(document_version(schema, doc, field, idx)?),
+                None => None,
+            };
+            let newer = match (&version, winners.get(&term)) {
+                (Some(version), Some((_, Some(winner)))) => version > winner,
+                _ => true,
+            };
+            if newer {
+                winners.insert(term.clone(), (idx, version));
+            }
+            terms.push(term);
+        }
+
+        if let Some(version_field) = self.version_field {
+            let indexed = indexed_versions(
+                searcher,
+                schema,
+                self.id_field,
+                version_field,
+                winners.keys(),
+            )?;
+            winners.retain(|term, (_, version)| {
+                indexed.get(term).is_none_or(|indexed| *version > *indexed)
+            });
+        }
+
+        let mut keep = vec![false; docs.len()];
+        for (idx, _) in winners.values() {
+            keep[*idx] = true;
+        }
+        for ((doc, term), keep) in docs.into_iter().zip(terms).zip(&keep) {
+            if !keep {
+                continue;
+            }
+            writer.delete_term(term);
+            writer
+                .add_document(doc)
+                .map_err(|err| TantivyGoError::from_tantivy("Failed to add the document", &err))?;
+        }
+        Ok(keep.len() - winners.len())
+    }
+
+    fn validate(&self, schema: &Schema) -> Result<(), TantivyGoError> {
+        let entry = schema.get_field_entry(self.id_field);
+        if !matches!(
+            entry.field_type(),
+            FieldType::Str(_) | FieldType::U64(_) | FieldType::I64(_)
+        ) || !entry.is_indexed()
+        {
+            return Err(TantivyGoError::InvalidArgument(format!(
+                "Field '{}' must be an indexed str, u64 or i64 field to upsert by",
+                entry.name()
+            )));
+        }
+
+        if let Some(version_field) = self.version_field {
+            let entry = schema.get_field_entry(version_field);
+            if !matches!(
+                entry.field_type(),
+                FieldType::U64(_) | FieldType::I64(_) | FieldType::F64(_) | FieldType::Date(_)
+            ) || !entry.is_fast()
+            {
+                return Err(TantivyGoError::InvalidArgument(format!(
+                    "Field '{}' must be a numeric or date fast field to compare versions",
+                    entry.name()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn id_term(
+        &self,
+        index: &Index,
+        schema: &Schema,
+        doc: &TantivyDocument,
+        idx: usize,
+    ) -> Result<Term, TantivyGoError> {
+        let field = self.id_field;
+        let value = doc.get_first(field);
+        let term = match schema.get_field_entry(field).field_type() {
+            FieldType::U64(_) => value
+                .and_then(|value| value.as_u64())
+                .map(|value| Term::from_field_u64(field, value)),
+            FieldType::I64(_) => value
+                .and_then(|value| value.as_i64())
+                .map(|value| Term::from_field_i64(field, value)),
+            _ => match value.and_then(|value| value.as_str()) {
+                Some(value) => Some(text_id_term(index, schema, field, value, idx)?),
+                None => None,
+            },
+        };
+        term.ok_or_else(|| {
+            TantivyGoError::InvalidDocument(format!(
+                "Document {} has no value for the id field '{}'",
+                idx,
+                schema.get_field_name(field)
+            ))
+        })
+    }
+}
+
+// A text id is indexed as the tokens its field makes of it, so it must make exactly one
+fn text_id_term(
+    index: &Index,
+    schema: &Schema,
+    field: Field,
+    value: &str,
+    idx: usize,
+) -> Result<Term, TantivyGoError> {
+    let mut terms = match extract_terms(index, field, "", value) {
+        Ok(terms) => terms,
+        Err(TantivyGoError::NoTerms(_)) => Vec::new(),
+        Err(err) => return Err(err),
+    };
+    if terms.len() != 1 {
+        return Err(TantivyGoError::InvalidDocument(format!(
+            "The id '{}' of document {} must be a single token of the id field '{}'",
+            value,
+            idx,
+            schema.get_field_name(field)
+        )));
+    }
+    Ok(terms.remove(0).1)
+}
+
+fn document_version(
+    schema: &Schema,
+    doc: &TantivyDocument,
+    field: Field,
+    idx: usize,
+) -> Result<FastFieldValue, TantivyGoError> {
+    let value = doc.get_first(field);
+    let version = match schema.get_field_entry(field).field_type() {
+        FieldType::U64(_) => value.and_then(|value| value.as_u64()).map(FastFieldValue::U64),
+        FieldType::I64(_) => value.and_then(|value| value.as_i64()).map(FastFieldValue::I64),
+        FieldType::F64(_) => value.and_then(|value| value.as_f64()).map(FastFieldValue::F64),
+        _ => value
+            .and_then(|value| value.as_datetime())
+            .map(|date| FastFieldValue::Date(date.into_timestamp_nanos())),
+    };
+    version.ok_or_else(|| {
+        TantivyGoError::InvalidDocument(format!(
+            "Document {} has no value for the version field '{}'",
+            idx,
+            schema.get_field_name(field)
+        ))
+    })
+}
+
+// Newest indexed version of every id, none for documents without a version.
+// The ids are looked up together in the postings of every segment, as a `TermSetQuery` would,
+// but keeping which id every document has. Their versions are then read grouped by segment.
+fn indexed_versions<'a>(
+    searcher: &Searcher,
+    schema: &Schema,
+    id_field: Field,
+    version_field: Field,
+    terms: impl Iterator<Item = &'a Term>,
+) -> Result<HashMap<Term, Option<FastFieldValue>>, TantivyGoError> {
+    let terms: Vec<&Term> = terms.collect();
+    let mut doc_terms = Vec::new();
+    let mut doc_addresses = Vec::new();
+    for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+        let inverted_index = segment_reader
+            .inverted_index(id_field)
+            .map_err(|err| TantivyGoError::from_tantivy("Failed to read the ids", &err))?;
+        for (term_idx, term) in terms.iter().enumerate() {
+            let postings = inverted_index
+                .read_postings(term, IndexRecordOption::Basic)
+                .map_err(|err| TantivyGoError::from_tantivy("Failed to read the ids", &err.into()))?;
+            let Some(mut postings) = postings else {
+                continue;
+            };
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if !segment_reader.is_deleted(doc) {
+                    doc_terms.push(term_idx);
+                    doc_addresses.push(DocAddress::new(segment_ord as u32, doc));
+                }
+                doc = postings.advance();
+            }
+        }
+    }
+
+    let doc_versions = read_fast_field_values(searcher, schema, &[version_field], &doc_addresses)?
+        .remove(0);
+    let mut versions: HashMap<Term, Option<FastFieldValue>> = HashMap::new();
+    for (term_idx, version) in doc_terms.into_iter().zip(doc_versions) {
+        let newest = versions.entry(terms[term_idx].clone()).or_insert(None);
+        if version.partial_cmp(&*newest) == Some(Ordering::Greater) {
+            *newest = version;
+        }
+    }
+    Ok(versions)
+}
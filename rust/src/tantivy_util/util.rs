@@ -1,60 +1,219 @@
-use std::borrow::Cow;
 use std::error::Error;
+use std::ffi::NulError;
 use std::fmt;
-use tantivy::schema::document::ReferenceValueLeaf;
-use tantivy::schema::Field;
+use tantivy::schema::document::{ReferenceValue, ReferenceValueLeaf};
+use tantivy::schema::{Facet, Field, FieldType, Schema, Value};
 use tantivy::tokenizer::{Token, TokenStream};
-use tantivy::{Index, Term};
+use tantivy::{Index, TantivyError, Term};
 
 pub const DOCUMENT_BUDGET_BYTES: usize = 50_000_000;
 
-pub fn extract_text_from_owned_value<'a>(
-    value: &'a ReferenceValueLeaf<'a>,
-) -> Result<Cow<'a, str>, TantivyGoError> {
-    if let ReferenceValueLeaf::Str(text) = value {
-        Ok(Cow::Borrowed(text))
+pub fn extract_json_from_leaf(
+    value: &ReferenceValueLeaf,
+) -> Result<serde_json::Value, TantivyGoError> {
+    match value {
+        ReferenceValueLeaf::Null => Ok(serde_json::Value::Null),
+        ReferenceValueLeaf::Str(text) => Ok(serde_json::Value::from(*text)),
+        ReferenceValueLeaf::I64(number) => Ok(serde_json::Value::from(*number)),
+        ReferenceValueLeaf::U64(number) => Ok(serde_json::Value::from(*number)),
+        ReferenceValueLeaf::F64(number) => Ok(serde_json::Value::from(*number)),
+        ReferenceValueLeaf::Bool(flag) => Ok(serde_json::Value::from(*flag)),
+        ReferenceValueLeaf::Date(date) => Ok(serde_json::Value::from(date.into_timestamp_nanos())),
+        ReferenceValueLeaf::Facet(encoded) => Facet::from_encoded(encoded.to_string().into_bytes())
+            .map(|facet| serde_json::Value::from(facet.to_path_string()))
+            .map_err(|err| TantivyGoError::from_err("Invalid facet value", &err.to_string())),
+        _ => Err(TantivyGoError::InvalidArgument(
+            "Only str, numeric, bool, date and facet values are supported".to_string(),
+        )),
+    }
+}
+
+pub fn extract_json_from_value<'a, V: Value<'a>>(
+    value: &V,
+) -> Result<serde_json::Value, TantivyGoError> {
+    match value.as_value() {
+        ReferenceValue::Leaf(leaf) => extract_json_from_leaf(&leaf),
+        ReferenceValue::Array(items) => items
+            .map(|item| extract_json_from_value(&item))
+            .collect::<Result<Vec<_>, _>>()
+            .map(serde_json::Value::Array),
+        ReferenceValue::Object(entries) => {
+            let mut object = serde_json::Map::new();
+            for (key, item) in entries {
+                object.insert(key.to_string(), extract_json_from_value(&item)?);
+            }
+            Ok(serde_json::Value::Object(object))
+        }
+    }
+}
+
+/// Field of a field id given by the host, which must be a field of `schema`.
+pub fn schema_field(schema: &Schema, field_id: u32) -> Result<Field, TantivyGoError> {
+    if (field_id as usize) < schema.num_fields() {
+        Ok(Field::from_field_id(field_id))
     } else {
-        Err(TantivyGoError("Only OwnedValue::Str is supported".to_string()))
+        Err(TantivyGoError::UnknownField(format!("Unknown field id {}", field_id)))
     }
 }
 
+/// Tokenizes `query` with the tokenizer of `field`.
+/// For JSON fields the terms are built under `json_path`, which must be empty otherwise.
 pub fn extract_terms(
     index: &Index,
     field: Field,
+    json_path: &str,
     query: &str,
 ) -> Result<Vec<(usize, Term)>, TantivyGoError> {
+    let json_term = match index.schema().get_field_entry(field).field_type() {
+        FieldType::JsonObject(options) => Some(Term::from_field_json_path(
+            field,
+            json_path,
+            options.is_expand_dots_enabled(),
+        )),
+        _ if json_path.is_empty() => None,
+        _ => {
+            return Err(TantivyGoError::InvalidArgument(format!(
+                "Json path '{}' is set for a non-json field",
+                json_path
+            )))
+        }
+    };
     let mut tokenizer = match index.tokenizer_for_field(field) {
         Ok(tokenizer) => tokenizer,
-        Err(err) => return Err(TantivyGoError::from_err("", &err.to_string())),
+        Err(err) => return Err(TantivyGoError::from_tantivy("Failed to get the tokenizer", &err)),
     };
     let mut token_stream = tokenizer.token_stream(query);
     let mut terms = Vec::new();
     token_stream.process(&mut |token: &Token| {
-        terms.push((token.position, Term::from_field_text(field, &token.text)))
+        let term = match &json_term {
+            Some(json_term) => {
+                let mut term = json_term.clone();
+                term.append_type_and_str(&token.text);
+                term
+            }
+            None => Term::from_field_text(field, &token.text),
+        };
+        terms.push((token.position, term))
     });
-    if terms.len() > 0 {
+    if !terms.is_empty() {
         Ok(terms)
     } else {
-        Err(TantivyGoError("Zero terms were extracted".to_string()))
+        Err(TantivyGoError::NoTerms("Zero terms were extracted".to_string()))
     }
 }
 
+// Error codes set in the `ErrorBuffer` of a failing call, stable across releases.
+
+/// Any failure without a more specific code.
+pub const ERROR_INTERNAL: u32 = 1;
+pub const ERROR_NULL_POINTER: u32 = 2;
+pub const ERROR_INVALID_UTF8: u32 = 3;
+/// An option, value or request of the host is invalid.
+pub const ERROR_INVALID_ARGUMENT: u32 = 4;
+pub const ERROR_QUERY_PARSE: u32 = 5;
+pub const ERROR_UNKNOWN_FIELD: u32 = 6;
+/// The query text of a field produced no terms, e.g. only stop words.
+pub const ERROR_NO_TERMS: u32 = 7;
+/// A document does not match the schema or the batch it is part of.
+pub const ERROR_INVALID_DOCUMENT: u32 = 8;
+pub const ERROR_INDEX_CORRUPTED: u32 = 9;
+/// The index is locked by another writer.
+pub const ERROR_LOCK_BUSY: u32 = 10;
+pub const ERROR_IO: u32 = 11;
+/// The call panicked, which poisoned its context.
+pub const ERROR_PANIC: u32 = 12;
+/// The context is unusable since an earlier call on it panicked.
+pub const ERROR_CONTEXT_POISONED: u32 = 13;
+
+/// Error of a call, its variant gives the code reported to the host next to the message.
 #[derive(Debug)]
-pub struct TantivyGoError(pub String);
+pub enum TantivyGoError {
+    Internal(String),
+    NullPointer(String),
+    InvalidUtf8(String),
+    InvalidArgument(String),
+    QueryParse(String),
+    UnknownField(String),
+    NoTerms(String),
+    InvalidDocument(String),
+    IndexCorrupted(String),
+    LockBusy(String),
+    Io(String),
+    Panic(String),
+    ContextPoisoned(String),
+}
 
 impl TantivyGoError {
     pub fn from_err(message: &str, err: &str) -> TantivyGoError {
-        TantivyGoError(format!("{}: {}", message, err).to_string())
+        TantivyGoError::Internal(format!("{}: {}", message, err))
+    }
+
+    /// Error of a tantivy call, its code derived from the kind of the tantivy error.
+    pub fn from_tantivy(message: &str, err: &TantivyError) -> TantivyGoError {
+        let message = format!("{}: {}", message, err);
+        match err {
+            TantivyError::LockFailure(..) => TantivyGoError::LockBusy(message),
+            TantivyError::DataCorruption(..) | TantivyError::IncompatibleIndex(..) => {
+                TantivyGoError::IndexCorrupted(message)
+            }
+            TantivyError::IoError(..)
+            | TantivyError::OpenDirectoryError(..)
+            | TantivyError::OpenReadError(..)
+            | TantivyError::OpenWriteError(..) => TantivyGoError::Io(message),
+            TantivyError::FieldNotFound(..) => TantivyGoError::UnknownField(message),
+            TantivyError::InvalidArgument(..)
+            | TantivyError::SchemaError(..)
+            | TantivyError::AggregationError(..) => TantivyGoError::InvalidArgument(message),
+            _ => TantivyGoError::Internal(message),
+        }
+    }
+
+    pub fn code(&self) -> u32 {
+        match self {
+            TantivyGoError::Internal(_) => ERROR_INTERNAL,
+            TantivyGoError::NullPointer(_) => ERROR_NULL_POINTER,
+            TantivyGoError::InvalidUtf8(_) => ERROR_INVALID_UTF8,
+            TantivyGoError::InvalidArgument(_) => ERROR_INVALID_ARGUMENT,
+            TantivyGoError::QueryParse(_) => ERROR_QUERY_PARSE,
+            TantivyGoError::UnknownField(_) => ERROR_UNKNOWN_FIELD,
+            TantivyGoError::NoTerms(_) => ERROR_NO_TERMS,
+            TantivyGoError::InvalidDocument(_) => ERROR_INVALID_DOCUMENT,
+            TantivyGoError::IndexCorrupted(_) => ERROR_INDEX_CORRUPTED,
+            TantivyGoError::LockBusy(_) => ERROR_LOCK_BUSY,
+            TantivyGoError::Io(_) => ERROR_IO,
+            TantivyGoError::Panic(_) => ERROR_PANIC,
+            TantivyGoError::ContextPoisoned(_) => ERROR_CONTEXT_POISONED,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            TantivyGoError::Internal(message)
+            | TantivyGoError::NullPointer(message)
+            | TantivyGoError::InvalidUtf8(message)
+            | TantivyGoError::InvalidArgument(message)
+            | TantivyGoError::QueryParse(message)
+            | TantivyGoError::UnknownField(message)
+            | TantivyGoError::NoTerms(message)
+            | TantivyGoError::InvalidDocument(message)
+            | TantivyGoError::IndexCorrupted(message)
+            | TantivyGoError::LockBusy(message)
+            | TantivyGoError::Io(message)
+            | TantivyGoError::Panic(message)
+            | TantivyGoError::ContextPoisoned(message) => message,
+        }
     }
+}
 
-    pub fn from_str(value: &str) -> TantivyGoError {
-        TantivyGoError(value.to_string())
+impl From<NulError> for TantivyGoError {
+    fn from(err: NulError) -> Self {
+        TantivyGoError::Internal(err.to_string())
     }
 }
 
 impl fmt::Display for TantivyGoError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.message())
     }
 }
 
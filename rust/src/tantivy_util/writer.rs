@@ -0,0 +1,63 @@
+use tantivy::index::SegmentId;
+use tantivy::{Index, IndexWriter, SegmentMeta};
+
+use crate::tantivy_util::TantivyGoError;
+
+/// Merges the smallest searchable segments so that at most `target_segments` remain and waits
+/// for the merge. Returns the number of searchable segments after the merge.
+pub fn force_merge(
+    index: &Index,
+    writer: &mut IndexWriter,
+    target_segments: usize,
+) -> Result<usize, TantivyGoError> {
+    let mut segments = searchable_segment_metas(index)?;
+    let target_segments = target_segments.max(1);
+    if segments.len() <= target_segments {
+        return Ok(segments.len());
+    }
+    segments.sort_by_key(SegmentMeta::num_docs);
+    let segment_ids: Vec<SegmentId> = segments[..segments.len() - target_segments + 1]
+        .iter()
+        .map(SegmentMeta::id)
+        .collect();
+    merge_segments(writer, &segment_ids)?;
+    Ok(searchable_segment_metas(index)?.len())
+}
+
+/// Rewrites every searchable segment having at least `deleted_ratio` of deleted documents
+/// without them and waits for the merges. Returns the number of rewritten segments.
+pub fn purge_deletes(
+    index: &Index,
+    writer: &mut IndexWriter,
+    deleted_ratio: f32,
+) -> Result<usize, TantivyGoError> {
+    if deleted_ratio <= 0.0 || deleted_ratio > 1.0 {
+        return Err(TantivyGoError::InvalidArgument(format!(
+            "Deleted docs ratio {} must be in (0, 1]",
+            deleted_ratio
+        )));
+    }
+    let mut purged = 0;
+    for segment in searchable_segment_metas(index)? {
+        let deleted = segment.num_deleted_docs() as f32;
+        if segment.max_doc() > 0 && deleted / segment.max_doc() as f32 >= deleted_ratio {
+            merge_segments(writer, &[segment.id()])?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
+
+fn searchable_segment_metas(index: &Index) -> Result<Vec<SegmentMeta>, TantivyGoError> {
+    index
+        .searchable_segment_metas()
+        .map_err(|err| TantivyGoError::from_tantivy("Failed to load segments", &err))
+}
+
+fn merge_segments(writer: &mut IndexWriter, segment_ids: &[SegmentId]) -> Result<(), TantivyGoError> {
+    writer
+        .merge(segment_ids)
+        .wait()
+        .map(|_| ())
+        .map_err(|err| TantivyGoError::from_tantivy("Failed to merge segments", &err))
+}
@@ -28,6 +28,17 @@ const (
 
 const DefaultTokenizer = "default"
 
+// DatePrecision is the precision of the indexed and fast values of a date field.
+type DatePrecision uint8
+
+const (
+	// DatePrecisionSeconds is the default precision of date fields.
+	DatePrecisionSeconds      DatePrecision = C.DATE_PRECISION_SECONDS
+	DatePrecisionMilliseconds DatePrecision = C.DATE_PRECISION_MILLISECONDS
+	DatePrecisionMicroseconds DatePrecision = C.DATE_PRECISION_MICROSECONDS
+	DatePrecisionNanoseconds  DatePrecision = C.DATE_PRECISION_NANOSECONDS
+)
+
 type Language string
 
 const (
@@ -99,7 +110,7 @@ func (b *SchemaBuilder) AddTextField(
 	cTokenizer := C.CString(tokenizer)
 	defer C.string_free(cName)
 	defer C.string_free(cTokenizer)
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	fieldId := C.schema_builder_add_text_field(
 		b.ptr,
 		cName,
@@ -114,14 +125,169 @@ func (b *SchemaBuilder) AddTextField(
 	return tryExtractError(errBuffer)
 }
 
+// AddI64Field adds a signed 64-bit integer field to the schema being built.
+//
+// Parameters:
+// - name: The name of the field.
+// - stored: Whether the field should be stored in the index.
+// - indexed: Whether the field should be indexed so it can be searched.
+// - isFast: Whether the field should be stored as tantivy fast field.
+//
+// Returns an error if the field could not be added.
+func (b *SchemaBuilder) AddI64Field(name string, stored bool, indexed bool, isFast bool) error {
+	return b.addField(name, func(cName *C.char, errBuffer *C.ErrorBuffer) C.uint32_t {
+		return C.schema_builder_add_i64_field(b.ptr, cName, C._Bool(stored), C._Bool(indexed), C._Bool(isFast), errBuffer)
+	})
+}
+
+// AddU64Field adds an unsigned 64-bit integer field to the schema being built.
+//
+// Parameters:
+// - name: The name of the field.
+// - stored: Whether the field should be stored in the index.
+// - indexed: Whether the field should be indexed so it can be searched.
+// - isFast: Whether the field should be stored as tantivy fast field.
+//
+// Returns an error if the field could not be added.
+func (b *SchemaBuilder) AddU64Field(name string, stored bool, indexed bool, isFast bool) error {
+	return b.addField(name, func(cName *C.char, errBuffer *C.ErrorBuffer) C.uint32_t {
+		return C.schema_builder_add_u64_field(b.ptr, cName, C._Bool(stored), C._Bool(indexed), C._Bool(isFast), errBuffer)
+	})
+}
+
+// AddF64Field adds a 64-bit floating point field to the schema being built.
+//
+// Parameters:
+// - name: The name of the field.
+// - stored: Whether the field should be stored in the index.
+// - indexed: Whether the field should be indexed so it can be searched.
+// - isFast: Whether the field should be stored as tantivy fast field.
+//
+// Returns an error if the field could not be added.
+func (b *SchemaBuilder) AddF64Field(name string, stored bool, indexed bool, isFast bool) error {
+	return b.addField(name, func(cName *C.char, errBuffer *C.ErrorBuffer) C.uint32_t {
+		return C.schema_builder_add_f64_field(b.ptr, cName, C._Bool(stored), C._Bool(indexed), C._Bool(isFast), errBuffer)
+	})
+}
+
+// AddBoolField adds a boolean field to the schema being built.
+//
+// Parameters:
+// - name: The name of the field.
+// - stored: Whether the field should be stored in the index.
+// - indexed: Whether the field should be indexed so it can be searched.
+// - isFast: Whether the field should be stored as tantivy fast field.
+//
+// Returns an error if the field could not be added.
+func (b *SchemaBuilder) AddBoolField(name string, stored bool, indexed bool, isFast bool) error {
+	return b.addField(name, func(cName *C.char, errBuffer *C.ErrorBuffer) C.uint32_t {
+		return C.schema_builder_add_bool_field(b.ptr, cName, C._Bool(stored), C._Bool(indexed), C._Bool(isFast), errBuffer)
+	})
+}
+
+// AddDateField adds a date field to the schema being built.
+// Indexed and fast values have the default tantivy precision of seconds, so range queries,
+// sorting and fast field values see dates truncated to the second.
+// Stored values keep their full precision. See AddDateFieldWithPrecision for a finer precision.
+//
+// Parameters:
+// - name: The name of the field.
+// - stored: Whether the field should be stored in the index.
+// - indexed: Whether the field should be indexed so it can be searched.
+// - isFast: Whether the field should be stored as tantivy fast field.
+//
+// Returns an error if the field could not be added.
+func (b *SchemaBuilder) AddDateField(name string, stored bool, indexed bool, isFast bool) error {
+	return b.AddDateFieldWithPrecision(name, stored, indexed, isFast, DatePrecisionSeconds)
+}
+
+// AddDateFieldWithPrecision adds a date field whose indexed and fast values are truncated to the
+// given precision instead of seconds. A finer precision takes more space in the index.
+//
+// Parameters:
+// - name: The name of the field.
+// - stored: Whether the field should be stored in the index.
+// - indexed: Whether the field should be indexed so it can be searched.
+// - isFast: Whether the field should be stored as tantivy fast field.
+// - precision: The precision of the indexed and fast values.
+//
+// Returns an error if the field could not be added or the precision is unknown.
+func (b *SchemaBuilder) AddDateFieldWithPrecision(name string, stored bool, indexed bool, isFast bool, precision DatePrecision) error {
+	return b.addField(name, func(cName *C.char, errBuffer *C.ErrorBuffer) C.uint32_t {
+		return C.schema_builder_add_date_field(b.ptr, cName, C._Bool(stored), C._Bool(indexed), C._Bool(isFast), C.uint8_t(precision), errBuffer)
+	})
+}
+
+// AddFacetField adds a hierarchical facet field to the schema being built.
+// Facets are always indexed, so the documents below a facet path can be filtered and counted.
+//
+// Parameters:
+// - name: The name of the field.
+// - stored: Whether the field should be stored in the index.
+//
+// Returns an error if the field could not be added.
+func (b *SchemaBuilder) AddFacetField(name string, stored bool) error {
+	return b.addField(name, func(cName *C.char, errBuffer *C.ErrorBuffer) C.uint32_t {
+		return C.schema_builder_add_facet_field(b.ptr, cName, C._Bool(stored), errBuffer)
+	})
+}
+
+// AddJsonField adds a json object field to the schema being built.
+// Values inside the object can be queried by path, e.g. `relations.status`.
+//
+// Parameters:
+// - name: The name of the field.
+// - stored: Whether the field should be stored in the index.
+// - isFast: Whether the field should be stored as tantivy fast field.
+// - expandDots: Whether keys containing dots should be treated as nested objects.
+// - indexRecordOption: The indexing option to be used for text values.
+// - tokenizer: The name of the tokenizer to be used for text values.
+//
+// Returns an error if the field could not be added.
+func (b *SchemaBuilder) AddJsonField(
+	name string,
+	stored bool,
+	isFast bool,
+	expandDots bool,
+	indexRecordOption int,
+	tokenizer string,
+) error {
+	cTokenizer := C.CString(tokenizer)
+	defer C.string_free(cTokenizer)
+	return b.addField(name, func(cName *C.char, errBuffer *C.ErrorBuffer) C.uint32_t {
+		return C.schema_builder_add_json_field(
+			b.ptr,
+			cName,
+			C._Bool(stored),
+			C._Bool(isFast),
+			C._Bool(expandDots),
+			pointerCType(indexRecordOption),
+			cTokenizer,
+			errBuffer,
+		)
+	})
+}
+
+func (b *SchemaBuilder) addField(name string, add func(cName *C.char, errBuffer *C.ErrorBuffer) C.uint32_t) error {
+	if _, contains := b.fieldNames[name]; contains {
+		return errors.New("field already defined: " + name)
+	}
+	b.fieldNames[name] = -1
+	cName := C.CString(name)
+	defer C.string_free(cName)
+	var errBuffer C.ErrorBuffer
+	fieldId := add(cName, &errBuffer)
+	b.fieldNames[name] = int(fieldId)
+	return tryExtractError(errBuffer)
+}
+
 // BuildSchema finalizes the schema building process and returns the resulting Schema.
 // Returns a pointer to the Schema and an error if the schema could not be built.
 func (b *SchemaBuilder) BuildSchema() (*Schema, error) {
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	ptr := C.schema_builder_build(b.ptr, &errBuffer)
 	if ptr == nil {
-		defer C.string_free(errBuffer)
-		return nil, errors.New(C.GoString(errBuffer))
+		return nil, tryExtractError(errBuffer)
 	}
 	return &Schema{
 		ptr:        ptr,
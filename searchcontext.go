@@ -10,18 +10,63 @@ type SearchContext interface {
 	GetQuery() string
 	// GetDocsLimit returns the document limit as a uintptr.
 	GetDocsLimit() uintptr
+	// GetOffset returns the number of documents to skip as a uintptr.
+	GetOffset() uintptr
+	// GetSearchAfter returns the cursor the results start after, empty to start from the top.
+	GetSearchAfter() string
 	// WithHighlights returns true if highlights are enabled.
 	WithHighlights() bool
 	// GetFieldAndWeights returns slices of field names and their corresponding weights.
 	GetFieldAndWeights() ([]string, []float32)
+	// GetSortFields returns the sort keys in priority order, empty when sorting by score.
+	GetSortFields() []SortField
+	// GetFacetCounts returns the facet field and the prefixes whose children are counted,
+	// no prefixes when no facet counts are requested.
+	GetFacetCounts() (string, []string)
+	// GetCollapse returns the collapse options, nil when every hit is returned.
+	GetCollapse() *Collapse
+	// GetSearcher returns the pinned searcher to search with, nil to use the current one.
+	GetSearcher() *Searcher
+}
+
+// SortOrder defines the direction of a sort key.
+type SortOrder int
+
+const (
+	Asc SortOrder = iota
+	Desc
+)
+
+// SortField is a single sort key, either a fast field or the relevance score.
+type SortField struct {
+	// FieldName is the name of a numeric, bool or date fast field, empty when ByScore is set.
+	FieldName string
+	ByScore   bool
+	Order     SortOrder
+}
+
+// Collapse keeps only the best hit per value of a string or u64 fast field, e.g. one hit per object.
+type Collapse struct {
+	FieldName string
+	// WithSiblings reports the number of collapsed hits of every returned document
+	// and the highlights of up to SiblingLimit of the best scoring ones.
+	WithSiblings bool
+	SiblingLimit uintptr
 }
 
 // searchContext is a structure that implements SearchContext.
 type searchContext struct {
 	query          string
 	docsLimit      uintptr
+	offset         uintptr
+	searchAfter    string
 	withHighlights bool
 	fieldNames     map[string]float32
+	sortFields     []SortField
+	facetField     string
+	facetPrefixes  []string
+	collapse       *Collapse
+	searcher       *Searcher
 }
 
 // GetQuery returns the search query string.
@@ -34,6 +79,16 @@ func (sc *searchContext) GetDocsLimit() uintptr {
 	return sc.docsLimit
 }
 
+// GetOffset returns the number of documents to skip.
+func (sc *searchContext) GetOffset() uintptr {
+	return sc.offset
+}
+
+// GetSearchAfter returns the cursor the results start after.
+func (sc *searchContext) GetSearchAfter() string {
+	return sc.searchAfter
+}
+
 // WithHighlights returns the highlights flag.
 func (sc *searchContext) WithHighlights() bool {
 	return sc.withHighlights
@@ -57,6 +112,26 @@ func (sc *searchContext) GetFieldAndWeights() ([]string, []float32) {
 	return fields, weights
 }
 
+// GetSortFields returns the sort keys in priority order.
+func (sc *searchContext) GetSortFields() []SortField {
+	return sc.sortFields
+}
+
+// GetFacetCounts returns the facet field and the prefixes whose children are counted.
+func (sc *searchContext) GetFacetCounts() (string, []string) {
+	return sc.facetField, sc.facetPrefixes
+}
+
+// GetCollapse returns the collapse options.
+func (sc *searchContext) GetCollapse() *Collapse {
+	return sc.collapse
+}
+
+// GetSearcher returns the pinned searcher.
+func (sc *searchContext) GetSearcher() *Searcher {
+	return sc.searcher
+}
+
 // SearchContextBuilder is a builder structure for creating searchContext.
 type SearchContextBuilder struct {
 	context *searchContext
@@ -84,12 +159,27 @@ func (b *SearchContextBuilder) SetQueryFromJson(query *FinalQuery) *SearchContex
 	return b
 }
 
-// SetDocsLimit sets the docsLimit for searchContext.
+// SetDocsLimit sets the docsLimit for searchContext, which must be at least 1.
 func (b *SearchContextBuilder) SetDocsLimit(limit uintptr) *SearchContextBuilder {
 	b.context.docsLimit = limit
 	return b
 }
 
+// SetOffset sets the number of documents to skip for searchContext.
+// With a search-after cursor the documents are skipped from the cursor on.
+func (b *SearchContextBuilder) SetOffset(offset uintptr) *SearchContextBuilder {
+	b.context.offset = offset
+	return b
+}
+
+// SetSearchAfter makes the search return the documents following the cursor of a previous
+// result (see SearchResult.GetCursor). The query and sort must be the same as for that result,
+// and cursors only stay valid until the index changes.
+func (b *SearchContextBuilder) SetSearchAfter(cursor string) *SearchContextBuilder {
+	b.context.searchAfter = cursor
+	return b
+}
+
 // SetWithHighlights sets the withHighlights flag for searchContext.
 func (b *SearchContextBuilder) SetWithHighlights(withHighlights bool) *SearchContextBuilder {
 	b.context.withHighlights = withHighlights
@@ -108,6 +198,44 @@ func (b *SearchContextBuilder) AddFieldDefaultWeight(field string) *SearchContex
 	return b
 }
 
+// AddSortField sorts the results by a fast field. Sort keys apply in the order they are added,
+// documents equal on every key keep a stable order between searches.
+func (b *SearchContextBuilder) AddSortField(field string, order SortOrder) *SearchContextBuilder {
+	b.context.sortFields = append(b.context.sortFields, SortField{FieldName: field, Order: order})
+	return b
+}
+
+// AddSortByScore sorts the results by relevance score, e.g. to break ties of a previous sort key.
+func (b *SearchContextBuilder) AddSortByScore(order SortOrder) *SearchContextBuilder {
+	b.context.sortFields = append(b.context.sortFields, SortField{ByScore: true, Order: order})
+	return b
+}
+
+// SetFacetCounts counts the matching documents under the direct children of every prefix
+// (e.g. `/space` or `/space/abc/type`) of a facet field, next to the top documents.
+// Prefixes may be nested in each other. See SearchResult.GetFacetCounts.
+func (b *SearchContextBuilder) SetFacetCounts(field string, prefixes ...string) *SearchContextBuilder {
+	b.context.facetField = field
+	b.context.facetPrefixes = prefixes
+	return b
+}
+
+// SetCollapse keeps only the best hit per value of a string or u64 fast field.
+// Offset and docs limit then count collapsed hits, and search-after cursors are not supported.
+// With siblings, every returned document carries a `collapsed` object in its json with the
+// number of collapsed hits and the highlights of up to siblingLimit of them.
+func (b *SearchContextBuilder) SetCollapse(field string, withSiblings bool, siblingLimit uintptr) *SearchContextBuilder {
+	b.context.collapse = &Collapse{FieldName: field, WithSiblings: withSiblings, SiblingLimit: siblingLimit}
+	return b
+}
+
+// SetSearcher runs the search on a searcher acquired with TantivyContext.AcquireSearcher,
+// so that the pages of a query come from the same commit.
+func (b *SearchContextBuilder) SetSearcher(searcher *Searcher) *SearchContextBuilder {
+	b.context.searcher = searcher
+	return b
+}
+
 // Build returns the constructed searchContext as an interface.
 func (b *SearchContextBuilder) Build() SearchContext {
 	return b.context
@@ -0,0 +1,53 @@
+package tantivy_go
+
+// #include "bindings.h"
+import "C"
+
+// Searcher is a searcher pinned to a reader generation. Searches given the same Searcher see the
+// same documents, whatever is committed or reloaded meanwhile, e.g. to page consistently through
+// the results of a query.
+type Searcher struct {
+	ptr        *C.PinnedSearcher
+	generation uint64
+}
+
+// AcquireSearcher pins the searcher of the current reader generation.
+// Pass it to SearchContextBuilder.SetSearcher and free it with Free once done, since it keeps
+// the segments it sees alive.
+//
+// Returns:
+//   - *Searcher: The pinned searcher.
+//   - error: An error if acquiring the searcher fails.
+func (tc *TantivyContext) AcquireSearcher() (*Searcher, error) {
+	var errBuffer C.ErrorBuffer
+	ptr := C.context_acquire_searcher(tc.ptr, &errBuffer)
+	if ptr == nil {
+		return nil, tryExtractError(errBuffer)
+	}
+	generation := C.pinned_searcher_get_generation(ptr, &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		C.pinned_searcher_free(ptr)
+		return nil, err
+	}
+	return &Searcher{ptr: ptr, generation: uint64(generation)}, nil
+}
+
+// Generation returns the id of the reader generation the searcher is pinned to.
+// Searchers acquired without any reload in between share the same generation.
+func (s *Searcher) Generation() uint64 {
+	return s.generation
+}
+
+// Free releases the searcher. It must not be used by any search afterwards.
+func (s *Searcher) Free() {
+	C.pinned_searcher_free(s.ptr)
+	s.ptr = nil
+}
+
+// searcherPtr returns nil when the search runs on the current searcher.
+func searcherPtr(sCtx SearchContext) *C.PinnedSearcher {
+	if sCtx.GetSearcher() == nil {
+		return nil
+	}
+	return sCtx.GetSearcher().ptr
+}
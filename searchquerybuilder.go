@@ -1,5 +1,7 @@
 package tantivy_go
 
+import "time"
+
 type QueryType int
 
 const (
@@ -11,6 +13,17 @@ const (
 	EveryTermQuery
 	OneOfTermQuery
 	AllQuery
+	RangeQuery
+	FuzzyTermQuery
+	FuzzyPrefixQuery
+	// RegexQuery and WildcardQuery match the text as a pattern against the field's
+	// terms without tokenizing it, so they are meant for raw tokenized fields.
+	// WildcardQuery supports `*` (any sequence) and `?` (any character).
+	RegexQuery
+	WildcardQuery
+	// FacetQuery matches the documents having the facet path (e.g. `/space/abc`)
+	// or any facet below it.
+	FacetQuery
 )
 
 type QueryModifier int
@@ -27,6 +40,56 @@ type FieldQuery struct {
 	Boost      float64 `json:"boost"`
 }
 
+type FuzzyFieldQuery struct {
+	FieldIndex    int     `json:"field_index"`
+	TextIndex     int     `json:"text_index"`
+	Distance      uint8   `json:"distance"`
+	Transposition bool    `json:"transposition"`
+	Boost         float64 `json:"boost"`
+}
+
+type RangeValueType int
+
+const (
+	RangeI64 RangeValueType = iota
+	RangeU64
+	RangeF64
+	// RangeDate bounds are nanoseconds since the Unix epoch.
+	RangeDate
+)
+
+type RangeBound struct {
+	Value     any  `json:"value"`
+	Inclusive bool `json:"inclusive"`
+}
+
+// Inclusive creates a range bound that includes the value.
+// time.Time values are converted to nanoseconds since the Unix epoch.
+func Inclusive(value any) *RangeBound {
+	return &RangeBound{Value: rangeValue(value), Inclusive: true}
+}
+
+// Exclusive creates a range bound that excludes the value.
+// time.Time values are converted to nanoseconds since the Unix epoch.
+func Exclusive(value any) *RangeBound {
+	return &RangeBound{Value: rangeValue(value), Inclusive: false}
+}
+
+func rangeValue(value any) any {
+	if t, ok := value.(time.Time); ok {
+		return t.UnixNano()
+	}
+	return value
+}
+
+type RangeFieldQuery struct {
+	FieldIndex int            `json:"field_index"`
+	ValueType  RangeValueType `json:"value_type"`
+	Lower      *RangeBound    `json:"lower,omitempty"`
+	Upper      *RangeBound    `json:"upper,omitempty"`
+	Boost      float64        `json:"boost"`
+}
+
 type QueryElement struct {
 	Query     Query         `json:"query"`
 	Modifier  QueryModifier `json:"query_modifier"`
@@ -131,6 +194,44 @@ func (qb *QueryBuilder) AllQuery(modifier QueryModifier, boost float64) *QueryBu
 	return qb
 }
 
+// FuzzyQuery matches terms within a Levenshtein distance (0 to 2) of each token of text.
+// queryType must be FuzzyTermQuery or FuzzyPrefixQuery; with transposition a swap of
+// two adjacent characters costs a single edit.
+func (qb *QueryBuilder) FuzzyQuery(modifier QueryModifier, field string, text string, queryType QueryType, distance uint8, transposition bool, boost float64) *QueryBuilder {
+	textIndex := qb.AddText(text)
+	fieldIndex := qb.AddField(field)
+	qb.subqueries = append(qb.subqueries, QueryElement{
+		Query: &FuzzyFieldQuery{
+			FieldIndex:    fieldIndex,
+			TextIndex:     textIndex,
+			Distance:      distance,
+			Transposition: transposition,
+			Boost:         boost,
+		},
+		Modifier:  modifier,
+		QueryType: queryType,
+	})
+	return qb
+}
+
+// RangeQuery matches documents whose numeric or date field value lies between lower and upper.
+// A nil bound leaves that side of the range open, but at least one bound must be set.
+func (qb *QueryBuilder) RangeQuery(modifier QueryModifier, field string, valueType RangeValueType, lower, upper *RangeBound, boost float64) *QueryBuilder {
+	fieldIndex := qb.AddField(field)
+	qb.subqueries = append(qb.subqueries, QueryElement{
+		Query: &RangeFieldQuery{
+			FieldIndex: fieldIndex,
+			ValueType:  valueType,
+			Lower:      lower,
+			Upper:      upper,
+			Boost:      boost,
+		},
+		Modifier:  modifier,
+		QueryType: RangeQuery,
+	})
+	return qb
+}
+
 func (qb *QueryBuilder) Build() FinalQuery {
 	return FinalQuery{
 		Texts:  qb.store.textList,
@@ -149,6 +250,10 @@ func (fq *FieldQuery) IsQuery() {}
 
 func (bq *BooleanQuery) IsQuery() {}
 
+func (rq *RangeFieldQuery) IsQuery() {}
+
+func (fq *FuzzyFieldQuery) IsQuery() {}
+
 type AllQueryStruct struct {
 	Boost float64 `json:"boost"`
 }
@@ -2,10 +2,7 @@ package tantivy_go
 
 //#include "bindings.h"
 import "C"
-import (
-	"errors"
-	"fmt"
-)
+import "encoding/json"
 
 type SearchResult struct{ ptr *C.SearchResult }
 
@@ -18,11 +15,10 @@ type SearchResult struct{ ptr *C.SearchResult }
 // - A pointer to the Document if successful, or nil if not found.
 // - An error if there was an issue retrieving the document.
 func (r *SearchResult) Get(index uint64) (*Document, error) {
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	ptr := C.search_result_get_doc(r.ptr, C.uintptr_t(index), &errBuffer)
 	if ptr == nil {
-		defer C.string_free(errBuffer)
-		return nil, errors.New(C.GoString(errBuffer))
+		return nil, tryExtractError(errBuffer)
 	}
 	return &Document{ptr: ptr}, nil
 }
@@ -33,18 +29,68 @@ func (r *SearchResult) Get(index uint64) (*Document, error) {
 // - The size of the search result if successful.
 // - An error if there was an issue getting the size.
 func (r *SearchResult) GetSize() (uint64, error) {
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 
 	size := C.search_result_get_size(r.ptr, &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		return uint64(0), err
+	}
+	return uint64(size), nil
+}
+
+// GetTotalHits returns the number of documents matching the query,
+// which may be greater than the number of documents in the search result.
+//
+// Returns:
+// - The total number of hits if successful.
+// - An error if there was an issue getting the total.
+func (r *SearchResult) GetTotalHits() (uint64, error) {
+	var errBuffer C.ErrorBuffer
+	totalHits := C.search_result_get_total_hits(r.ptr, &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
+	}
+	return uint64(totalHits), nil
+}
+
+// GetCursor returns the cursor of the last document in the search result.
+// Pass it to SearchContextBuilder.SetSearchAfter to fetch the next page with the same query and sort.
+//
+// Returns:
+// - The cursor, or an empty string if the search result is empty.
+// - An error if there was an issue getting the cursor.
+func (r *SearchResult) GetCursor() (string, error) {
+	var errBuffer C.ErrorBuffer
+	ptr := C.search_result_get_cursor(r.ptr, &errBuffer)
+	if ptr == nil {
+		return "", tryExtractError(errBuffer)
+	}
+	defer C.string_free(ptr)
+	return C.GoString(ptr), nil
+}
+
+// FacetCounts maps every requested facet prefix to the number of matching documents
+// under each of its direct children, keyed by facet path.
+type FacetCounts map[string]map[string]uint64
 
-	errorMessage := C.GoString(errBuffer)
-	defer C.string_free(errBuffer)
+// GetFacetCounts returns the facet counts requested with SearchContextBuilder.SetFacetCounts.
+//
+// Returns:
+// - The facet counts, or nil if no facet was requested.
+// - An error if there was an issue getting the counts.
+func (r *SearchResult) GetFacetCounts() (FacetCounts, error) {
+	var errBuffer C.ErrorBuffer
+	ptr := C.search_result_get_facet_counts(r.ptr, &errBuffer)
+	if ptr == nil {
+		return nil, tryExtractError(errBuffer)
+	}
+	defer C.string_free(ptr)
 
-	if errorMessage == "" {
-		return uint64(size), nil
-	} else {
-		return uint64(0), fmt.Errorf(errorMessage)
+	var counts FacetCounts
+	if err := json.Unmarshal([]byte(C.GoString(ptr)), &counts); err != nil {
+		return nil, err
 	}
+	return counts, nil
 }
 
 func (r *SearchResult) Free() {
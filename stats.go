@@ -0,0 +1,53 @@
+package tantivy_go
+
+// #include "bindings.h"
+import "C"
+import (
+	"encoding/json"
+	"fmt"
+)
+
+// IndexStats describes the searchable segments of an index. Sizes are in bytes.
+type IndexStats struct {
+	NumDocs        uint64         `json:"num_docs"`
+	NumDeletedDocs uint64         `json:"num_deleted_docs"`
+	NumSegments    int            `json:"num_segments"`
+	Segments       []SegmentStats `json:"segments"`
+	// CommitOpstamp is the opstamp of the last commit on disk. Unlike the other statistics it
+	// may be ahead of the searchable segments while the reader has not loaded that commit yet,
+	// see ReaderReload.
+	CommitOpstamp uint64 `json:"commit_opstamp"`
+	TotalBytes    uint64 `json:"total_bytes"`
+	// FieldBytes is the size of the indexed, positions, fast field and norm data of every field, by field name.
+	FieldBytes map[string]uint64 `json:"field_bytes"`
+	// StoreBytes is the size of the stored documents.
+	StoreBytes uint64 `json:"store_bytes"`
+}
+
+// SegmentStats describes a searchable segment.
+type SegmentStats struct {
+	Id             string `json:"id"`
+	NumDocs        uint32 `json:"num_docs"`
+	NumDeletedDocs uint32 `json:"num_deleted_docs"`
+	TotalBytes     uint64 `json:"total_bytes"`
+}
+
+// Stats returns statistics of the index, for example to decide when to ForceMerge or PurgeDeletes.
+//
+// Returns:
+//   - *IndexStats: The document counts, segments and space usage of the index.
+//   - error: An error if reading the statistics fails.
+func (tc *TantivyContext) Stats() (*IndexStats, error) {
+	var errBuffer C.ErrorBuffer
+	cStats := C.context_stats(tc.ptr, &errBuffer)
+	if cStats == nil {
+		return nil, tryExtractError(errBuffer)
+	}
+	defer C.string_free(cStats)
+
+	var stats IndexStats
+	if err := json.Unmarshal([]byte(C.GoString(cStats)), &stats); err != nil {
+		return nil, fmt.Errorf("failed to parse index stats: %w", err)
+	}
+	return &stats, nil
+}
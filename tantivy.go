@@ -63,6 +63,8 @@ const TokenizerRaw = "raw"
 var doOnce sync.Once
 
 // LibInit initializes the library with an optional directive.
+// cleanOnPanic and utf8Lenient are the defaults of the contexts created afterwards,
+// see ContextOptions.ClearOnPanic and ContextOptions.Utf8Lenient.
 //
 // Parameters:
 //   - directive: A variadic parameter that allows specifying an initialization directive.
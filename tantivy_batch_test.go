@@ -5,6 +5,7 @@ import (
 	"os"
 	"path/filepath"
 	"testing"
+	"time"
 
 	"github.com/stretchr/testify/require"
 )
@@ -322,3 +323,556 @@ func TestBatchDeleteAndAddSameID(t *testing.T) {
 		results.Free()
 	})
 }
+
+func TestStagedOperations(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-staged-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, false, IndexRecordOptionBasic, "simple"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	newDoc := func(id string) *Document {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(id, index, "id"))
+		require.NoError(t, doc.AddField(fmt.Sprintf("document %s", id), index, "body"))
+		return doc
+	}
+	numDocs := func() uint64 {
+		n, err := index.NumDocs()
+		require.NoError(t, err)
+		return n
+	}
+
+	t.Run("staged documents are searchable after commit", func(t *testing.T) {
+		first, err := index.AddDocuments(newDoc("1"))
+		require.NoError(t, err)
+		second, err := index.AddDocuments(newDoc("2"), newDoc("3"))
+		require.NoError(t, err)
+		require.Greater(t, second, first)
+		require.Equal(t, uint64(0), numDocs())
+
+		opstamp, err := index.Commit()
+		require.NoError(t, err)
+		require.Greater(t, opstamp, second)
+		require.Equal(t, uint64(3), numDocs())
+	})
+
+	t.Run("staged deletions apply on commit", func(t *testing.T) {
+		_, err := index.DeleteTerms("id", "1", "2")
+		require.NoError(t, err)
+		require.Equal(t, uint64(3), numDocs())
+
+		_, err = index.Commit()
+		require.NoError(t, err)
+		require.Equal(t, uint64(1), numDocs())
+	})
+
+	t.Run("rollback drops staged operations", func(t *testing.T) {
+		_, err := index.AddDocuments(newDoc("4"))
+		require.NoError(t, err)
+		_, err = index.DeleteTerms("id", "3")
+		require.NoError(t, err)
+
+		_, err = index.Rollback()
+		require.NoError(t, err)
+		_, err = index.Commit()
+		require.NoError(t, err)
+		require.Equal(t, uint64(1), numDocs())
+
+		_, err = index.AddDocuments(newDoc("5"))
+		require.NoError(t, err)
+		_, err = index.Commit()
+		require.NoError(t, err)
+		require.Equal(t, uint64(2), numDocs())
+	})
+
+	t.Run("err - unknown field", func(t *testing.T) {
+		_, err := index.DeleteTerms("unknown", "1")
+		require.Error(t, err)
+	})
+}
+
+func TestDeleteByQuery(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-delete-by-query-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, false, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("space", true, false, false, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddDateField("modified", true, true, true))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+
+	now := time.Date(2024, 5, 10, 0, 0, 0, 0, time.UTC)
+	addDocs := func(ids ...int) {
+		var docs []*Document
+		for _, i := range ids {
+			doc := NewDocument()
+			require.NoError(t, doc.AddField(fmt.Sprint(i), index, "id"))
+			require.NoError(t, doc.AddField(fmt.Sprintf("space%d", i%2), index, "space"))
+			require.NoError(t, doc.AddDateField(now.AddDate(0, 0, -i), index, "modified"))
+			docs = append(docs, doc)
+		}
+		require.NoError(t, index.AddAndConsumeDocuments(docs...))
+	}
+	numDocs := func() uint64 {
+		n, err := index.NumDocs()
+		require.NoError(t, err)
+		return n
+	}
+
+	addDocs(0, 1, 2, 3, 4, 5, 6, 7)
+
+	t.Run("delete everything in a space", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("space1").
+			AddFieldDefaultWeight("space").
+			Build()
+		_, err := index.DeleteByQuery(sCtx)
+		require.NoError(t, err)
+		require.Equal(t, uint64(4), numDocs())
+	})
+
+	t.Run("delete everything older than a date", func(t *testing.T) {
+		finalQuery := NewQueryBuilder().
+			RangeQuery(Must, "modified", RangeDate, nil, Exclusive(now.AddDate(0, 0, -3)), 1.0).
+			Build()
+		sCtx := NewSearchContextBuilder().SetQueryFromJson(&finalQuery).Build()
+		_, err := index.DeleteByQueryJsonWithPayload(sCtx, "checkpoint")
+		require.NoError(t, err)
+		require.Equal(t, uint64(2), numDocs())
+
+		payload, err := index.LastCommitPayload()
+		require.NoError(t, err)
+		require.Equal(t, "checkpoint", payload)
+	})
+
+	t.Run("delete all keeps the index usable", func(t *testing.T) {
+		_, err := index.DeleteAll()
+		require.NoError(t, err)
+		require.Equal(t, uint64(0), numDocs())
+
+		addDocs(8)
+		require.Equal(t, uint64(1), numDocs())
+	})
+
+	t.Run("err - invalid query", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().SetQuery("{").Build()
+		_, err := index.DeleteByQueryJson(sCtx)
+		require.Error(t, err)
+	})
+}
+
+func TestUpsertDocuments(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-upsert-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+	require.NoError(t, builder.AddU64Field("version", true, true, true))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	newDoc := func(id string, version uint64) *Document {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(id, index, "id"))
+		require.NoError(t, doc.AddField(fmt.Sprintf("%s version %d", id, version), index, "body"))
+		require.NoError(t, doc.AddU64Field(version, index, "version"))
+		return doc
+	}
+	versionsOf := func(index *TantivyContext) map[string]uint64 {
+		finalQuery := NewQueryBuilder().AllQuery(Must, 1.0).Build()
+		sCtx := NewSearchContextBuilder().SetQueryFromJson(&finalQuery).SetDocsLimit(100).Build()
+		result, err := index.SearchFastFieldsJson(sCtx, "id", "version")
+		require.NoError(t, err)
+		byId := map[string]uint64{}
+		for _, row := range result.Rows {
+			byId[row[0].(string)] = row[1].(uint64)
+		}
+		return byId
+	}
+	versions := func() map[string]uint64 {
+		return versionsOf(index)
+	}
+
+	t.Run("replaces documents by their own id", func(t *testing.T) {
+		_, err := index.UpsertDocuments("id", newDoc("a", 1), newDoc("b", 1))
+		require.NoError(t, err)
+		_, err = index.UpsertDocuments("id", newDoc("a", 2), newDoc("c", 1))
+		require.NoError(t, err)
+		require.Equal(t, map[string]uint64{"a": 2, "b": 1, "c": 1}, versions())
+	})
+
+	t.Run("skips documents that are not newer", func(t *testing.T) {
+		_, err := index.UpsertNewerDocuments("id", "version", newDoc("a", 1), newDoc("b", 3), newDoc("c", 1))
+		require.NoError(t, err)
+		require.Equal(t, map[string]uint64{"a": 2, "b": 3, "c": 1}, versions())
+	})
+
+	t.Run("newest document of an id in the batch wins", func(t *testing.T) {
+		_, err := index.UpsertDocumentsWithPayload("id", "version", "checkpoint", newDoc("c", 5), newDoc("c", 4))
+		require.NoError(t, err)
+		require.Equal(t, map[string]uint64{"a": 2, "b": 3, "c": 5}, versions())
+
+		payload, err := index.LastCommitPayload()
+		require.NoError(t, err)
+		require.Equal(t, "checkpoint", payload)
+	})
+
+	t.Run("compares versions against the last commit with manual reload", func(t *testing.T) {
+		manual, err := NewTantivyContextWithOptions(filepath.Join(tempDir, "manual"), schema, &ContextOptions{ReaderReload: ReloadManual})
+		require.NoError(t, err)
+		defer manual.Free()
+		require.NoError(t, manual.RegisterTextAnalyzerRaw("raw"))
+		require.NoError(t, manual.RegisterTextAnalyzerSimple("simple", 100, English))
+
+		_, err = manual.UpsertNewerDocuments("id", "version", newDoc("a", 2))
+		require.NoError(t, err)
+		_, err = manual.UpsertNewerDocuments("id", "version", newDoc("a", 1))
+		require.NoError(t, err)
+		require.Empty(t, versionsOf(manual))
+
+		require.NoError(t, manual.ReloadReader())
+		require.Equal(t, map[string]uint64{"a": 2}, versionsOf(manual))
+	})
+
+	t.Run("err - document without id", func(t *testing.T) {
+		doc := NewDocument()
+		require.NoError(t, doc.AddU64Field(1, index, "version"))
+		_, err := index.UpsertDocuments("id", doc)
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "has no value for the id field")
+	})
+
+	t.Run("err - version field that is not fast", func(t *testing.T) {
+		_, err := index.UpsertNewerDocuments("id", "body", newDoc("a", 9))
+		require.Error(t, err)
+	})
+
+	t.Run("err - unknown field id", func(t *testing.T) {
+		// a field id the index does not have, as sent by a stale schema
+		schema.fieldNames["bogus"] = 42
+		defer delete(schema.fieldNames, "bogus")
+
+		_, err := index.UpsertDocuments("bogus", newDoc("a", 9))
+		var tantivyErr *Error
+		require.ErrorAs(t, err, &tantivyErr)
+		require.Equal(t, ErrUnknownField, tantivyErr.Code)
+
+		_, err = index.UpsertNewerDocuments("id", "bogus", newDoc("a", 9))
+		require.ErrorAs(t, err, &tantivyErr)
+		require.Equal(t, ErrUnknownField, tantivyErr.Code)
+	})
+
+	t.Run("id of a tokenized field matches as indexed", func(t *testing.T) {
+		before, err := index.NumDocs()
+		require.NoError(t, err)
+
+		_, err = index.UpsertDocuments("body", newDoc("d", 1))
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "must be a single token of the id field")
+
+		bodyDoc := func(body string) *Document {
+			doc := NewDocument()
+			require.NoError(t, doc.AddField("e", index, "id"))
+			require.NoError(t, doc.AddField(body, index, "body"))
+			return doc
+		}
+		_, err = index.UpsertDocuments("body", bodyDoc("MixedCase"))
+		require.NoError(t, err)
+		_, err = index.UpsertDocuments("body", bodyDoc("MIXEDCASE"))
+		require.NoError(t, err)
+
+		after, err := index.NumDocs()
+		require.NoError(t, err)
+		require.Equal(t, before+1, after)
+	})
+}
+
+func TestIndexingPolicy(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-indexing-policy-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddU64Field("version", true, true, true))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+
+	docs := func() []*Document {
+		valid1 := NewDocument()
+		require.NoError(t, valid1.AddField("1", index, "id"))
+		require.NoError(t, valid1.AddU64Field(1, index, "version"))
+		invalid := NewDocument()
+		require.NoError(t, invalid.AddField("2", index, "id"))
+		require.NoError(t, invalid.AddField("not a number", index, "version"))
+		valid2 := NewDocument()
+		require.NoError(t, valid2.AddField("3", index, "id"))
+		require.NoError(t, valid2.AddU64Field(3, index, "version"))
+		return []*Document{valid1, invalid, valid2}
+	}
+
+	t.Run("fail fast rejects the whole batch", func(t *testing.T) {
+		_, failures, err := index.AddAndConsumeDocumentsWithPolicy(FailFast, "", docs()...)
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "Failed to add document 1")
+		require.Nil(t, failures)
+
+		numDocs, err := index.NumDocs()
+		require.NoError(t, err)
+		require.Equal(t, uint64(0), numDocs)
+	})
+
+	t.Run("best effort indexes the valid documents", func(t *testing.T) {
+		_, failures, err := index.AddAndConsumeDocumentsWithPolicy(BestEffort, "", docs()...)
+		require.NoError(t, err)
+		require.Len(t, failures, 1)
+		require.Equal(t, 1, failures[0].Index)
+		require.Contains(t, failures[0].Error, "version")
+
+		numDocs, err := index.NumDocs()
+		require.NoError(t, err)
+		require.Equal(t, uint64(2), numDocs)
+	})
+}
+
+func TestIndexWriterOptions(t *testing.T) {
+	tempDir := t.TempDir()
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	t.Run("custom budget, threads and merge policy", func(t *testing.T) {
+		index, err := NewTantivyContextWithOptions(filepath.Join(tempDir, "custom"), schema, &ContextOptions{
+			MemoryBudgetBytes: 20_000_000,
+			NumThreads:        1,
+			MergePolicy: &MergePolicy{
+				MinNumSegments:          4,
+				MaxDocsBeforeMerge:      100_000,
+				LevelLogSize:            0.5,
+				DelDocsRatioBeforeMerge: 0.5,
+			},
+		})
+		require.NoError(t, err)
+		defer index.Free()
+
+		require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+
+		doc := NewDocument()
+		require.NoError(t, doc.AddField("1", index, "id"))
+		require.NoError(t, index.AddAndConsumeDocuments(doc))
+
+		numDocs, err := index.NumDocs()
+		require.NoError(t, err)
+		require.Equal(t, uint64(1), numDocs)
+
+		require.NoError(t, index.SetMergePolicy(MergePolicy{MinNumSegments: 2}))
+		require.Error(t, index.SetMergePolicy(MergePolicy{DelDocsRatioBeforeMerge: 2}))
+	})
+
+	t.Run("budget too small for the threads", func(t *testing.T) {
+		_, err := NewTantivyContextWithOptions(filepath.Join(tempDir, "small"), schema, &ContextOptions{
+			MemoryBudgetBytes: 1_000_000,
+			NumThreads:        2,
+		})
+		require.Error(t, err)
+	})
+}
+
+func TestContextSettings(t *testing.T) {
+	tempDir := t.TempDir()
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	lenient, strict := true, false
+	lenientIndex, err := NewTantivyContextWithOptions(filepath.Join(tempDir, "lenient"), schema, &ContextOptions{
+		Utf8Lenient: &lenient,
+	})
+	require.NoError(t, err)
+	defer lenientIndex.Free()
+
+	strictIndex, err := NewTantivyContextWithOptions(filepath.Join(tempDir, "strict"), schema, &ContextOptions{
+		Utf8Lenient: &strict,
+	})
+	require.NoError(t, err)
+	defer strictIndex.Free()
+
+	require.NoError(t, lenientIndex.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, strictIndex.RegisterTextAnalyzerRaw("raw"))
+
+	invalidUtf8 := string([]byte{0x68, 0x65, 0x6c, 0x6c, 0x6f, 0xff})
+
+	// Opening the strict index after the lenient one keeps the lenient one lenient
+	doc := NewDocument()
+	require.NoError(t, doc.AddField(invalidUtf8, lenientIndex, "id"))
+	require.NoError(t, lenientIndex.AddAndConsumeDocuments(doc))
+
+	doc = NewDocument()
+	defer doc.Free()
+	require.Error(t, doc.AddField(invalidUtf8, strictIndex, "id"))
+
+	numDocs, err := lenientIndex.NumDocs()
+	require.NoError(t, err)
+	require.Equal(t, uint64(1), numDocs)
+}
+
+func TestMerging(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-merging-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.PauseMerging())
+
+	// Every commit creates a segment while merging is paused
+	for i := 0; i < 4; i++ {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(fmt.Sprintf("%d", i), index, "id"))
+		require.NoError(t, index.AddAndConsumeDocuments(doc))
+	}
+
+	t.Run("force merge down to the target segments", func(t *testing.T) {
+		numSegments, err := index.ForceMerge(2)
+		require.NoError(t, err)
+		require.Equal(t, uint64(2), numSegments)
+
+		numSegments, err = index.ForceMerge(1)
+		require.NoError(t, err)
+		require.Equal(t, uint64(1), numSegments)
+
+		numDocs, err := index.NumDocs()
+		require.NoError(t, err)
+		require.Equal(t, uint64(4), numDocs)
+	})
+
+	t.Run("purge deletes rewrites segments with deleted documents", func(t *testing.T) {
+		require.NoError(t, index.DeleteDocuments("id", "0", "1"))
+
+		purged, err := index.PurgeDeletes(0.5)
+		require.NoError(t, err)
+		require.Equal(t, uint64(1), purged)
+
+		purged, err = index.PurgeDeletes(0.5)
+		require.NoError(t, err)
+		require.Equal(t, uint64(0), purged)
+
+		_, err = index.PurgeDeletes(0)
+		require.Error(t, err)
+	})
+
+	require.NoError(t, index.ResumeMerging())
+}
+
+func TestStats(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-stats-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "raw"))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.PauseMerging())
+
+	newDoc := func(id string) *Document {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(id, index, "id"))
+		require.NoError(t, doc.AddField("some body", index, "body"))
+		return doc
+	}
+	// Two commits, two segments
+	require.NoError(t, index.AddAndConsumeDocuments(newDoc("1"), newDoc("2")))
+	require.NoError(t, index.AddAndConsumeDocuments(newDoc("3")))
+	opstamp, err := index.DeleteDocumentsWithOpstamp("id", "1")
+	require.NoError(t, err)
+
+	stats, err := index.Stats()
+	require.NoError(t, err)
+	require.Equal(t, uint64(2), stats.NumDocs)
+	require.Equal(t, uint64(1), stats.NumDeletedDocs)
+	require.Equal(t, 2, stats.NumSegments)
+	require.Len(t, stats.Segments, 2)
+	require.Equal(t, opstamp, stats.CommitOpstamp)
+	require.Greater(t, stats.TotalBytes, uint64(0))
+	require.Greater(t, stats.StoreBytes, uint64(0))
+	require.Greater(t, stats.FieldBytes["id"], uint64(0))
+	require.Greater(t, stats.FieldBytes["body"], uint64(0))
+}
@@ -0,0 +1,270 @@
+package tantivy_go
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestTypedFields(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-typed-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	err = builder.AddTextField("id", true, false, false, IndexRecordOptionBasic, "raw")
+	require.NoError(t, err)
+	err = builder.AddI64Field("delta", true, true, false)
+	require.NoError(t, err)
+	err = builder.AddU64Field("size", true, true, true)
+	require.NoError(t, err)
+	err = builder.AddF64Field("rank", true, false, true)
+	require.NoError(t, err)
+	err = builder.AddBoolField("archived", true, true, false)
+	require.NoError(t, err)
+	err = builder.AddDateField("modified", true, true, true)
+	require.NoError(t, err)
+
+	err = builder.AddU64Field("size", true, true, true)
+	require.Error(t, err)
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	err = index.RegisterTextAnalyzerRaw("raw")
+	require.NoError(t, err)
+
+	modified := time.Date(2024, 5, 1, 12, 30, 0, 0, time.UTC)
+
+	doc := NewDocument()
+	require.NoError(t, doc.AddField("1", index, "id"))
+	require.NoError(t, doc.AddI64Field(-42, index, "delta"))
+	require.NoError(t, doc.AddU64Field(1024, index, "size"))
+	require.NoError(t, doc.AddF64Field(0.5, index, "rank"))
+	require.NoError(t, doc.AddBoolField(true, index, "archived"))
+	require.NoError(t, doc.AddDateField(modified, index, "modified"))
+	require.Error(t, doc.AddU64Field(1, index, "unknown"))
+
+	err = index.AddAndConsumeDocuments(doc)
+	require.NoError(t, err)
+
+	qb := NewQueryBuilder()
+	finalQuery := qb.AllQuery(Must, 1.0).Build()
+	sCtx := NewSearchContextBuilder().
+		SetQueryFromJson(&finalQuery).
+		SetDocsLimit(10).
+		SetWithHighlights(true).
+		Build()
+
+	result, err := index.SearchJson(sCtx)
+	require.NoError(t, err)
+
+	type typedDoc struct {
+		Id       string  `json:"id"`
+		Delta    int64   `json:"delta"`
+		Size     uint64  `json:"size"`
+		Rank     float64 `json:"rank"`
+		Archived bool    `json:"archived"`
+		Modified int64   `json:"modified"`
+	}
+
+	docs, err := GetSearchResults(result, index, func(jsonStr string) (typedDoc, error) {
+		var d typedDoc
+		return d, json.Unmarshal([]byte(jsonStr), &d)
+	}, "id", "delta", "size", "rank", "archived", "modified")
+	require.NoError(t, err)
+	require.Equal(t, []typedDoc{{
+		Id:       "1",
+		Delta:    -42,
+		Size:     1024,
+		Rank:     0.5,
+		Archived: true,
+		Modified: modified.UnixNano(),
+	}}, docs)
+}
+
+func TestDateFieldPrecision(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-date-precision-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddDateField("seconds", true, true, true))
+	require.NoError(t, builder.AddDateFieldWithPrecision("millis", true, true, true, DatePrecisionMilliseconds))
+	require.Error(t, builder.AddDateFieldWithPrecision("unknown", true, true, true, DatePrecision(42)))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	modified := time.Date(2024, 5, 1, 12, 30, 15, 123456789, time.UTC)
+
+	doc := NewDocument()
+	require.NoError(t, doc.AddDateField(modified, index, "seconds"))
+	require.NoError(t, doc.AddDateField(modified, index, "millis"))
+	require.NoError(t, index.AddAndConsumeDocuments(doc))
+
+	qb := NewQueryBuilder()
+	finalQuery := qb.AllQuery(Must, 1.0).Build()
+	sCtx := NewSearchContextBuilder().
+		SetQueryFromJson(&finalQuery).
+		SetDocsLimit(10).
+		Build()
+
+	values, err := index.SearchFastFieldsJson(sCtx, "seconds", "millis")
+	require.NoError(t, err)
+	require.Len(t, values.Rows, 1)
+	require.True(t, modified.Truncate(time.Second).Equal(values.Rows[0][0].(time.Time)))
+	require.True(t, modified.Truncate(time.Millisecond).Equal(values.Rows[0][1].(time.Time)))
+}
+
+func TestJsonField(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-json-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	err = builder.AddTextField("id", true, false, false, IndexRecordOptionBasic, "raw")
+	require.NoError(t, err)
+	err = builder.AddJsonField("relations", true, false, false, IndexRecordOptionWithFreqsAndPositions, "simple")
+	require.NoError(t, err)
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	doc1 := NewDocument()
+	require.NoError(t, doc1.AddField("1", index, "id"))
+	require.NoError(t, doc1.AddJsonField(`{"status":"done","tags":["a","b"],"meta":{"priority":3}}`, index, "relations"))
+
+	doc2 := NewDocument()
+	require.NoError(t, doc2.AddField("2", index, "id"))
+	require.NoError(t, doc2.AddJsonField(`{"status":"open"}`, index, "relations"))
+
+	require.Error(t, NewDocument().AddJsonField(`["not", "an", "object"]`, index, "relations"))
+
+	err = index.AddAndConsumeDocuments(doc1, doc2)
+	require.NoError(t, err)
+
+	finalQuery := NewQueryBuilder().
+		Query(Must, "relations.status", "done", TermQuery, 1.0).
+		Build()
+	sCtx := NewSearchContextBuilder().
+		SetQueryFromJson(&finalQuery).
+		SetDocsLimit(10).
+		Build()
+
+	result, err := index.SearchJson(sCtx)
+	require.NoError(t, err)
+
+	docs, err := GetSearchResults(result, index, func(jsonStr string) (map[string]any, error) {
+		var d map[string]any
+		return d, json.Unmarshal([]byte(jsonStr), &d)
+	}, "id", "relations")
+	require.NoError(t, err)
+	require.Len(t, docs, 1)
+	require.Equal(t, "1", docs[0]["id"])
+	require.Equal(t, map[string]any{
+		"status": "done",
+		"tags":   []any{"a", "b"},
+		"meta":   map[string]any{"priority": float64(3)},
+	}, docs[0]["relations"])
+}
+
+func TestRangeQuery(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-range-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, false, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+	require.NoError(t, builder.AddU64Field("size", true, true, true))
+	require.NoError(t, builder.AddDateField("modified", true, true, true))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	now := time.Date(2024, 5, 10, 0, 0, 0, 0, time.UTC)
+	var docs []*Document
+	for i, age := range []int{1, 3, 10, 30} {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(fmt.Sprint(i), index, "id"))
+		require.NoError(t, doc.AddField("report", index, "body"))
+		require.NoError(t, doc.AddU64Field(uint64(age*100), index, "size"))
+		require.NoError(t, doc.AddDateField(now.AddDate(0, 0, -age), index, "modified"))
+		docs = append(docs, doc)
+	}
+	require.NoError(t, index.AddAndConsumeDocuments(docs...))
+
+	search := func(qb *QueryBuilder) []string {
+		finalQuery := qb.Build()
+		sCtx := NewSearchContextBuilder().
+			SetQueryFromJson(&finalQuery).
+			SetDocsLimit(10).
+			Build()
+		result, err := index.SearchJson(sCtx)
+		require.NoError(t, err)
+		ids, err := GetSearchResults(result, index, func(jsonStr string) (string, error) {
+			var d struct {
+				Id string `json:"id"`
+			}
+			return d.Id, json.Unmarshal([]byte(jsonStr), &d)
+		}, "id")
+		require.NoError(t, err)
+		return ids
+	}
+
+	t.Run("modified in the last 7 days", func(t *testing.T) {
+		ids := search(NewQueryBuilder().
+			Query(Must, "body", "report", TermQuery, 1.0).
+			RangeQuery(Must, "modified", RangeDate, Inclusive(now.AddDate(0, 0, -7)), nil, 1.0))
+		require.ElementsMatch(t, []string{"0", "1"}, ids)
+	})
+
+	t.Run("size between bounds", func(t *testing.T) {
+		ids := search(NewQueryBuilder().
+			RangeQuery(Must, "size", RangeU64, Inclusive(300), Exclusive(3000), 1.0))
+		require.ElementsMatch(t, []string{"1", "2"}, ids)
+	})
+
+	t.Run("err - when no bounds", func(t *testing.T) {
+		finalQuery := NewQueryBuilder().RangeQuery(Must, "size", RangeU64, nil, nil, 1.0).Build()
+		sCtx := NewSearchContextBuilder().SetQueryFromJson(&finalQuery).SetDocsLimit(10).Build()
+		_, err := index.SearchJson(sCtx)
+		require.Error(t, err)
+	})
+}
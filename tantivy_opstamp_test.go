@@ -269,6 +269,64 @@ func TestOpstampPersistence(t *testing.T) {
 }
 
 // copyDir recursively copies a directory
+func TestCommitPayload(t *testing.T) {
+	indexDir := t.TempDir()
+
+	newSchema := func() *Schema {
+		builder, err := NewSchemaBuilder()
+		require.NoError(t, err)
+		require.NoError(t, builder.AddTextField("id", true, false, false, IndexRecordOptionBasic, DefaultTokenizer))
+		schema, err := builder.BuildSchema()
+		require.NoError(t, err)
+		return schema
+	}
+	newDoc := func(ctx *TantivyContext, id string) *Document {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(id, ctx, "id"))
+		return doc
+	}
+
+	ctx, err := NewTantivyContextWithSchema(indexDir, newSchema())
+	require.NoError(t, err)
+
+	payload, err := ctx.LastCommitPayload()
+	require.NoError(t, err)
+	assert.Equal(t, "", payload)
+
+	_, err = ctx.AddAndConsumeDocumentsWithPayload("cursor-1", newDoc(ctx, "doc_1"))
+	require.NoError(t, err)
+	payload, err = ctx.LastCommitPayload()
+	require.NoError(t, err)
+	assert.Equal(t, "cursor-1", payload)
+
+	_, err = ctx.DeleteDocumentsWithPayload("cursor-2", "id", "doc_1")
+	require.NoError(t, err)
+	payload, err = ctx.LastCommitPayload()
+	require.NoError(t, err)
+	assert.Equal(t, "cursor-2", payload)
+
+	_, err = ctx.AddDocuments(newDoc(ctx, "doc_2"))
+	require.NoError(t, err)
+	_, err = ctx.CommitWithPayload(`{"cursor":3}`)
+	require.NoError(t, err)
+	ctx.Free()
+
+	// The payload is read back from the index meta after reopening
+	ctx, err = NewTantivyContextWithSchema(indexDir, newSchema())
+	require.NoError(t, err)
+	defer ctx.Free()
+
+	payload, err = ctx.LastCommitPayload()
+	require.NoError(t, err)
+	assert.Equal(t, `{"cursor":3}`, payload)
+
+	_, err = ctx.BatchAddAndDeleteDocumentsWithOpstamp([]*Document{newDoc(ctx, "doc_3")}, "id", []string{"doc_2"})
+	require.NoError(t, err)
+	payload, err = ctx.LastCommitPayload()
+	require.NoError(t, err)
+	assert.Equal(t, "", payload)
+}
+
 func copyDir(src, dst string) error {
 	return filepath.Walk(src, func(path string, info os.FileInfo, err error) error {
 		if err != nil {
@@ -0,0 +1,152 @@
+package tantivy_go
+
+import (
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func newQueryTestIndex(t *testing.T, bodies ...string) *TantivyContext {
+	indexPath := filepath.Join(t.TempDir(), "test-query-index")
+	t.Cleanup(func() { os.RemoveAll(indexPath) })
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, false, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+	require.NoError(t, builder.AddTextField("name", true, true, false, IndexRecordOptionBasic, "raw"))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	t.Cleanup(index.Free)
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	var docs []*Document
+	for i, body := range bodies {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(string(rune('a'+i)), index, "id"))
+		require.NoError(t, doc.AddField(body, index, "body"))
+		require.NoError(t, doc.AddField(body, index, "name"))
+		docs = append(docs, doc)
+	}
+	require.NoError(t, index.AddAndConsumeDocuments(docs...))
+	return index
+}
+
+func searchIds(t *testing.T, index *TantivyContext, qb *QueryBuilder) ([]string, error) {
+	finalQuery := qb.Build()
+	sCtx := NewSearchContextBuilder().
+		SetQueryFromJson(&finalQuery).
+		SetDocsLimit(10).
+		Build()
+	result, err := index.SearchJson(sCtx)
+	if err != nil {
+		return nil, err
+	}
+	return GetSearchResults(result, index, func(jsonStr string) (string, error) {
+		var d struct {
+			Id string `json:"id"`
+		}
+		return d.Id, json.Unmarshal([]byte(jsonStr), &d)
+	}, "id")
+}
+
+func TestFuzzyQuery(t *testing.T) {
+	index := newQueryTestIndex(t,
+		"quarterly report",
+		"annual review",
+		"reprot draft",
+	)
+
+	t.Run("single typo", func(t *testing.T) {
+		ids, err := searchIds(t, index, NewQueryBuilder().
+			FuzzyQuery(Must, "body", "reviw", FuzzyTermQuery, 1, true, 1.0))
+		require.NoError(t, err)
+		require.ElementsMatch(t, []string{"b"}, ids)
+	})
+
+	t.Run("transposition counts as one edit", func(t *testing.T) {
+		ids, err := searchIds(t, index, NewQueryBuilder().
+			FuzzyQuery(Must, "body", "report", FuzzyTermQuery, 1, true, 1.0))
+		require.NoError(t, err)
+		require.ElementsMatch(t, []string{"a", "c"}, ids)
+
+		ids, err = searchIds(t, index, NewQueryBuilder().
+			FuzzyQuery(Must, "body", "report", FuzzyTermQuery, 1, false, 1.0))
+		require.NoError(t, err)
+		require.ElementsMatch(t, []string{"a"}, ids)
+	})
+
+	t.Run("every token must match", func(t *testing.T) {
+		ids, err := searchIds(t, index, NewQueryBuilder().
+			FuzzyQuery(Must, "body", "anual reviw", FuzzyTermQuery, 1, true, 1.0))
+		require.NoError(t, err)
+		require.ElementsMatch(t, []string{"b"}, ids)
+	})
+
+	t.Run("prefix", func(t *testing.T) {
+		ids, err := searchIds(t, index, NewQueryBuilder().
+			FuzzyQuery(Must, "body", "quartr", FuzzyPrefixQuery, 1, true, 1.0))
+		require.NoError(t, err)
+		require.ElementsMatch(t, []string{"a"}, ids)
+	})
+
+	t.Run("err - distance above 2", func(t *testing.T) {
+		_, err := searchIds(t, index, NewQueryBuilder().
+			FuzzyQuery(Must, "body", "report", FuzzyTermQuery, 3, true, 1.0))
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "Invalid distance")
+	})
+}
+
+func TestPatternQuery(t *testing.T) {
+	index := newQueryTestIndex(t,
+		"2024-01 invoice.pdf",
+		"2023-12 invoice.pdf",
+		"2024-02 notes.txt",
+	)
+
+	t.Run("regex starts with", func(t *testing.T) {
+		ids, err := searchIds(t, index, NewQueryBuilder().
+			Query(Must, "name", "2024-.*", RegexQuery, 1.0))
+		require.NoError(t, err)
+		require.ElementsMatch(t, []string{"a", "c"}, ids)
+	})
+
+	t.Run("wildcard ends with", func(t *testing.T) {
+		ids, err := searchIds(t, index, NewQueryBuilder().
+			Query(Must, "name", "*.pdf", WildcardQuery, 1.0))
+		require.NoError(t, err)
+		require.ElementsMatch(t, []string{"a", "b"}, ids)
+	})
+
+	t.Run("wildcard single character", func(t *testing.T) {
+		ids, err := searchIds(t, index, NewQueryBuilder().
+			Query(Must, "name", "2024-0? *", WildcardQuery, 1.0))
+		require.NoError(t, err)
+		require.ElementsMatch(t, []string{"a", "c"}, ids)
+	})
+
+	t.Run("err - invalid pattern", func(t *testing.T) {
+		_, err := searchIds(t, index, NewQueryBuilder().
+			Query(Must, "name", "invoice(", RegexQuery, 1.0))
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "Invalid regex pattern")
+	})
+
+	t.Run("err - too expensive pattern", func(t *testing.T) {
+		_, err := searchIds(t, index, NewQueryBuilder().
+			Query(Must, "name", "(a|b)*a(a|b){20}", RegexQuery, 1.0))
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "Regex pattern is too expensive")
+	})
+}
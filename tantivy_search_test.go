@@ -0,0 +1,1167 @@
+package tantivy_go
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestSortedSearch(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-sort-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+	require.NoError(t, builder.AddU64Field("rank", true, true, true))
+	require.NoError(t, builder.AddDateField("modified", true, true, true))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	now := time.Date(2024, 5, 10, 0, 0, 0, 0, time.UTC)
+	var docs []*Document
+	for _, d := range []struct {
+		id      string
+		body    string
+		rank    uint64
+		daysAgo int
+	}{
+		{"a", "report alpha", 1, 3},
+		{"b", "report beta report", 2, 1},
+		{"c", "report gamma", 2, 3},
+		{"d", "report delta", 1, -1},
+	} {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(d.id, index, "id"))
+		require.NoError(t, doc.AddField(d.body, index, "body"))
+		require.NoError(t, doc.AddU64Field(d.rank, index, "rank"))
+		if d.daysAgo >= 0 {
+			require.NoError(t, doc.AddDateField(now.AddDate(0, 0, -d.daysAgo), index, "modified"))
+		}
+		docs = append(docs, doc)
+	}
+	require.NoError(t, index.AddAndConsumeDocuments(docs...))
+
+	ids := func(result *SearchResult, err error) []string {
+		require.NoError(t, err)
+		ids, err := GetSearchResults(result, index, func(jsonStr string) (string, error) {
+			var d struct {
+				Id string `json:"id"`
+			}
+			return d.Id, json.Unmarshal([]byte(jsonStr), &d)
+		}, "id")
+		require.NoError(t, err)
+		return ids
+	}
+
+	t.Run("modified desc then score, missing values last", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(10).
+			AddSortField("modified", Desc).
+			AddSortByScore(Desc).
+			Build()
+		require.Equal(t, []string{"b", "a", "c", "d"}, ids(index.Search(sCtx)))
+	})
+
+	t.Run("json query with two sort fields", func(t *testing.T) {
+		finalQuery := NewQueryBuilder().Query(Must, "body", "report", TermQuery, 1.0).Build()
+		sCtx := NewSearchContextBuilder().
+			SetQueryFromJson(&finalQuery).
+			SetDocsLimit(10).
+			AddSortField("rank", Asc).
+			AddSortField("modified", Desc).
+			Build()
+		require.Equal(t, []string{"a", "d", "b", "c"}, ids(index.SearchJson(sCtx)))
+	})
+
+	t.Run("fast field results follow the sort", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(10).
+			AddSortField("modified", Desc).
+			Build()
+		result, err := index.SearchFastField(sCtx, "id")
+		require.NoError(t, err)
+		require.Equal(t, []string{"b", "a", "c", "d"}, result.Values)
+
+		finalQuery := NewQueryBuilder().AllQuery(Must, 1.0).Build()
+		sCtx = NewSearchContextBuilder().
+			SetQueryFromJson(&finalQuery).
+			SetDocsLimit(10).
+			AddSortField("rank", Desc).
+			AddSortField("modified", Asc).
+			Build()
+		result, err = index.SearchFastFieldJson(sCtx, "id")
+		require.NoError(t, err)
+		require.Equal(t, []string{"c", "b", "a", "d"}, result.Values)
+	})
+
+	t.Run("err - sort by a field that is not fast", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(10).
+			AddSortField("body", Asc).
+			Build()
+		_, err := index.Search(sCtx)
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "fast field to sort by")
+	})
+
+	t.Run("err - unknown sort field", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(10).
+			AddSortField("missing", Asc).
+			Build()
+		_, err := index.Search(sCtx)
+		require.Error(t, err)
+	})
+}
+
+func TestPagination(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-pagination-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddU64Field("rank", true, true, true))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+
+	// Two commits, so results span several segments
+	for _, batch := range [][]string{{"a", "b", "c", "d"}, {"e", "f", "g"}} {
+		var docs []*Document
+		for _, id := range batch {
+			doc := NewDocument()
+			require.NoError(t, doc.AddField(id, index, "id"))
+			require.NoError(t, doc.AddU64Field(uint64(id[0]%3), index, "rank"))
+			docs = append(docs, doc)
+		}
+		require.NoError(t, index.AddAndConsumeDocuments(docs...))
+	}
+
+	finalQuery := NewQueryBuilder().AllQuery(Must, 1.0).Build()
+	newBuilder := func(limit uintptr) *SearchContextBuilder {
+		return NewSearchContextBuilder().
+			SetQueryFromJson(&finalQuery).
+			SetDocsLimit(limit).
+			AddSortField("rank", Desc)
+	}
+	page := func(sCtx SearchContext) ([]string, string) {
+		result, err := index.SearchJson(sCtx)
+		require.NoError(t, err)
+		cursor, err := result.GetCursor()
+		require.NoError(t, err)
+		ids, err := GetSearchResults(result, index, func(jsonStr string) (string, error) {
+			var d struct {
+				Id string `json:"id"`
+			}
+			return d.Id, json.Unmarshal([]byte(jsonStr), &d)
+		}, "id")
+		require.NoError(t, err)
+		return ids, cursor
+	}
+
+	all, _ := page(newBuilder(10).Build())
+	require.Len(t, all, 7)
+
+	t.Run("offset", func(t *testing.T) {
+		ids, _ := page(newBuilder(3).SetOffset(2).Build())
+		require.Equal(t, all[2:5], ids)
+
+		ids, cursor := page(newBuilder(3).SetOffset(10).Build())
+		require.Empty(t, ids)
+		require.Empty(t, cursor)
+	})
+
+	t.Run("search after cursor", func(t *testing.T) {
+		var paged []string
+		cursor := ""
+		for {
+			ids, next := page(newBuilder(2).SetSearchAfter(cursor).Build())
+			if len(ids) == 0 {
+				break
+			}
+			paged = append(paged, ids...)
+			cursor = next
+		}
+		require.Equal(t, all, paged)
+	})
+
+	t.Run("fast field search after cursor", func(t *testing.T) {
+		first, err := index.SearchFastFieldJson(newBuilder(3).Build(), "id")
+		require.NoError(t, err)
+		require.Equal(t, all[:3], first.Values)
+		require.NotEmpty(t, first.Cursor)
+
+		second, err := index.SearchFastFieldJson(newBuilder(3).SetSearchAfter(first.Cursor).SetOffset(1).Build(), "id")
+		require.NoError(t, err)
+		require.Equal(t, all[4:7], second.Values)
+	})
+
+	t.Run("err - invalid cursor", func(t *testing.T) {
+		_, err := index.SearchJson(newBuilder(2).SetSearchAfter("not a cursor").Build())
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "Invalid search cursor")
+	})
+}
+
+func TestTotalHits(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-total-hits-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	var docs []*Document
+	for i := 0; i < 12; i++ {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(fmt.Sprint(i), index, "id"))
+		body := "draft"
+		if i%3 == 0 {
+			body = "final report"
+		} else if i%2 == 0 {
+			body = "draft report"
+		}
+		require.NoError(t, doc.AddField(body, index, "body"))
+		docs = append(docs, doc)
+	}
+	require.NoError(t, index.AddAndConsumeDocuments(docs...))
+
+	t.Run("search result", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(2).
+			Build()
+		result, err := index.Search(sCtx)
+		require.NoError(t, err)
+		defer result.Free()
+
+		size, err := result.GetSize()
+		require.NoError(t, err)
+		require.Equal(t, uint64(2), size)
+
+		totalHits, err := result.GetTotalHits()
+		require.NoError(t, err)
+		require.Equal(t, uint64(8), totalHits)
+	})
+
+	t.Run("fast field result", func(t *testing.T) {
+		finalQuery := NewQueryBuilder().Query(Must, "body", "draft", TermQuery, 1.0).Build()
+		sCtx := NewSearchContextBuilder().
+			SetQueryFromJson(&finalQuery).
+			SetDocsLimit(3).
+			Build()
+		result, err := index.SearchFastFieldJson(sCtx, "id")
+		require.NoError(t, err)
+		require.Len(t, result.Values, 3)
+		require.Equal(t, uint64(8), result.TotalHits)
+	})
+
+	t.Run("count", func(t *testing.T) {
+		count, err := index.Count(NewSearchContextBuilder().
+			SetQuery("final").
+			AddFieldDefaultWeight("body").
+			Build())
+		require.NoError(t, err)
+		require.Equal(t, uint64(4), count)
+
+		finalQuery := NewQueryBuilder().AllQuery(Must, 1.0).Build()
+		count, err = index.CountJson(NewSearchContextBuilder().SetQueryFromJson(&finalQuery).Build())
+		require.NoError(t, err)
+		require.Equal(t, uint64(12), count)
+	})
+
+	t.Run("err - invalid json query", func(t *testing.T) {
+		_, err := index.CountJson(NewSearchContextBuilder().SetQuery("{").Build())
+		require.Error(t, err)
+	})
+}
+
+func TestAggregate(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-aggregate-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("tag", true, true, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+	require.NoError(t, builder.AddU64Field("size", true, true, true))
+	require.NoError(t, builder.AddDateField("modified", true, true, true))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	day := time.Date(2024, 5, 10, 0, 0, 0, 0, time.UTC)
+	var docs []*Document
+	for _, d := range []struct {
+		tag     string
+		body    string
+		size    uint64
+		daysAgo int
+	}{
+		{"note", "weekly report", 10, 0},
+		{"note", "monthly report", 30, 1},
+		{"task", "fix report", 20, 1},
+		{"page", "landing", 40, 2},
+	} {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(d.tag, index, "tag"))
+		require.NoError(t, doc.AddField(d.body, index, "body"))
+		require.NoError(t, doc.AddU64Field(d.size, index, "size"))
+		require.NoError(t, doc.AddDateField(day.AddDate(0, 0, -d.daysAgo), index, "modified"))
+		docs = append(docs, doc)
+	}
+	require.NoError(t, index.AddAndConsumeDocuments(docs...))
+
+	type bucket struct {
+		Key      any    `json:"key"`
+		DocCount uint64 `json:"doc_count"`
+	}
+	var result struct {
+		Tags struct {
+			Buckets []bucket `json:"buckets"`
+		} `json:"tags"`
+		Sizes struct {
+			Count uint64  `json:"count"`
+			Sum   float64 `json:"sum"`
+			Min   float64 `json:"min"`
+			Max   float64 `json:"max"`
+		} `json:"sizes"`
+		BySize struct {
+			Buckets []bucket `json:"buckets"`
+		} `json:"by_size"`
+		ByDay struct {
+			Buckets []bucket `json:"buckets"`
+		} `json:"by_day"`
+	}
+
+	t.Run("terms and stats on matching documents", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			Build()
+		aggJson, err := index.Aggregate(sCtx, `{
+			"tags": {"terms": {"field": "tag"}},
+			"sizes": {"stats": {"field": "size"}}
+		}`)
+		require.NoError(t, err)
+		require.NoError(t, json.Unmarshal([]byte(aggJson), &result))
+
+		require.Equal(t, []bucket{{"note", 2}, {"task", 1}}, result.Tags.Buckets)
+		require.Equal(t, uint64(3), result.Sizes.Count)
+		require.Equal(t, 60.0, result.Sizes.Sum)
+		require.Equal(t, 10.0, result.Sizes.Min)
+		require.Equal(t, 30.0, result.Sizes.Max)
+	})
+
+	t.Run("histograms with a json query", func(t *testing.T) {
+		finalQuery := NewQueryBuilder().AllQuery(Must, 1.0).Build()
+		sCtx := NewSearchContextBuilder().SetQueryFromJson(&finalQuery).Build()
+		aggJson, err := index.AggregateJson(sCtx, `{
+			"by_size": {"histogram": {"field": "size", "interval": 25}},
+			"by_day": {"date_histogram": {"field": "modified", "fixed_interval": "1d"}}
+		}`)
+		require.NoError(t, err)
+		require.NoError(t, json.Unmarshal([]byte(aggJson), &result))
+
+		require.Equal(t, []bucket{{0.0, 2}, {25.0, 2}}, result.BySize.Buckets)
+		var dayCounts []uint64
+		for _, b := range result.ByDay.Buckets {
+			dayCounts = append(dayCounts, b.DocCount)
+		}
+		require.Equal(t, []uint64{1, 2, 1}, dayCounts)
+	})
+
+	t.Run("err - invalid aggregation request", func(t *testing.T) {
+		finalQuery := NewQueryBuilder().AllQuery(Must, 1.0).Build()
+		sCtx := NewSearchContextBuilder().SetQueryFromJson(&finalQuery).Build()
+		_, err := index.AggregateJson(sCtx, `{"sizes": {"median": {"field": "size"}}}`)
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "Invalid aggregation request")
+	})
+}
+
+func TestFacets(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-facet-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, false, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+	require.NoError(t, builder.AddFacetField("path", true))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	var docs []*Document
+	for _, d := range []struct {
+		id   string
+		body string
+		path string
+	}{
+		{"a", "weekly report", "/space/abc/type/note"},
+		{"b", "monthly report", "/space/abc/type/task"},
+		{"c", "yearly report", "/space/def/type/note"},
+		{"d", "landing page", "/space/abc/type/note"},
+	} {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField(d.id, index, "id"))
+		require.NoError(t, doc.AddField(d.body, index, "body"))
+		require.NoError(t, doc.AddFacetField(d.path, index, "path"))
+		docs = append(docs, doc)
+	}
+	require.NoError(t, index.AddAndConsumeDocuments(docs...))
+
+	t.Run("counts per prefix next to the top docs", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(1).
+			SetFacetCounts("path", "/space", "/space/abc/type").
+			Build()
+		result, err := index.Search(sCtx)
+		require.NoError(t, err)
+		defer result.Free()
+
+		size, err := result.GetSize()
+		require.NoError(t, err)
+		require.Equal(t, uint64(1), size)
+
+		counts, err := result.GetFacetCounts()
+		require.NoError(t, err)
+		require.Equal(t, FacetCounts{
+			"/space":          {"/space/abc": 2, "/space/def": 1},
+			"/space/abc/type": {"/space/abc/type/note": 1, "/space/abc/type/task": 1},
+		}, counts)
+	})
+
+	t.Run("no counts when not requested", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(1).
+			Build()
+		result, err := index.Search(sCtx)
+		require.NoError(t, err)
+		defer result.Free()
+
+		counts, err := result.GetFacetCounts()
+		require.NoError(t, err)
+		require.Nil(t, counts)
+	})
+
+	t.Run("facet filter narrows the search", func(t *testing.T) {
+		finalQuery := NewQueryBuilder().
+			Query(Must, "body", "report", TermQuery, 1.0).
+			Query(Must, "path", "/space/abc", FacetQuery, 1.0).
+			Build()
+		sCtx := NewSearchContextBuilder().
+			SetQueryFromJson(&finalQuery).
+			SetDocsLimit(10).
+			SetFacetCounts("path", "/space/abc/type").
+			Build()
+		result, err := index.SearchJson(sCtx)
+		require.NoError(t, err)
+		defer result.Free()
+
+		counts, err := result.GetFacetCounts()
+		require.NoError(t, err)
+		require.Equal(t, FacetCounts{
+			"/space/abc/type": {"/space/abc/type/note": 1, "/space/abc/type/task": 1},
+		}, counts)
+
+		doc, err := result.Get(0)
+		require.NoError(t, err)
+		jsonStr, err := doc.ToJson(index, "path")
+		require.NoError(t, err)
+		require.Contains(t, jsonStr, `"path":"/space/abc/type/`)
+	})
+
+	t.Run("err - invalid facet path", func(t *testing.T) {
+		doc := NewDocument()
+		require.Error(t, doc.AddFacetField("space/abc", index, "path"))
+
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(1).
+			SetFacetCounts("path", "space").
+			Build()
+		_, err := index.Search(sCtx)
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "Invalid facet path")
+	})
+
+	t.Run("err - facet counts on a non facet field", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(1).
+			SetFacetCounts("body", "/space").
+			Build()
+		_, err := index.Search(sCtx)
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "is not a facet field")
+	})
+}
+
+func TestCollapse(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-collapse-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("objectId", true, false, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+	require.NoError(t, builder.AddU64Field("version", true, true, true))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	// Every block of a page is its own document, two commits spread them over segments
+	for _, batch := range [][]struct {
+		objectId string
+		body     string
+		version  uint64
+	}{
+		{{"page1", "report report report", 1}, {"page1", "draft of the report", 1}, {"page2", "report report", 2}},
+		{{"page1", "report appendix", 1}, {"page3", "final report with many other words", 3}},
+	} {
+		var docs []*Document
+		for _, d := range batch {
+			doc := NewDocument()
+			require.NoError(t, doc.AddField(d.objectId, index, "objectId"))
+			require.NoError(t, doc.AddField(d.body, index, "body"))
+			require.NoError(t, doc.AddU64Field(d.version, index, "version"))
+			docs = append(docs, doc)
+		}
+		require.NoError(t, index.AddAndConsumeDocuments(docs...))
+	}
+
+	type collapsedDoc struct {
+		ObjectId  string `json:"objectId"`
+		Collapsed *struct {
+			Count      uint64 `json:"count"`
+			Highlights []struct {
+				FieldName string `json:"field_name"`
+			} `json:"highlights"`
+		} `json:"collapsed"`
+	}
+	search := func(sCtx SearchContext) []collapsedDoc {
+		result, err := index.Search(sCtx)
+		require.NoError(t, err)
+		docs, err := GetSearchResults(result, index, func(jsonStr string) (collapsedDoc, error) {
+			var d collapsedDoc
+			return d, json.Unmarshal([]byte(jsonStr), &d)
+		}, "objectId")
+		require.NoError(t, err)
+		return docs
+	}
+	newBuilder := func() *SearchContextBuilder {
+		return NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(10)
+	}
+
+	t.Run("one hit per object", func(t *testing.T) {
+		docs := search(newBuilder().SetCollapse("objectId", false, 0).Build())
+		var ids []string
+		for _, d := range docs {
+			ids = append(ids, d.ObjectId)
+			require.Nil(t, d.Collapsed)
+		}
+		require.Equal(t, []string{"page1", "page2", "page3"}, ids)
+	})
+
+	t.Run("siblings with highlights", func(t *testing.T) {
+		docs := search(newBuilder().
+			SetWithHighlights(true).
+			SetCollapse("objectId", true, 1).
+			Build())
+		require.Len(t, docs, 3)
+		require.Equal(t, "page1", docs[0].ObjectId)
+		require.NotNil(t, docs[0].Collapsed)
+		require.Equal(t, uint64(2), docs[0].Collapsed.Count)
+		require.Len(t, docs[0].Collapsed.Highlights, 1)
+		require.Equal(t, "body", docs[0].Collapsed.Highlights[0].FieldName)
+		for _, d := range docs[1:] {
+			require.Equal(t, uint64(0), d.Collapsed.Count)
+			require.Empty(t, d.Collapsed.Highlights)
+		}
+	})
+
+	t.Run("offset counts collapsed hits", func(t *testing.T) {
+		docs := search(newBuilder().SetDocsLimit(1).SetOffset(1).SetCollapse("objectId", false, 0).Build())
+		require.Len(t, docs, 1)
+		require.Equal(t, "page2", docs[0].ObjectId)
+	})
+
+	t.Run("fast field search by u64 field", func(t *testing.T) {
+		result, err := index.SearchFastField(newBuilder().SetCollapse("version", false, 0).Build(), "objectId")
+		require.NoError(t, err)
+		require.Equal(t, []string{"page1", "page2", "page3"}, result.Values)
+		require.Equal(t, uint64(5), result.TotalHits)
+	})
+
+	t.Run("err - collapse by a field that is not fast", func(t *testing.T) {
+		_, err := index.Search(newBuilder().SetCollapse("body", false, 0).Build())
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "fast field to collapse by")
+	})
+
+	t.Run("err - collapse with a cursor", func(t *testing.T) {
+		_, err := index.Search(newBuilder().
+			SetSearchAfter("0.0.1").
+			SetCollapse("objectId", false, 0).
+			Build())
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "can not be combined with collapse")
+	})
+
+	t.Run("hits of a segment without the collapse field are groups of their own", func(t *testing.T) {
+		var docs []*Document
+		for _, body := range []string{"loose report", "another loose report"} {
+			doc := NewDocument()
+			require.NoError(t, doc.AddField(body, index, "body"))
+			docs = append(docs, doc)
+		}
+		require.NoError(t, index.AddAndConsumeDocuments(docs...))
+
+		found := search(newBuilder().SetCollapse("objectId", true, 1).Build())
+		require.Len(t, found, 5)
+		var ungrouped int
+		for _, d := range found {
+			if d.ObjectId == "" {
+				ungrouped++
+				require.Equal(t, uint64(0), d.Collapsed.Count)
+			}
+		}
+		require.Equal(t, 2, ungrouped)
+	})
+}
+
+func TestSearchFastFields(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-fast-fields-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+	require.NoError(t, builder.AddU64Field("size", true, true, true))
+	require.NoError(t, builder.AddI64Field("delta", true, true, true))
+	require.NoError(t, builder.AddF64Field("rank", true, false, true))
+	require.NoError(t, builder.AddBoolField("archived", true, true, true))
+	require.NoError(t, builder.AddDateField("modified", true, true, true))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	modified := time.Date(2024, 5, 1, 12, 30, 0, 0, time.UTC)
+
+	full := NewDocument()
+	require.NoError(t, full.AddField("a", index, "id"))
+	require.NoError(t, full.AddField("report report", index, "body"))
+	require.NoError(t, full.AddU64Field(1024, index, "size"))
+	require.NoError(t, full.AddI64Field(-42, index, "delta"))
+	require.NoError(t, full.AddF64Field(0.5, index, "rank"))
+	require.NoError(t, full.AddBoolField(true, index, "archived"))
+	require.NoError(t, full.AddDateField(modified, index, "modified"))
+
+	sparse := NewDocument()
+	require.NoError(t, sparse.AddField("b", index, "id"))
+	require.NoError(t, sparse.AddField("report", index, "body"))
+	require.NoError(t, sparse.AddU64Field(7, index, "size"))
+
+	require.NoError(t, index.AddAndConsumeDocuments(full, sparse))
+
+	fastFields := []string{"id", "size", "delta", "rank", "archived", "modified"}
+
+	t.Run("typed values of several fields", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(10).
+			AddSortField("size", Desc).
+			Build()
+		result, err := index.SearchFastFields(sCtx, fastFields...)
+		require.NoError(t, err)
+		require.Equal(t, uint64(2), result.TotalHits)
+		require.Len(t, result.Scores, 2)
+		require.NotEmpty(t, result.Cursor)
+		require.Equal(t, [][]any{
+			{"a", uint64(1024), int64(-42), 0.5, true, modified},
+			{"b", uint64(7), nil, nil, nil, nil},
+		}, result.Rows)
+	})
+
+	t.Run("json query", func(t *testing.T) {
+		finalQuery := NewQueryBuilder().AllQuery(Must, 1.0).Build()
+		sCtx := NewSearchContextBuilder().
+			SetQueryFromJson(&finalQuery).
+			SetDocsLimit(1).
+			AddSortField("size", Asc).
+			Build()
+		result, err := index.SearchFastFieldsJson(sCtx, "size", "id")
+		require.NoError(t, err)
+		require.Equal(t, [][]any{{uint64(7), "b"}}, result.Rows)
+	})
+
+	t.Run("single field values as strings", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(10).
+			AddSortField("size", Asc).
+			Build()
+		result, err := index.SearchFastField(sCtx, "size")
+		require.NoError(t, err)
+		require.Equal(t, []string{"7", "1024"}, result.Values)
+	})
+
+	t.Run("err - field that is not fast", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(10).
+			Build()
+		_, err := index.SearchFastFields(sCtx, "id", "body")
+		require.Error(t, err)
+		require.Contains(t, err.Error(), "is not a fast field")
+	})
+
+	t.Run("segment without values of a field", func(t *testing.T) {
+		bare := NewDocument()
+		require.NoError(t, bare.AddField("c", index, "id"))
+		require.NoError(t, bare.AddField("report", index, "body"))
+		require.NoError(t, index.AddAndConsumeDocuments(bare))
+
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(10).
+			AddSortField("size", Desc).
+			Build()
+		result, err := index.SearchFastFields(sCtx, "id", "size", "delta")
+		require.NoError(t, err)
+		require.Equal(t, [][]any{
+			{"a", uint64(1024), int64(-42)},
+			{"b", uint64(7), nil},
+			{"c", nil, nil},
+		}, result.Rows)
+	})
+}
+
+func TestConcurrentSearch(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-concurrent-index")
+	defer os.RemoveAll(indexPath)
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	const before, batches = 5, 20
+	addDoc := func(i int) error {
+		doc := NewDocument()
+		if err := doc.AddField(fmt.Sprintf("%d", i), index, "id"); err != nil {
+			return err
+		}
+		if err := doc.AddField("concurrent report", index, "body"); err != nil {
+			return err
+		}
+		return index.AddAndConsumeDocuments(doc)
+	}
+	for i := 0; i < before; i++ {
+		require.NoError(t, addDoc(i))
+	}
+
+	sCtx := NewSearchContextBuilder().
+		SetQuery("report").
+		AddFieldDefaultWeight("body").
+		SetDocsLimit(before + batches).
+		Build()
+	hits := func() (uint64, error) {
+		result, err := index.Search(sCtx)
+		if err != nil {
+			return 0, err
+		}
+		defer result.Free()
+		return result.GetSize()
+	}
+
+	size, err := hits()
+	require.NoError(t, err)
+	require.Equal(t, uint64(before), size)
+
+	// require must not be called outside the test goroutine, so the goroutines report their errors
+	errs := make(chan error, 5)
+	var wg sync.WaitGroup
+	wg.Add(1)
+	go func() {
+		defer wg.Done()
+		for i := before; i < before+batches; i++ {
+			if err := addDoc(i); err != nil {
+				errs <- err
+				return
+			}
+		}
+	}()
+	for g := 0; g < 4; g++ {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			for i := 0; i < batches; i++ {
+				size, err := hits()
+				if err != nil {
+					errs <- err
+					return
+				}
+				if size < before || size > before+batches {
+					errs <- fmt.Errorf("got %d hits, expected %d to %d", size, before, before+batches)
+					return
+				}
+			}
+		}()
+	}
+	wg.Wait()
+	close(errs)
+	for err := range errs {
+		require.NoError(t, err)
+	}
+
+	size, err = hits()
+	require.NoError(t, err)
+	require.Equal(t, uint64(before+batches), size)
+
+	numDocs, err := index.NumDocs()
+	require.NoError(t, err)
+	require.Equal(t, uint64(before+batches), numDocs)
+}
+
+func TestPinnedSearcher(t *testing.T) {
+	tempDir := t.TempDir()
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("id", true, false, true, IndexRecordOptionBasic, "raw"))
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	newIndex := func(name string, options *ContextOptions) *TantivyContext {
+		index, err := NewTantivyContextWithOptions(filepath.Join(tempDir, name), schema, options)
+		require.NoError(t, err)
+		require.NoError(t, index.RegisterTextAnalyzerRaw("raw"))
+		require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+		return index
+	}
+	addDocs := func(index *TantivyContext, ids ...string) {
+		var docs []*Document
+		for _, id := range ids {
+			doc := NewDocument()
+			require.NoError(t, doc.AddField(id, index, "id"))
+			require.NoError(t, doc.AddField("pinned report", index, "body"))
+			docs = append(docs, doc)
+		}
+		require.NoError(t, index.AddAndConsumeDocuments(docs...))
+	}
+	count := func(index *TantivyContext, searcher *Searcher) uint64 {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetSearcher(searcher).
+			Build()
+		count, err := index.Count(sCtx)
+		require.NoError(t, err)
+		return count
+	}
+
+	t.Run("pinned searcher ignores later commits", func(t *testing.T) {
+		index := newIndex("pinned", nil)
+		defer index.Free()
+
+		addDocs(index, "1", "2", "3")
+		searcher, err := index.AcquireSearcher()
+		require.NoError(t, err)
+		defer searcher.Free()
+
+		addDocs(index, "4")
+		require.Equal(t, uint64(3), count(index, searcher))
+		require.Equal(t, uint64(4), count(index, nil))
+
+		page := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(2).
+			SetOffset(2).
+			SetSearcher(searcher).
+			Build()
+		result, err := index.Search(page)
+		require.NoError(t, err)
+		defer result.Free()
+		size, err := result.GetSize()
+		require.NoError(t, err)
+		require.Equal(t, uint64(1), size)
+
+		latest, err := index.AcquireSearcher()
+		require.NoError(t, err)
+		defer latest.Free()
+		require.Greater(t, latest.Generation(), searcher.Generation())
+	})
+
+	t.Run("manual reload", func(t *testing.T) {
+		index := newIndex("manual", &ContextOptions{ReaderReload: ReloadManual})
+		defer index.Free()
+
+		addDocs(index, "1")
+		require.Equal(t, uint64(0), count(index, nil))
+
+		require.NoError(t, index.ReloadReader())
+		require.Equal(t, uint64(1), count(index, nil))
+	})
+
+	t.Run("unknown reload policy", func(t *testing.T) {
+		_, err := NewTantivyContextWithOptions(filepath.Join(tempDir, "unknown"), schema, &ContextOptions{ReaderReload: "sometimes"})
+		require.Error(t, err)
+	})
+}
+
+func TestErrorCodes(t *testing.T) {
+	tempDir := t.TempDir()
+	indexPath := filepath.Join(tempDir, "test-error-codes-index")
+
+	builder, err := NewSchemaBuilder()
+	require.NoError(t, err)
+
+	require.NoError(t, builder.AddTextField("body", true, true, false, IndexRecordOptionWithFreqsAndPositions, "simple"))
+
+	schema, err := builder.BuildSchema()
+	require.NoError(t, err)
+
+	index, err := NewTantivyContextWithSchema(indexPath, schema)
+	require.NoError(t, err)
+	defer index.Free()
+
+	require.NoError(t, index.RegisterTextAnalyzerSimple("simple", 100, English))
+
+	requireCode := func(t *testing.T, err error, code ErrorCode) {
+		var tantivyErr *Error
+		require.ErrorAs(t, err, &tantivyErr)
+		require.Equal(t, code, tantivyErr.Code)
+		require.NotEmpty(t, tantivyErr.Message)
+	}
+	search := func(query string) error {
+		sCtx := NewSearchContextBuilder().
+			SetQuery(query).
+			AddFieldDefaultWeight("body").
+			Build()
+		_, err := index.Count(sCtx)
+		return err
+	}
+
+	t.Run("query parse", func(t *testing.T) {
+		requireCode(t, search("body:(report"), ErrQueryParse)
+	})
+
+	t.Run("unknown field", func(t *testing.T) {
+		requireCode(t, search("missing:report"), ErrUnknownField)
+	})
+
+	t.Run("invalid argument", func(t *testing.T) {
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetSearchAfter("not a cursor").
+			Build()
+		_, err := index.Search(sCtx)
+		requireCode(t, err, ErrInvalidArgument)
+		require.Contains(t, err.Error(), "Invalid search cursor")
+	})
+
+	t.Run("docs limit", func(t *testing.T) {
+		doc := NewDocument()
+		require.NoError(t, doc.AddField("monthly report", index, "body"))
+		require.NoError(t, index.AddAndConsumeDocuments(doc))
+
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(0).
+			Build()
+		_, err := index.Search(sCtx)
+		requireCode(t, err, ErrInvalidArgument)
+
+		sCtx = NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(^uintptr(0)).
+			SetOffset(1).
+			Build()
+		_, err = index.Search(sCtx)
+		requireCode(t, err, ErrInvalidArgument)
+
+		sCtx = NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetDocsLimit(^uintptr(0)).
+			Build()
+		result, err := index.Search(sCtx)
+		require.NoError(t, err)
+		defer result.Free()
+		size, err := result.GetSize()
+		require.NoError(t, err)
+		require.Equal(t, uint64(1), size)
+	})
+
+	t.Run("unknown field id", func(t *testing.T) {
+		// a field id the index does not have, as sent by a stale schema
+		schema.fieldNames["bogus"] = 42
+		defer delete(schema.fieldNames, "bogus")
+
+		_, err := index.Count(NewSearchContextBuilder().SetQuery("report").AddFieldDefaultWeight("bogus").Build())
+		requireCode(t, err, ErrUnknownField)
+
+		sCtx := NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			AddSortField("bogus", Asc).
+			Build()
+		_, err = index.Search(sCtx)
+		requireCode(t, err, ErrUnknownField)
+
+		sCtx = NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetFacetCounts("bogus", "/space").
+			Build()
+		_, err = index.Search(sCtx)
+		requireCode(t, err, ErrUnknownField)
+
+		sCtx = NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			SetCollapse("bogus", false, 0).
+			Build()
+		_, err = index.Search(sCtx)
+		requireCode(t, err, ErrUnknownField)
+
+		sCtx = NewSearchContextBuilder().
+			SetQuery("report").
+			AddFieldDefaultWeight("body").
+			Build()
+		_, err = index.SearchFastFields(sCtx, "bogus")
+		requireCode(t, err, ErrUnknownField)
+
+		doc := NewDocument()
+		require.NoError(t, doc.AddField("report", index, "bogus"))
+		err = index.AddAndConsumeDocuments(doc)
+		requireCode(t, err, ErrInvalidDocument)
+		require.Contains(t, err.Error(), "Unknown field id 42")
+	})
+
+	t.Run("lock busy", func(t *testing.T) {
+		_, err := NewTantivyContextWithSchema(indexPath, schema)
+		requireCode(t, err, ErrLockBusy)
+	})
+}
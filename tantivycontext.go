@@ -3,12 +3,17 @@ package tantivy_go
 // #include "bindings.h"
 import "C"
 import (
+	"encoding/json"
 	"errors"
 	"fmt"
 	"sync"
 	"unsafe"
 )
 
+// TantivyContext is an open index with its writer and reader.
+//
+// When an add, delete, upsert or commit call fails, every operation staged since the last
+// commit is rolled back, including the ones staged by earlier calls.
 type TantivyContext struct {
 	ptr    *C.TantivyContext
 	schema *Schema
@@ -25,13 +30,32 @@ type TantivyContext struct {
 //   - *TantivyContext: A pointer to a newly created TantivyContext instance.
 //   - error: An error if the index creation fails.
 func NewTantivyContextWithSchema(path string, schema *Schema) (*TantivyContext, error) {
+	return NewTantivyContextWithOptions(path, schema, nil)
+}
+
+// NewTantivyContextWithOptions creates a new instance of TantivyContext with the provided schema
+// and options.
+//
+// Parameters:
+//   - path: The path to the index as a string.
+//   - schema: A pointer to the Schema to be used.
+//   - options: The index writer and reader options, the defaults when nil.
+//
+// Returns:
+//   - *TantivyContext: A pointer to a newly created TantivyContext instance.
+//   - error: An error if the index creation fails.
+func NewTantivyContextWithOptions(path string, schema *Schema, options *ContextOptions) (*TantivyContext, error) {
+	cOptions, err := contextOptionsCString(options)
+	if err != nil {
+		return nil, err
+	}
+	defer C.string_free(cOptions)
 	cPath := C.CString(path)
 	defer C.string_free(cPath)
-	var errBuffer *C.char
-	ptr := C.context_create_with_schema(cPath, schema.ptr, &errBuffer)
+	var errBuffer C.ErrorBuffer
+	ptr := C.context_create_with_schema(cPath, schema.ptr, cOptions, &errBuffer)
 	if ptr == nil {
-		defer C.string_free(errBuffer)
-		return nil, errors.New(C.GoString(errBuffer))
+		return nil, tryExtractError(errBuffer)
 	}
 	return &TantivyContext{
 		ptr:    ptr,
@@ -60,17 +84,59 @@ func (tc *TantivyContext) AddAndConsumeDocuments(docs ...*Document) error {
 //   - uint64: The opstamp from the commit operation. Returns 0 if no documents are provided.
 //   - error: An error if adding and consuming the documents fails.
 func (tc *TantivyContext) AddAndConsumeDocumentsWithOpstamp(docs ...*Document) (uint64, error) {
+	return tc.AddAndConsumeDocumentsWithPayload("", docs...)
+}
+
+// AddAndConsumeDocumentsWithPayload adds and consumes the provided documents to the index
+// and stores the payload with the commit, see LastCommitPayload.
+//
+// Parameters:
+//   - payload: The payload of the commit, none when empty.
+//   - docs: A variadic parameter of pointers to Document to be added and consumed.
+//
+// Returns:
+//   - uint64: The opstamp from the commit operation. Returns 0 if no documents are provided.
+//   - error: An error if adding and consuming the documents fails.
+func (tc *TantivyContext) AddAndConsumeDocumentsWithPayload(payload string, docs ...*Document) (uint64, error) {
+	opstamp, _, err := tc.AddAndConsumeDocumentsWithPolicy(FailFast, payload, docs...)
+	return opstamp, err
+}
+
+// AddAndConsumeDocumentsWithPolicy adds and consumes the provided documents to the index
+// following the indexing policy and stores the payload with the commit.
+//
+// Parameters:
+//   - policy: What to do with documents that fail to index.
+//   - payload: The payload of the commit, none when empty.
+//   - docs: A variadic parameter of pointers to Document to be added and consumed.
+//
+// Returns:
+//   - uint64: The opstamp from the commit operation. Returns 0 if no documents are provided.
+//   - []DocumentFailure: The documents skipped with BestEffort.
+//   - error: An error if adding and consuming the documents fails.
+func (tc *TantivyContext) AddAndConsumeDocumentsWithPolicy(policy IndexingPolicy, payload string, docs ...*Document) (uint64, []DocumentFailure, error) {
 	if len(docs) == 0 {
-		return 0, nil
+		return 0, nil, nil
 	}
 	tc.lock.Lock()
 	defer tc.lock.Unlock()
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	docsPtr := make([]*C.Document, len(docs))
 	for j, doc := range docs {
 		docsPtr[j] = doc.ptr
 	}
-	opstamp := C.context_add_and_consume_documents(tc.ptr, &docsPtr[0], C.uintptr_t(len(docs)), &errBuffer)
+	cPayload := payloadCString(payload)
+	defer C.string_free(cPayload)
+	var failuresBuffer *C.char
+	opstamp := C.context_add_and_consume_documents(
+		tc.ptr,
+		&docsPtr[0],
+		C.uintptr_t(len(docs)),
+		C.uint8_t(policy),
+		cPayload,
+		&failuresBuffer,
+		&errBuffer,
+	)
 	for _, doc := range docs {
 		// Free the strings in the document
 		// This is necessary because the document is consumed by the index
@@ -80,10 +146,11 @@ func (tc *TantivyContext) AddAndConsumeDocumentsWithOpstamp(docs ...*Document) (
 	}
 	err := tryExtractError(errBuffer)
 	if err != nil {
-		return 0, err
+		return 0, nil, err
 	}
 
-	return uint64(opstamp), nil
+	failures, err := extractFailures(failuresBuffer)
+	return uint64(opstamp), failures, err
 }
 
 // DeleteDocuments deletes documents from the index based on the specified field and IDs.
@@ -109,6 +176,21 @@ func (tc *TantivyContext) DeleteDocuments(fieldName string, deleteIds ...string)
 //   - uint64: The opstamp from the delete operation. Returns 0 if no IDs are provided.
 //   - error: An error if deleting the documents fails.
 func (tc *TantivyContext) DeleteDocumentsWithOpstamp(fieldName string, deleteIds ...string) (uint64, error) {
+	return tc.DeleteDocumentsWithPayload("", fieldName, deleteIds...)
+}
+
+// DeleteDocumentsWithPayload deletes documents from the index based on the specified field and IDs
+// and stores the payload with the commit, see LastCommitPayload.
+//
+// Parameters:
+//   - payload: The payload of the commit, none when empty.
+//   - fieldName: The field name to match against the document IDs.
+//   - deleteIds: A variadic parameter of document IDs to be deleted.
+//
+// Returns:
+//   - uint64: The opstamp from the delete operation. Returns 0 if no IDs are provided.
+//   - error: An error if deleting the documents fails.
+func (tc *TantivyContext) DeleteDocumentsWithPayload(payload string, fieldName string, deleteIds ...string) (uint64, error) {
 	if len(deleteIds) == 0 {
 		return 0, nil
 	}
@@ -127,11 +209,13 @@ func (tc *TantivyContext) DeleteDocumentsWithOpstamp(fieldName string, deleteIds
 	}
 	cDeleteIds := (**C.char)(unsafe.Pointer(&deleteIDsPtr[0]))
 
-	var errBuffer *C.char
-	opstamp := C.context_delete_documents(tc.ptr, C.uint(fieldId), cDeleteIds, C.uintptr_t(len(deleteIds)), &errBuffer)
-	if errBuffer != nil {
-		defer C.string_free(errBuffer)
-		return 0, errors.New(C.GoString(errBuffer))
+	cPayload := payloadCString(payload)
+	defer C.string_free(cPayload)
+
+	var errBuffer C.ErrorBuffer
+	opstamp := C.context_delete_documents(tc.ptr, C.uint(fieldId), cDeleteIds, C.uintptr_t(len(deleteIds)), cPayload, &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
 	}
 
 	return uint64(opstamp), nil
@@ -154,9 +238,45 @@ func (tc *TantivyContext) DeleteDocumentsWithOpstamp(fieldName string, deleteIds
 //   - uint64: The opstamp from the commit operation. Returns 0 if both addDocs and deleteFieldValues are empty.
 //   - error: An error if the batch operation fails.
 func (tc *TantivyContext) BatchAddAndDeleteDocumentsWithOpstamp(addDocs []*Document, deleteFieldName string, deleteFieldValues []string) (uint64, error) {
+	return tc.BatchAddAndDeleteDocumentsWithPayload(addDocs, deleteFieldName, deleteFieldValues, "")
+}
+
+// BatchAddAndDeleteDocumentsWithPayload performs batch add and delete operations within a single commit
+// and stores the payload with the commit, see LastCommitPayload.
+//
+// Parameters:
+//   - addDocs: Documents to add to the index.
+//   - deleteFieldName: The field name to match against for deletion.
+//   - deleteFieldValues: Field values to delete from the index.
+//   - payload: The payload of the commit, none when empty.
+//
+// Returns:
+//   - uint64: The opstamp from the commit operation. Returns 0 if both addDocs and deleteFieldValues are empty.
+//   - error: An error if the batch operation fails.
+func (tc *TantivyContext) BatchAddAndDeleteDocumentsWithPayload(addDocs []*Document, deleteFieldName string, deleteFieldValues []string, payload string) (uint64, error) {
+	opstamp, _, err := tc.BatchAddAndDeleteDocumentsWithPolicy(addDocs, deleteFieldName, deleteFieldValues, payload, FailFast)
+	return opstamp, err
+}
+
+// BatchAddAndDeleteDocumentsWithPolicy performs batch add and delete operations within a single commit
+// following the indexing policy and stores the payload with the commit.
+// With FailFast nothing of the batch is applied when a document is invalid.
+//
+// Parameters:
+//   - addDocs: Documents to add to the index.
+//   - deleteFieldName: The field name to match against for deletion.
+//   - deleteFieldValues: Field values to delete from the index.
+//   - payload: The payload of the commit, none when empty.
+//   - policy: What to do with documents that fail to index.
+//
+// Returns:
+//   - uint64: The opstamp from the commit operation. Returns 0 if both addDocs and deleteFieldValues are empty.
+//   - []DocumentFailure: The documents skipped with BestEffort.
+//   - error: An error if the batch operation fails.
+func (tc *TantivyContext) BatchAddAndDeleteDocumentsWithPolicy(addDocs []*Document, deleteFieldName string, deleteFieldValues []string, payload string, policy IndexingPolicy) (uint64, []DocumentFailure, error) {
 	// If both operations are empty, return early without acquiring lock
 	if len(addDocs) == 0 && len(deleteFieldValues) == 0 {
-		return 0, nil
+		return 0, nil, nil
 	}
 
 	tc.lock.Lock()
@@ -182,7 +302,7 @@ func (tc *TantivyContext) BatchAddAndDeleteDocumentsWithOpstamp(addDocs []*Docum
 	if len(deleteFieldValues) > 0 {
 		fieldId, contains := tc.schema.fieldNames[deleteFieldName]
 		if !contains {
-			return 0, errors.New("field not found in schema")
+			return 0, nil, errors.New("field not found in schema")
 		}
 		deleteFieldId = C.uint(fieldId)
 
@@ -196,8 +316,12 @@ func (tc *TantivyContext) BatchAddAndDeleteDocumentsWithOpstamp(addDocs []*Docum
 		deleteValuesLen = C.uintptr_t(len(deleteFieldValues))
 	}
 
+	cPayload := payloadCString(payload)
+	defer C.string_free(cPayload)
+
 	// Execute batch operation
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
+	var failuresBuffer *C.char
 	opstamp := C.context_batch_add_and_delete_documents(
 		tc.ptr,
 		addDocsPtr,
@@ -205,6 +329,9 @@ func (tc *TantivyContext) BatchAddAndDeleteDocumentsWithOpstamp(addDocs []*Docum
 		deleteFieldId,
 		deleteValuesPtr,
 		deleteValuesLen,
+		C.uint8_t(policy),
+		cPayload,
+		&failuresBuffer,
 		&errBuffer,
 	)
 
@@ -213,11 +340,349 @@ func (tc *TantivyContext) BatchAddAndDeleteDocumentsWithOpstamp(addDocs []*Docum
 		doc.FreeStrings()
 	}
 
-	if errBuffer != nil {
-		defer C.string_free(errBuffer)
-		return 0, errors.New(C.GoString(errBuffer))
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, nil, err
+	}
+
+	failures, err := extractFailures(failuresBuffer)
+	return uint64(opstamp), failures, err
+}
+
+// UpsertDocuments replaces the indexed documents having the id of a new document and commits.
+// The id of every document is read from its own value of the id field,
+// so no separate list of ids to delete is needed.
+// A text id must make a single token with the tokenizer of the id field, e.g. a raw tokenizer.
+//
+// Parameters:
+//   - idFieldName: The indexed text, u64 or i64 field holding the document id.
+//   - docs: A variadic parameter of pointers to Document to be added and consumed.
+//
+// Returns:
+//   - uint64: The opstamp of the commit. Returns 0 if no documents are provided.
+//   - error: An error if a document has no id or the upsert fails.
+func (tc *TantivyContext) UpsertDocuments(idFieldName string, docs ...*Document) (uint64, error) {
+	return tc.UpsertDocumentsWithPayload(idFieldName, "", "", docs...)
+}
+
+// UpsertNewerDocuments replaces the indexed documents having the id of a new document and commits,
+// skipping every document whose version is not newer than the indexed one.
+// Versions are compared against the last commit, even one the reader has not loaded yet,
+// not against operations staged and not yet committed.
+//
+// Parameters:
+//   - idFieldName: The indexed text, u64 or i64 field holding the document id.
+//   - versionFieldName: The numeric or date fast field holding the document version.
+//   - docs: A variadic parameter of pointers to Document to be added and consumed.
+//
+// Returns:
+//   - uint64: The opstamp of the commit. Returns 0 if no documents are provided.
+//   - error: An error if a document has no id or version or the upsert fails.
+func (tc *TantivyContext) UpsertNewerDocuments(idFieldName string, versionFieldName string, docs ...*Document) (uint64, error) {
+	return tc.UpsertDocumentsWithPayload(idFieldName, versionFieldName, "", docs...)
+}
+
+// UpsertDocumentsWithPayload replaces the indexed documents having the id of a new document
+// and stores the payload with the commit, see LastCommitPayload.
+// With a non-empty versionFieldName only documents with a newer version replace the indexed ones.
+func (tc *TantivyContext) UpsertDocumentsWithPayload(idFieldName string, versionFieldName string, payload string, docs ...*Document) (uint64, error) {
+	if len(docs) == 0 {
+		return 0, nil
+	}
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+
+	idFieldId, contains := tc.schema.fieldNames[idFieldName]
+	if !contains {
+		return 0, errors.New("id field not found in schema")
+	}
+	versionFieldId := C.uint(C.NO_VERSION)
+	if versionFieldName != "" {
+		fieldId, contains := tc.schema.fieldNames[versionFieldName]
+		if !contains {
+			return 0, errors.New("version field not found in schema")
+		}
+		versionFieldId = C.uint(fieldId)
+	}
+
+	docsPtr := make([]*C.Document, len(docs))
+	for j, doc := range docs {
+		docsPtr[j] = doc.ptr
+	}
+	cPayload := payloadCString(payload)
+	defer C.string_free(cPayload)
+
+	var errBuffer C.ErrorBuffer
+	opstamp := C.context_upsert_documents(
+		tc.ptr,
+		C.uint(idFieldId),
+		&docsPtr[0],
+		C.uintptr_t(len(docs)),
+		versionFieldId,
+		cPayload,
+		&errBuffer,
+	)
+	for _, doc := range docs {
+		doc.FreeStrings()
+	}
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
+	}
+	return uint64(opstamp), nil
+}
+
+// DeleteByQuery deletes the documents matching the query and commits.
+//
+// Parameters:
+//   - sCtx (SearchContext): The context containing query string and field weights.
+//
+// Returns:
+//   - uint64: The opstamp of the commit.
+//   - error: An error if the query is invalid or deleting the documents fails.
+func (tc *TantivyContext) DeleteByQuery(sCtx SearchContext) (uint64, error) {
+	return tc.DeleteByQueryWithPayload(sCtx, "")
+}
+
+// DeleteByQueryWithPayload deletes the documents matching the query
+// and stores the payload with the commit, see LastCommitPayload.
+func (tc *TantivyContext) DeleteByQueryWithPayload(sCtx SearchContext, payload string) (uint64, error) {
+	fieldNames, weights := sCtx.GetFieldAndWeights()
+	if len(fieldNames) == 0 {
+		return 0, fmt.Errorf("fieldNames must not be empty")
+	}
+
+	fieldNamesPtr, err := tc.extractFields(fieldNames)
+	if err != nil {
+		return 0, err
+	}
+
+	fieldWeightsPtr := make([]C.float, len(fieldNames))
+	for j, weight := range weights {
+		fieldWeightsPtr[j] = C.float(weight)
+	}
+
+	return tc.deleteByQuery(
+		sCtx,
+		(*C.uint)(unsafe.Pointer(&fieldNamesPtr[0])),
+		(*C.float)(unsafe.Pointer(&fieldWeightsPtr[0])),
+		len(fieldNames),
+		false,
+		payload,
+	)
+}
+
+// DeleteByQueryJson deletes the documents matching a JSON query and commits.
+//
+// Parameters:
+//   - sCtx (SearchContext): The context containing the JSON query.
+//
+// Returns:
+//   - uint64: The opstamp of the commit.
+//   - error: An error if the query is invalid or deleting the documents fails.
+func (tc *TantivyContext) DeleteByQueryJson(sCtx SearchContext) (uint64, error) {
+	return tc.DeleteByQueryJsonWithPayload(sCtx, "")
+}
+
+// DeleteByQueryJsonWithPayload deletes the documents matching a JSON query
+// and stores the payload with the commit, see LastCommitPayload.
+func (tc *TantivyContext) DeleteByQueryJsonWithPayload(sCtx SearchContext, payload string) (uint64, error) {
+	return tc.deleteByQuery(sCtx, nil, nil, 0, true, payload)
+}
+
+func (tc *TantivyContext) deleteByQuery(
+	sCtx SearchContext,
+	fieldIdsPtr *C.uint,
+	fieldWeightsPtr *C.float,
+	fieldIdsLen int,
+	queryIsJson bool,
+	payload string,
+) (uint64, error) {
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+
+	cQuery := C.CString(sCtx.GetQuery())
+	defer C.string_free(cQuery)
+	cPayload := payloadCString(payload)
+	defer C.string_free(cPayload)
+
+	var errBuffer C.ErrorBuffer
+	opstamp := C.context_delete_by_query(
+		tc.ptr,
+		fieldIdsPtr,
+		fieldWeightsPtr,
+		C.uintptr_t(fieldIdsLen),
+		cQuery,
+		C.bool(queryIsJson),
+		cPayload,
+		&errBuffer,
+	)
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
+	}
+	return uint64(opstamp), nil
+}
+
+// DeleteAll deletes every document of the index and commits.
+// The index directory and the schema are kept, so the index can be refilled right away.
+//
+// Returns:
+//   - uint64: The opstamp of the commit.
+//   - error: An error if deleting the documents fails.
+func (tc *TantivyContext) DeleteAll() (uint64, error) {
+	return tc.DeleteAllWithPayload("")
+}
+
+// DeleteAllWithPayload deletes every document of the index
+// and stores the payload with the commit, see LastCommitPayload.
+func (tc *TantivyContext) DeleteAllWithPayload(payload string) (uint64, error) {
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+
+	cPayload := payloadCString(payload)
+	defer C.string_free(cPayload)
+
+	var errBuffer C.ErrorBuffer
+	opstamp := C.context_delete_all(tc.ptr, cPayload, &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
+	}
+	return uint64(opstamp), nil
+}
+
+// AddDocuments stages the provided documents without committing them.
+// The documents become searchable after Commit and are dropped by Rollback.
+//
+// Parameters:
+//   - docs: A variadic parameter of pointers to Document to be added and consumed.
+//
+// Returns:
+//   - uint64: The opstamp of the last staged document. Returns 0 if no documents are provided.
+//   - error: An error if staging the documents fails.
+func (tc *TantivyContext) AddDocuments(docs ...*Document) (uint64, error) {
+	opstamp, _, err := tc.AddDocumentsWithPolicy(FailFast, docs...)
+	return opstamp, err
+}
+
+// AddDocumentsWithPolicy stages the provided documents without committing them
+// following the indexing policy.
+//
+// Parameters:
+//   - policy: What to do with documents that fail to index.
+//   - docs: A variadic parameter of pointers to Document to be added and consumed.
+//
+// Returns:
+//   - uint64: The opstamp of the last staged document. Returns 0 if no documents are provided.
+//   - []DocumentFailure: The documents skipped with BestEffort.
+//   - error: An error if staging the documents fails.
+func (tc *TantivyContext) AddDocumentsWithPolicy(policy IndexingPolicy, docs ...*Document) (uint64, []DocumentFailure, error) {
+	if len(docs) == 0 {
+		return 0, nil, nil
+	}
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+	var errBuffer C.ErrorBuffer
+	var failuresBuffer *C.char
+	docsPtr := make([]*C.Document, len(docs))
+	for j, doc := range docs {
+		docsPtr[j] = doc.ptr
+	}
+	opstamp := C.context_add_documents(
+		tc.ptr,
+		&docsPtr[0],
+		C.uintptr_t(len(docs)),
+		C.uint8_t(policy),
+		&failuresBuffer,
+		&errBuffer,
+	)
+	for _, doc := range docs {
+		doc.FreeStrings()
+	}
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, nil, err
+	}
+	failures, err := extractFailures(failuresBuffer)
+	return uint64(opstamp), failures, err
+}
+
+// DeleteTerms stages the deletion of the documents whose field matches one of the values
+// without committing it. The documents disappear after Commit and stay after Rollback.
+//
+// Parameters:
+//   - fieldName: The field name to match against the values.
+//   - values: A variadic parameter of field values to be deleted.
+//
+// Returns:
+//   - uint64: The opstamp of the last staged deletion. Returns 0 if no values are provided.
+//   - error: An error if staging the deletion fails.
+func (tc *TantivyContext) DeleteTerms(fieldName string, values ...string) (uint64, error) {
+	if len(values) == 0 {
+		return 0, nil
+	}
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+	fieldId, contains := tc.schema.fieldNames[fieldName]
+	if !contains {
+		return 0, errors.New("field not found in schema")
+	}
+
+	valuesPtr := make([]*C.char, len(values))
+	for j, value := range values {
+		cValue := C.CString(value)
+		defer C.free(unsafe.Pointer(cValue))
+		valuesPtr[j] = cValue
+	}
+
+	var errBuffer C.ErrorBuffer
+	opstamp := C.context_delete_terms(tc.ptr, C.uint(fieldId), &valuesPtr[0], C.uintptr_t(len(values)), &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
 	}
+	return uint64(opstamp), nil
+}
 
+// Commit commits all operations staged with AddDocuments and DeleteTerms.
+//
+// Returns:
+//   - uint64: The opstamp of the commit.
+//   - error: An error if the commit fails.
+func (tc *TantivyContext) Commit() (uint64, error) {
+	return tc.CommitWithPayload("")
+}
+
+// CommitWithPayload commits all operations staged with AddDocuments and DeleteTerms
+// and stores the payload with the commit, see LastCommitPayload.
+//
+// Parameters:
+//   - payload: The payload of the commit, none when empty.
+//
+// Returns:
+//   - uint64: The opstamp of the commit.
+//   - error: An error if the commit fails.
+func (tc *TantivyContext) CommitWithPayload(payload string) (uint64, error) {
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+	cPayload := payloadCString(payload)
+	defer C.string_free(cPayload)
+	var errBuffer C.ErrorBuffer
+	opstamp := C.context_commit(tc.ptr, cPayload, &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
+	}
+	return uint64(opstamp), nil
+}
+
+// Rollback drops all operations staged since the last commit.
+//
+// Returns:
+//   - uint64: The opstamp of the last commit.
+//   - error: An error if the rollback fails.
+func (tc *TantivyContext) Rollback() (uint64, error) {
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+	var errBuffer C.ErrorBuffer
+	opstamp := C.context_rollback(tc.ptr, &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
+	}
 	return uint64(opstamp), nil
 }
 
@@ -227,11 +692,10 @@ func (tc *TantivyContext) BatchAddAndDeleteDocumentsWithOpstamp(addDocs []*Docum
 //   - uint64: The number of documents.
 //   - error: An error if retrieving the document count fails.
 func (tc *TantivyContext) NumDocs() (uint64, error) {
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	numDocs := C.context_num_docs(tc.ptr, &errBuffer)
-	if errBuffer != nil {
-		defer C.string_free(errBuffer)
-		return 0, errors.New(C.GoString(errBuffer))
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
 	}
 	return uint64(numDocs), nil
 }
@@ -263,20 +727,29 @@ func (tc *TantivyContext) Search(sCtx SearchContext) (*SearchResult, error) {
 		fieldWeightsPtr[j] = C.float(weight)
 	}
 
-	var errBuffer *C.char
+	options, err := tc.extractSearchOptions(sCtx)
+	if err != nil {
+		return nil, err
+	}
+	cOptions, err := options.cString()
+	if err != nil {
+		return nil, err
+	}
+	defer C.string_free(cOptions)
+
+	var errBuffer C.ErrorBuffer
 	ptr := C.context_search(
 		tc.ptr,
+		searcherPtr(sCtx),
 		(*C.uint)(unsafe.Pointer(&fieldNamesPtr[0])),
 		(*C.float)(unsafe.Pointer(&fieldWeightsPtr[0])),
 		C.uintptr_t(len(fieldNames)),
 		cQuery,
+		cOptions,
 		&errBuffer,
-		pointerCType(sCtx.GetDocsLimit()),
-		C.bool(sCtx.WithHighlights()),
 	)
 	if ptr == nil {
-		defer C.string_free(errBuffer)
-		return nil, errors.New(C.GoString(errBuffer))
+		return nil, tryExtractError(errBuffer)
 	}
 
 	return &SearchResult{ptr: ptr}, nil
@@ -296,29 +769,179 @@ func (tc *TantivyContext) SearchJson(sCtx SearchContext) (*SearchResult, error)
 	cQuery := C.CString(sCtx.GetQuery())
 	defer C.string_free(cQuery)
 
+	options, err := tc.extractSearchOptions(sCtx)
+	if err != nil {
+		return nil, err
+	}
+	cOptions, err := options.cString()
+	if err != nil {
+		return nil, err
+	}
+	defer C.string_free(cOptions)
+
 	// Prepare the error buffer
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 
 	// Call the C function
 	ptr := C.context_search_json(
 		tc.ptr,
+		searcherPtr(sCtx),
 		cQuery,
+		cOptions,
 		&errBuffer,
-		pointerCType(sCtx.GetDocsLimit()),
-		C.bool(sCtx.WithHighlights()),
 	)
 	if ptr == nil {
-		defer C.string_free(errBuffer)
-		return nil, errors.New(C.GoString(errBuffer))
+		return nil, tryExtractError(errBuffer)
 	}
 
 	return &SearchResult{ptr: ptr}, nil
 }
 
+// Count returns the number of documents matching the query without loading any of them.
+//
+// Parameters:
+//   - sCtx (SearchContext): The context for the search, containing query string and field weights.
+//
+// Returns:
+//   - uint64: The number of matching documents.
+//   - error: An error if the query fails.
+func (tc *TantivyContext) Count(sCtx SearchContext) (uint64, error) {
+	fieldNames, weights := sCtx.GetFieldAndWeights()
+	if len(fieldNames) == 0 {
+		return 0, fmt.Errorf("fieldNames must not be empty")
+	}
+	cQuery := C.CString(sCtx.GetQuery())
+	defer C.string_free(cQuery)
+
+	fieldNamesPtr, err := tc.extractFields(fieldNames)
+	if err != nil {
+		return 0, err
+	}
+
+	fieldWeightsPtr := make([]C.float, len(fieldNames))
+	for j, weight := range weights {
+		fieldWeightsPtr[j] = C.float(weight)
+	}
+
+	var errBuffer C.ErrorBuffer
+	count := C.context_count(
+		tc.ptr,
+		searcherPtr(sCtx),
+		(*C.uint)(unsafe.Pointer(&fieldNamesPtr[0])),
+		(*C.float)(unsafe.Pointer(&fieldWeightsPtr[0])),
+		C.uintptr_t(len(fieldNames)),
+		cQuery,
+		C.bool(false),
+		&errBuffer,
+	)
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
+	}
+	return uint64(count), nil
+}
+
+// CountJson returns the number of documents matching a JSON query without loading any of them.
+//
+// Parameters:
+//   - sCtx (SearchContext): The context for the search, containing the JSON query.
+//
+// Returns:
+//   - uint64: The number of matching documents.
+//   - error: An error if the query fails.
+func (tc *TantivyContext) CountJson(sCtx SearchContext) (uint64, error) {
+	cQuery := C.CString(sCtx.GetQuery())
+	defer C.string_free(cQuery)
+
+	var errBuffer C.ErrorBuffer
+	count := C.context_count(tc.ptr, searcherPtr(sCtx), nil, nil, 0, cQuery, C.bool(true), &errBuffer)
+	if err := tryExtractError(errBuffer); err != nil {
+		return 0, err
+	}
+	return uint64(count), nil
+}
+
+// Aggregate runs an aggregation request over the documents matching the query.
+// The request and the result use tantivy's aggregation JSON format
+// (terms, range, histogram, date_histogram, stats, ...).
+//
+// Parameters:
+//   - sCtx (SearchContext): The context for the search, containing query string and field weights.
+//   - aggregationJson (string): The aggregation request as JSON.
+//
+// Returns:
+//   - string: The aggregation results as JSON.
+//   - error: An error if the query or the aggregation request is invalid.
+func (tc *TantivyContext) Aggregate(sCtx SearchContext, aggregationJson string) (string, error) {
+	fieldNames, weights := sCtx.GetFieldAndWeights()
+	if len(fieldNames) == 0 {
+		return "", fmt.Errorf("fieldNames must not be empty")
+	}
+	cQuery := C.CString(sCtx.GetQuery())
+	defer C.string_free(cQuery)
+	cAggregation := C.CString(aggregationJson)
+	defer C.string_free(cAggregation)
+
+	fieldNamesPtr, err := tc.extractFields(fieldNames)
+	if err != nil {
+		return "", err
+	}
+
+	fieldWeightsPtr := make([]C.float, len(fieldNames))
+	for j, weight := range weights {
+		fieldWeightsPtr[j] = C.float(weight)
+	}
+
+	var errBuffer C.ErrorBuffer
+	cStr := C.context_aggregate(
+		tc.ptr,
+		searcherPtr(sCtx),
+		(*C.uint)(unsafe.Pointer(&fieldNamesPtr[0])),
+		(*C.float)(unsafe.Pointer(&fieldWeightsPtr[0])),
+		C.uintptr_t(len(fieldNames)),
+		cQuery,
+		C.bool(false),
+		cAggregation,
+		&errBuffer,
+	)
+	return aggregationResult(cStr, errBuffer)
+}
+
+// AggregateJson runs an aggregation request over the documents matching a JSON query.
+//
+// Parameters:
+//   - sCtx (SearchContext): The context for the search, containing the JSON query.
+//   - aggregationJson (string): The aggregation request as JSON.
+//
+// Returns:
+//   - string: The aggregation results as JSON.
+//   - error: An error if the query or the aggregation request is invalid.
+func (tc *TantivyContext) AggregateJson(sCtx SearchContext, aggregationJson string) (string, error) {
+	cQuery := C.CString(sCtx.GetQuery())
+	defer C.string_free(cQuery)
+	cAggregation := C.CString(aggregationJson)
+	defer C.string_free(cAggregation)
+
+	var errBuffer C.ErrorBuffer
+	cStr := C.context_aggregate(tc.ptr, searcherPtr(sCtx), nil, nil, 0, cQuery, C.bool(true), cAggregation, &errBuffer)
+	return aggregationResult(cStr, errBuffer)
+}
+
+func aggregationResult(cStr *C.char, errBuffer C.ErrorBuffer) (string, error) {
+	if cStr == nil {
+		if err := tryExtractError(errBuffer); err != nil {
+			return "", err
+		}
+		return "", errors.New("empty aggregation result")
+	}
+	defer C.string_free(cStr)
+
+	return C.GoString(cStr), nil
+}
+
 // Close waits till the merging operations are finished and releases all the resources held by the indexWriter
 func (tc *TantivyContext) Close() error {
 	ptr := tc.ptr
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	C.context_wait_and_free(ptr, &errBuffer)
 	return tryExtractError(errBuffer)
 }
@@ -343,7 +966,7 @@ func (tc *TantivyContext) Free() {
 func (tc *TantivyContext) RegisterTextAnalyzerNgram(tokenizerName string, minGram, maxGram uintptr, prefixOnly bool) error {
 	cTokenizerName := C.CString(tokenizerName)
 	defer C.string_free(cTokenizerName)
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	C.context_register_text_analyzer_ngram(tc.ptr, cTokenizerName, C.uintptr_t(minGram), C.uintptr_t(maxGram), C.bool(prefixOnly), &errBuffer)
 
 	return tryExtractError(errBuffer)
@@ -362,7 +985,7 @@ func (tc *TantivyContext) RegisterTextAnalyzerNgram(tokenizerName string, minGra
 func (tc *TantivyContext) RegisterTextAnalyzerEdgeNgram(tokenizerName string, minGram, maxGram uintptr, limit uintptr) error {
 	cTokenizerName := C.CString(tokenizerName)
 	defer C.string_free(cTokenizerName)
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	C.context_register_text_analyzer_edge_ngram(tc.ptr, cTokenizerName, C.uintptr_t(minGram), C.uintptr_t(maxGram), C.uintptr_t(limit), &errBuffer)
 	return tryExtractError(errBuffer)
 }
@@ -381,7 +1004,7 @@ func (tc *TantivyContext) RegisterTextAnalyzerSimple(tokenizerName string, textL
 	defer C.string_free(cTokenizerName)
 	cLang := C.CString(string(lang))
 	defer C.string_free(cLang)
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	C.context_register_text_analyzer_simple(tc.ptr, cTokenizerName, C.uintptr_t(textLimit), cLang, &errBuffer)
 
 	return tryExtractError(errBuffer)
@@ -398,7 +1021,7 @@ func (tc *TantivyContext) RegisterTextAnalyzerSimple(tokenizerName string, textL
 func (tc *TantivyContext) RegisterTextAnalyzerJieba(tokenizerName string, textLimit uintptr) error {
 	cTokenizerName := C.CString(tokenizerName)
 	defer C.string_free(cTokenizerName)
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	C.context_register_jieba_tokenizer(tc.ptr, cTokenizerName, C.uintptr_t(textLimit), &errBuffer)
 
 	return tryExtractError(errBuffer)
@@ -414,7 +1037,7 @@ func (tc *TantivyContext) RegisterTextAnalyzerJieba(tokenizerName string, textLi
 func (tc *TantivyContext) RegisterTextAnalyzerRaw(tokenizerName string) error {
 	cTokenizerName := C.CString(tokenizerName)
 	defer C.string_free(cTokenizerName)
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	C.context_register_text_analyzer_raw(tc.ptr, cTokenizerName, &errBuffer)
 
 	return tryExtractError(errBuffer)
@@ -477,6 +1100,109 @@ func (tc *TantivyContext) extractFields(fieldNames []string) ([]C.uint, error) {
 	return includeFieldsPtr, nil
 }
 
+// payloadCString returns nil when the commit has no payload.
+func payloadCString(payload string) *C.char {
+	if payload == "" {
+		return nil
+	}
+	return C.CString(payload)
+}
+
+// searchOptions mirrors the json search options of the native search calls.
+type searchOptions struct {
+	DocsLimit      uintptr          `json:"docs_limit"`
+	Offset         uintptr          `json:"offset,omitempty"`
+	Sort           []sortOption     `json:"sort,omitempty"`
+	SearchAfter    string           `json:"search_after,omitempty"`
+	Facets         *facetOptions    `json:"facets,omitempty"`
+	Collapse       *collapseOptions `json:"collapse,omitempty"`
+	WithHighlights bool             `json:"with_highlights,omitempty"`
+}
+
+type sortOption struct {
+	FieldId    uint32 `json:"field_id"`
+	Descending bool   `json:"descending,omitempty"`
+}
+
+type facetOptions struct {
+	FieldId  uint32   `json:"field_id"`
+	Prefixes []string `json:"prefixes"`
+}
+
+type collapseOptions struct {
+	FieldId      uint32  `json:"field_id"`
+	WithSiblings bool    `json:"with_siblings,omitempty"`
+	SiblingLimit uintptr `json:"sibling_limit,omitempty"`
+}
+
+// extractSearchOptions returns the paging, sorting, facet and collapse options of the search,
+// with the field names resolved against the schema.
+func (tc *TantivyContext) extractSearchOptions(sCtx SearchContext) (*searchOptions, error) {
+	options := &searchOptions{
+		DocsLimit:      sCtx.GetDocsLimit(),
+		Offset:         sCtx.GetOffset(),
+		SearchAfter:    sCtx.GetSearchAfter(),
+		WithHighlights: sCtx.WithHighlights(),
+	}
+
+	for _, sortField := range sCtx.GetSortFields() {
+		fieldId := uint32(C.SORT_BY_SCORE)
+		if !sortField.ByScore {
+			id, contains := tc.schema.fieldNames[sortField.FieldName]
+			if !contains {
+				return nil, errors.New("sort field not found in schema")
+			}
+			fieldId = uint32(id)
+		}
+		options.Sort = append(options.Sort, sortOption{FieldId: fieldId, Descending: sortField.Order == Desc})
+	}
+
+	if field, prefixes := sCtx.GetFacetCounts(); len(prefixes) > 0 {
+		fieldId, contains := tc.schema.fieldNames[field]
+		if !contains {
+			return nil, errors.New("facet field not found in schema")
+		}
+		options.Facets = &facetOptions{FieldId: uint32(fieldId), Prefixes: prefixes}
+	}
+
+	if collapse := sCtx.GetCollapse(); collapse != nil {
+		fieldId, contains := tc.schema.fieldNames[collapse.FieldName]
+		if !contains {
+			return nil, errors.New("collapse field not found in schema")
+		}
+		options.Collapse = &collapseOptions{
+			FieldId:      uint32(fieldId),
+			WithSiblings: collapse.WithSiblings,
+			SiblingLimit: collapse.SiblingLimit,
+		}
+	}
+	return options, nil
+}
+
+func (options *searchOptions) cString() (*C.char, error) {
+	optionsJson, err := json.Marshal(options)
+	if err != nil {
+		return nil, fmt.Errorf("failed to serialize search options: %w", err)
+	}
+	return C.CString(string(optionsJson)), nil
+}
+
+// LastCommitPayload returns the payload stored with the last commit, also after reopening the index.
+// It lets the caller store a sync checkpoint atomically with the indexed changes.
+//
+// Returns:
+//   - string: The payload, or an empty string if the last commit has none.
+//   - error: An error if reading the index meta fails.
+func (tc *TantivyContext) LastCommitPayload() (string, error) {
+	var errBuffer C.ErrorBuffer
+	ptr := C.context_last_commit_payload(tc.ptr, &errBuffer)
+	if ptr == nil {
+		return "", tryExtractError(errBuffer)
+	}
+	defer C.string_free(ptr)
+	return C.GoString(ptr), nil
+}
+
 // CommitOpstamp gets the opstamp of the last commit.
 //
 // Note: Due to a bug in Tantivy (https://github.com/quickwit-oss/tantivy/issues/2666),
@@ -489,18 +1215,106 @@ func (tc *TantivyContext) CommitOpstamp() uint64 {
 
 // ReloadReader forces the index reader to reload and check for new commits.
 //
-// Note: This method is called automatically during search operations (Search, SearchJson, NumDocs),
-// so manual calls are typically not necessary. The reader uses ReloadPolicy::Manual internally,
-// but reloading happens automatically when needed.
+// Note: With the default ReloadOnSearch policy this happens before every search, so manual calls
+// are only needed with ReloadManual, or to see a commit right away with ReloadOnCommitWithDelay.
 //
 // Returns:
 //   - error: An error if reloading the reader fails.
 func (tc *TantivyContext) ReloadReader() error {
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	C.context_reload_reader(tc.ptr, &errBuffer)
 	return tryExtractError(errBuffer)
 }
 
+// SetMergePolicy replaces the merge policy of the index writer.
+// It applies to the merges scheduled after the call.
+//
+// Parameters:
+//   - policy: The parameters of the log merge policy.
+//
+// Returns:
+//   - error: An error if the policy is invalid.
+func (tc *TantivyContext) SetMergePolicy(policy MergePolicy) error {
+	policyJson, err := json.Marshal(policy)
+	if err != nil {
+		return fmt.Errorf("failed to serialize merge policy: %w", err)
+	}
+	cPolicy := C.CString(string(policyJson))
+	defer C.string_free(cPolicy)
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+	var errBuffer C.ErrorBuffer
+	C.context_set_merge_policy(tc.ptr, cPolicy, &errBuffer)
+	return tryExtractError(errBuffer)
+}
+
+// PauseMerging stops scheduling background segment merges, for example while the app is in the background.
+// Running merges are not interrupted. SetMergePolicy applies once merging is resumed.
+//
+// Returns:
+//   - error: An error if pausing fails.
+func (tc *TantivyContext) PauseMerging() error {
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+	var errBuffer C.ErrorBuffer
+	C.context_pause_merging(tc.ptr, &errBuffer)
+	return tryExtractError(errBuffer)
+}
+
+// ResumeMerging schedules background segment merges again with the current merge policy.
+//
+// Returns:
+//   - error: An error if resuming fails.
+func (tc *TantivyContext) ResumeMerging() error {
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+	var errBuffer C.ErrorBuffer
+	C.context_resume_merging(tc.ptr, &errBuffer)
+	return tryExtractError(errBuffer)
+}
+
+// ForceMerge merges the smallest searchable segments so that at most targetSegments remain
+// and waits for the merge. It fails when a segment is being merged in the background, see PauseMerging.
+//
+// Parameters:
+//   - targetSegments: The maximum number of segments to keep, at least 1.
+//
+// Returns:
+//   - uint64: The number of searchable segments after the merge.
+//   - error: An error if merging fails.
+func (tc *TantivyContext) ForceMerge(targetSegments uint64) (uint64, error) {
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+	var errBuffer C.ErrorBuffer
+	numSegments := C.context_force_merge(tc.ptr, C.uintptr_t(targetSegments), &errBuffer)
+	err := tryExtractError(errBuffer)
+	if err != nil {
+		return 0, err
+	}
+	return uint64(numSegments), nil
+}
+
+// PurgeDeletes rewrites the searchable segments having at least deletedRatio of deleted documents
+// without them and waits for the merges.
+//
+// Parameters:
+//   - deletedRatio: The ratio of deleted documents of a segment to rewrite it, in (0, 1].
+//
+// Returns:
+//   - uint64: The number of rewritten segments.
+//   - error: An error if merging fails.
+func (tc *TantivyContext) PurgeDeletes(deletedRatio float32) (uint64, error) {
+	tc.lock.Lock()
+	defer tc.lock.Unlock()
+	var errBuffer C.ErrorBuffer
+	purged := C.context_purge_deletes(tc.ptr, C.float(deletedRatio), &errBuffer)
+	err := tryExtractError(errBuffer)
+	if err != nil {
+		return 0, err
+	}
+	return uint64(purged), nil
+}
+
 // GarbageCollectFiles performs garbage collection on unused index files.
 // This method removes files that were created by tantivy and are no longer
 // used by any segment.
@@ -509,7 +1323,7 @@ func (tc *TantivyContext) ReloadReader() error {
 //   - uint64: The number of files that were deleted.
 //   - error: An error if garbage collection fails.
 func (tc *TantivyContext) GarbageCollectFiles() (uint64, error) {
-	var errBuffer *C.char
+	var errBuffer C.ErrorBuffer
 	deletedCount := C.context_garbage_collect_files(tc.ptr, &errBuffer)
 	err := tryExtractError(errBuffer)
 	if err != nil {
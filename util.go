@@ -2,15 +2,16 @@ package tantivy_go
 
 // #include "bindings.h"
 import "C"
-import "fmt"
 
-func tryExtractError(errBuffer *C.char) error {
-	errorMessage := C.GoString(errBuffer)
-	defer C.string_free(errBuffer)
+func tryExtractError(errBuffer C.ErrorBuffer) error {
+	if errBuffer.message == nil {
+		return nil
+	}
+	errorMessage := C.GoString(errBuffer.message)
+	defer C.string_free(errBuffer.message)
 
 	if errorMessage == "" {
 		return nil
-	} else {
-		return fmt.Errorf(errorMessage)
 	}
+	return &Error{Code: ErrorCode(errBuffer.code), Message: errorMessage}
 }